@@ -283,6 +283,10 @@ pub fn from_fn_attrs<'ll, 'tcx>(
     to_add.extend(instrument_function_attr(cx));
     to_add.extend(probestack_attr(cx));
     to_add.extend(stackprotector_attr(cx));
+    if cx.sess().opts.debugging_opts.print_stack_usage {
+        // Makes LLVM report the frame size of every function that has a frame.
+        to_add.push(llvm::CreateAttrStringValue(cx.llcx, "warn-stack-size", "0"));
+    }
 
     if codegen_fn_attrs.flags.contains(CodegenFnAttrFlags::COLD) {
         to_add.push(AttributeKind::Cold.create_attr(cx.llcx));
@@ -0,0 +1,75 @@
+use crate::back::write::create_informational_target_machine;
+use crate::llvm;
+use crate::llvm_util;
+
+use rustc_codegen_ssa::back::verify_cpu::TargetDisassembler;
+use rustc_data_structures::small_c_str::SmallCStr;
+use rustc_session::Session;
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+/// `LLVMDisassembler_Option_AsmPrinterVariant`, which selects the Intel syntax on x86.
+const OPTION_ASM_PRINTER_VARIANT: u64 = 4;
+
+pub struct LlvmDisassembler {
+    context: &'static mut llvm::DisasmContext,
+    target_machine: &'static mut llvm::TargetMachine,
+}
+
+impl LlvmDisassembler {
+    pub fn new(sess: &Session) -> Option<LlvmDisassembler> {
+        let triple = SmallCStr::new(&sess.target.llvm_target);
+        let cpu = SmallCStr::new(llvm_util::target_cpu(sess));
+        let features = CString::new(llvm_util::global_llvm_features(sess, false).join(",")).ok()?;
+        unsafe {
+            let context = llvm::LLVMCreateDisasmCPUFeatures(
+                triple.as_ptr(),
+                cpu.as_ptr(),
+                features.as_ptr(),
+                ptr::null_mut(),
+                0,
+                None,
+                None,
+            )?;
+            llvm::LLVMSetDisasmOptions(context, OPTION_ASM_PRINTER_VARIANT);
+            let target_machine = create_informational_target_machine(sess);
+            Some(LlvmDisassembler { context, target_machine })
+        }
+    }
+}
+
+impl TargetDisassembler for LlvmDisassembler {
+    fn decode(&mut self, bytes: &[u8], address: u64) -> Option<(usize, String)> {
+        let mut text = [0 as libc::c_char; 256];
+        let len = unsafe {
+            llvm::LLVMDisasmInstruction(
+                self.context,
+                bytes.as_ptr(),
+                bytes.len() as u64,
+                address,
+                text.as_mut_ptr(),
+                text.len(),
+            )
+        };
+        if len == 0 {
+            return None;
+        }
+        let text = unsafe { CStr::from_ptr(text.as_ptr()) };
+        Some((len, text.to_string_lossy().into_owned()))
+    }
+
+    fn has_feature(&self, feature: &str) -> bool {
+        let feature = SmallCStr::new(feature);
+        unsafe { llvm::LLVMRustHasFeature(self.target_machine, feature.as_ptr()) }
+    }
+}
+
+impl Drop for LlvmDisassembler {
+    fn drop(&mut self) {
+        unsafe {
+            llvm::LLVMDisasmDispose(&mut *(self.context as *mut _));
+            llvm::LLVMRustDisposeTargetMachine(&mut *(self.target_machine as *mut _));
+        }
+    }
+}
@@ -380,6 +380,14 @@ fn report_inline_asm(
                 ));
             }
         }
+        llvm::diagnostic::StackSize(stack_size) => {
+            if let Some(frame_sizes) = &cgcx.frame_sizes {
+                let name = String::from_utf8_lossy(llvm::get_value_name(stack_size.function));
+                let mut frame_sizes = frame_sizes.lock().unwrap();
+                let frame_size = frame_sizes.entry(name.into_owned()).or_default();
+                *frame_size = (*frame_size).max(stack_size.size);
+            }
+        }
         llvm::diagnostic::PGO(diagnostic_ref) | llvm::diagnostic::Linker(diagnostic_ref) => {
             let msg = llvm::build_string(|s| {
                 llvm::LLVMRustWriteDiagnosticInfoToString(diagnostic_ref, s)
@@ -752,6 +760,18 @@ pub(crate) unsafe fn codegen(
             create_msvc_imps(cgcx, llcx, llmod);
         }
 
+        if let Some(frame_sizes) = &cgcx.frame_sizes {
+            // LLVM only reports the frames that aren't empty, so every function that is compiled
+            // starts out with a frame size of zero.
+            let mut frame_sizes = frame_sizes.lock().unwrap();
+            for function in base::iter_functions(llmod) {
+                if llvm::LLVMIsDeclaration(function) == 0 {
+                    let name = String::from_utf8_lossy(llvm::get_value_name(function));
+                    frame_sizes.entry(name.into_owned()).or_default();
+                }
+            }
+        }
+
         // A codegen-specific pass manager is used to generate object
         // files for an LLVM module.
         //
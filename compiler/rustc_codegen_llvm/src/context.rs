@@ -9,6 +9,7 @@
 use crate::value::Value;
 
 use cstr::cstr;
+use libc::c_uint;
 use rustc_codegen_ssa::base::wants_msvc_seh;
 use rustc_codegen_ssa::traits::*;
 use rustc_data_structures::base_n;
@@ -29,7 +30,7 @@
 use rustc_target::abi::{
     call::FnAbi, HasDataLayout, PointeeInfo, Size, TargetDataLayout, VariantIdx,
 };
-use rustc_target::spec::{HasTargetSpec, RelocModel, Target, TlsModel};
+use rustc_target::spec::{HasTargetSpec, MsvcCxxPersonality, RelocModel, Target, TlsModel};
 use smallvec::SmallVec;
 
 use std::cell::{Cell, RefCell};
@@ -498,6 +499,10 @@ fn eh_personality(&self) -> &'ll Value {
         // `rust_eh_personality` function, but rather we wired it up to the
         // CRT's custom personality function, which forces LLVM to consider
         // landing pads as "landing pads for SEH".
+        //
+        // LLVM only recognizes `__CxxFrameHandler3` as a C++ SEH personality, so
+        // that is the name used in the IR even if the target asks for another
+        // personality; see `redirect_msvc_cxx_personality`.
         if let Some(llpersonality) = self.eh_personality.get() {
             return llpersonality;
         }
@@ -530,6 +535,9 @@ fn eh_personality(&self) -> &'ll Value {
                 }
             }
         };
+        if wants_msvc_seh(self.sess()) {
+            self.redirect_msvc_cxx_personality();
+        }
         self.eh_personality.set(Some(llfn));
         llfn
     }
@@ -591,6 +599,34 @@ fn declare_c_main(&self, fn_type: Self::Type) -> Option<Self::Function> {
 }
 
 impl<'ll> CodegenCx<'ll, '_> {
+    /// Makes references to `__CxxFrameHandler3` from this module resolve to the personality
+    /// selected by the target, by adding an `/ALTERNATENAME` directive to the object file.
+    fn redirect_msvc_cxx_personality(&self) {
+        let personality = self.sess().target.msvc_cxx_personality;
+        if personality == MsvcCxxPersonality::FrameHandler3 {
+            return;
+        }
+        // C symbols are decorated with a leading underscore on 32-bit x86.
+        let prefix = if self.sess().target.arch == "x86" { "_" } else { "" };
+        let directive = format!(
+            "/ALTERNATENAME:{prefix}{}={prefix}{}",
+            MsvcCxxPersonality::FrameHandler3.symbol_name(),
+            personality.symbol_name(),
+        );
+        unsafe {
+            let option = llvm::LLVMMDStringInContext(
+                self.llcx,
+                directive.as_ptr().cast(),
+                directive.len() as c_uint,
+            );
+            llvm::LLVMAddNamedMetadataOperand(
+                self.llmod,
+                "llvm.linker.options\0".as_ptr().cast(),
+                llvm::LLVMMDNodeInContext(self.llcx, &option, 1),
+            );
+        }
+    }
+
     crate fn get_intrinsic(&self, key: &str) -> (&'ll Type, &'ll Value) {
         if let Some(v) = self.intrinsics.borrow().get(key).cloned() {
             return v;
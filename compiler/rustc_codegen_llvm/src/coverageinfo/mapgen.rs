@@ -71,11 +71,16 @@ pub fn finalize<'ll, 'tcx>(cx: &CodegenCx<'ll, 'tcx>) {
         let mangled_function_name = tcx.symbol_name(instance).to_string();
         let source_hash = function_coverage.source_hash();
         let is_used = function_coverage.is_used();
-        let (expressions, counter_regions) =
+        let (expressions, counter_regions, branch_regions) =
             function_coverage.get_expressions_and_counter_regions();
 
         let coverage_mapping_buffer = llvm::build_byte_buffer(|coverage_mapping_buffer| {
-            mapgen.write_coverage_mapping(expressions, counter_regions, coverage_mapping_buffer);
+            mapgen.write_coverage_mapping(
+                expressions,
+                counter_regions,
+                branch_regions,
+                coverage_mapping_buffer,
+            );
         });
 
         if coverage_mapping_buffer.is_empty() {
@@ -147,17 +152,25 @@ fn new(tcx: TyCtxt<'_>, version: u32) -> Self {
         Self { filenames }
     }
 
-    /// Using the `expressions` and `counter_regions` collected for the current function, generate
-    /// the `mapping_regions` and `virtual_file_mapping`, and capture any new filenames. Then use
-    /// LLVM APIs to encode the `virtual_file_mapping`, `expressions`, and `mapping_regions` into
-    /// the given `coverage_mapping` byte buffer, compliant with the LLVM Coverage Mapping format.
+    /// Using the `expressions`, `counter_regions`, and `branch_regions` collected for the current
+    /// function, generate the `mapping_regions` and `virtual_file_mapping`, and capture any new
+    /// filenames. Then use LLVM APIs to encode the `virtual_file_mapping`, `expressions`, and
+    /// `mapping_regions` into the given `coverage_mapping` byte buffer, compliant with the LLVM
+    /// Coverage Mapping format.
     fn write_coverage_mapping<'a>(
         &mut self,
         expressions: Vec<CounterExpression>,
         counter_regions: impl Iterator<Item = (Counter, &'a CodeRegion)>,
+        branch_regions: impl Iterator<Item = (Counter, Counter, &'a CodeRegion)>,
         coverage_mapping_buffer: &RustString,
     ) {
-        let mut counter_regions = counter_regions.collect::<Vec<_>>();
+        // A region with a `false_counter` is a branch region.
+        let mut counter_regions = counter_regions
+            .map(|(counter, region)| (counter, None, region))
+            .chain(branch_regions.map(|(true_counter, false_counter, region)| {
+                (true_counter, Some(false_counter), region)
+            }))
+            .collect::<Vec<_>>();
         if counter_regions.is_empty() {
             return;
         }
@@ -172,8 +185,8 @@ fn write_coverage_mapping<'a>(
         // `file_id` (indexing files referenced by the current function), and construct the
         // function-specific `virtual_file_mapping` from `file_id` to its index in the module's
         // `filenames` array.
-        counter_regions.sort_unstable_by_key(|(_counter, region)| *region);
-        for (counter, region) in counter_regions {
+        counter_regions.sort_unstable_by_key(|(_counter, _false_counter, region)| *region);
+        for (counter, false_counter, region) in counter_regions {
             let CodeRegion { file_name, start_line, start_col, end_line, end_col } = *region;
             let same_file = current_file_name.as_ref().map_or(false, |p| *p == file_name);
             if !same_file {
@@ -187,15 +200,31 @@ fn write_coverage_mapping<'a>(
                 let (filenames_index, _) = self.filenames.insert_full(c_filename);
                 virtual_file_mapping.push(filenames_index as u32);
             }
-            debug!("Adding counter {:?} to map for {:?}", counter, region);
-            mapping_regions.push(CounterMappingRegion::code_region(
-                counter,
-                current_file_id,
-                start_line,
-                start_col,
-                end_line,
-                end_col,
-            ));
+            if let Some(false_counter) = false_counter {
+                debug!(
+                    "Adding branch counters {:?} and {:?} to map for {:?}",
+                    counter, false_counter, region
+                );
+                mapping_regions.push(CounterMappingRegion::branch_region(
+                    counter,
+                    false_counter,
+                    current_file_id,
+                    start_line,
+                    start_col,
+                    end_line,
+                    end_col,
+                ));
+            } else {
+                debug!("Adding counter {:?} to map for {:?}", counter, region);
+                mapping_regions.push(CounterMappingRegion::code_region(
+                    counter,
+                    current_file_id,
+                    start_line,
+                    start_col,
+                    end_line,
+                    end_col,
+                ));
+            }
         }
 
         // Encode and append the current function's coverage mapping data
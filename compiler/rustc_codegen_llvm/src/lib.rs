@@ -24,6 +24,7 @@
 pub use llvm_util::target_features;
 use rustc_ast::expand::allocator::AllocatorKind;
 use rustc_codegen_ssa::back::lto::{LtoModuleCodegen, SerializedModule, ThinModule};
+use rustc_codegen_ssa::back::verify_cpu::TargetDisassembler;
 use rustc_codegen_ssa::back::write::{
     CodegenContext, FatLTOInput, ModuleConfig, TargetMachineFactoryConfig, TargetMachineFactoryFn,
 };
@@ -45,6 +46,7 @@
 
 mod back {
     pub mod archive;
+    pub mod disasm;
     pub mod lto;
     mod profiling;
     pub mod write;
@@ -139,6 +141,10 @@ fn target_cpu<'b>(&self, sess: &'b Session) -> &'b str {
     fn tune_cpu<'b>(&self, sess: &'b Session) -> Option<&'b str> {
         llvm_util::tune_cpu(sess)
     }
+    fn target_disassembler(&self, sess: &Session) -> Option<Box<dyn TargetDisassembler>> {
+        let disassembler = back::disasm::LlvmDisassembler::new(sess)?;
+        Some(Box::new(disassembler))
+    }
 
     fn spawn_thread<F, T>(time_trace: bool, f: F) -> std::thread::JoinHandle<T>
     where
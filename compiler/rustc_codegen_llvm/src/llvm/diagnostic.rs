@@ -87,6 +87,22 @@ unsafe fn unpack(kind: OptimizationDiagnosticKind, di: &'ll DiagnosticInfo) -> S
     }
 }
 
+/// The frame size of a function, which LLVM reports for functions with a
+/// `warn-stack-size` attribute lower than that.
+pub struct StackSizeDiagnostic<'ll> {
+    pub function: &'ll Value,
+    pub size: u64,
+}
+
+impl<'ll> StackSizeDiagnostic<'ll> {
+    unsafe fn unpack(di: &'ll DiagnosticInfo) -> Self {
+        let mut function = None;
+        let mut size = 0;
+        super::LLVMRustUnpackStackSizeDiagnostic(di, &mut function, &mut size);
+        StackSizeDiagnostic { function: function.unwrap(), size }
+    }
+}
+
 pub struct SrcMgrDiagnostic {
     pub level: super::DiagnosticLevel,
     pub message: String,
@@ -172,6 +188,7 @@ unsafe fn unpackSrcMgr(di: &DiagnosticInfo) -> Self {
 pub enum Diagnostic<'ll> {
     Optimization(OptimizationDiagnostic<'ll>),
     InlineAsm(InlineAsmDiagnostic),
+    StackSize(StackSizeDiagnostic<'ll>),
     PGO(&'ll DiagnosticInfo),
     Linker(&'ll DiagnosticInfo),
     Unsupported(&'ll DiagnosticInfo),
@@ -188,6 +205,8 @@ pub unsafe fn unpack(di: &'ll DiagnosticInfo) -> Self {
         match kind {
             Dk::InlineAsm => InlineAsm(InlineAsmDiagnostic::unpackInlineAsm(di)),
 
+            Dk::StackSize => StackSize(StackSizeDiagnostic::unpack(di)),
+
             Dk::OptimizationRemark => {
                 Optimization(OptimizationDiagnostic::unpack(OptimizationRemark, di))
             }
@@ -670,6 +670,9 @@ struct InvariantOpaque<'a> {
 extern "C" {
     pub type TargetMachine;
 }
+extern "C" {
+    pub type DisasmContext;
+}
 extern "C" {
     pub type Archive;
 }
@@ -796,9 +799,6 @@ impl CounterMappingRegion {
             }
         }
 
-        // This function might be used in the future; the LLVM API is still evolving, as is coverage
-        // support.
-        #[allow(dead_code)]
         crate fn branch_region(
             counter: coverage_map::Counter,
             false_counter: coverage_map::Counter,
@@ -1159,6 +1159,8 @@ pub fn LLVMRustGetOrInsertGlobal<'a>(
     pub fn LLVMRustInsertPrivateGlobal<'a>(M: &'a Module, T: &'a Type) -> &'a Value;
     pub fn LLVMGetFirstGlobal(M: &Module) -> Option<&Value>;
     pub fn LLVMGetNextGlobal(GlobalVar: &Value) -> Option<&Value>;
+    pub fn LLVMGetFirstFunction(M: &Module) -> Option<&Value>;
+    pub fn LLVMGetNextFunction(Fn: &Value) -> Option<&Value>;
     pub fn LLVMDeleteGlobal(GlobalVar: &Value);
     pub fn LLVMGetInitializer(GlobalVar: &Value) -> Option<&Value>;
     pub fn LLVMSetInitializer<'a>(GlobalVar: &'a Value, ConstantVal: &'a Value);
@@ -2262,6 +2264,26 @@ pub fn LLVMRustAddLastExtensionPasses(
 
     pub fn LLVMRustHasFeature(T: &TargetMachine, s: *const c_char) -> bool;
 
+    pub fn LLVMCreateDisasmCPUFeatures(
+        Triple: *const c_char,
+        CPU: *const c_char,
+        Features: *const c_char,
+        DisInfo: *mut c_void,
+        TagType: c_int,
+        GetOpInfo: Option<unsafe extern "C" fn()>,
+        SymbolLookUp: Option<unsafe extern "C" fn()>,
+    ) -> Option<&'static mut DisasmContext>;
+    pub fn LLVMSetDisasmOptions(DC: &DisasmContext, Options: u64) -> c_int;
+    pub fn LLVMDisasmInstruction(
+        DC: &DisasmContext,
+        Bytes: *const u8,
+        BytesSize: u64,
+        PC: u64,
+        OutString: *mut c_char,
+        OutStringSize: size_t,
+    ) -> size_t;
+    pub fn LLVMDisasmDispose(DC: &'static mut DisasmContext);
+
     pub fn LLVMRustPrintTargetCPUs(T: &TargetMachine);
     pub fn LLVMRustGetTargetFeaturesCount(T: &TargetMachine) -> size_t;
     pub fn LLVMRustGetTargetFeature(
@@ -2389,6 +2411,12 @@ pub fn LLVMRustUnpackOptimizationDiagnostic<'a>(
         message_out: &RustString,
     );
 
+    pub fn LLVMRustUnpackStackSizeDiagnostic<'a>(
+        DI: &'a DiagnosticInfo,
+        function_out: &mut Option<&'a Value>,
+        size_out: &mut u64,
+    );
+
     pub fn LLVMRustUnpackInlineAsmDiagnostic<'a>(
         DI: &'a DiagnosticInfo,
         level_out: &mut DiagnosticLevel,
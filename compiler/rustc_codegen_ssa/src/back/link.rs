@@ -5,8 +5,9 @@
 use rustc_data_structures::temp_dir::MaybeTempDir;
 use rustc_errors::{ErrorGuaranteed, Handler};
 use rustc_fs_util::fix_windows_verbatim_for_gcc;
-use rustc_hir::def_id::CrateNum;
+use rustc_hir::def_id::{CrateNum, LOCAL_CRATE};
 use rustc_middle::middle::dependency_format::Linkage;
+use rustc_session::config::SwitchWithOptPath;
 use rustc_session::config::{self, CFGuard, CrateType, DebugInfo, LdImpl, Strip};
 use rustc_session::config::{OutputFilenames, OutputType, PrintRequest, SplitDwarfKind};
 use rustc_session::cstore::DllImport;
@@ -23,6 +24,7 @@
 
 use super::archive::{find_library, ArchiveBuilder};
 use super::command::Command;
+use super::link_plan::{self, NativeLibAction, NativeLibRecorder};
 use super::linker::{self, Linker};
 use super::metadata::{create_rmeta_file, MetadataPosition};
 use super::rpath::{self, RPathConfig};
@@ -663,6 +665,7 @@ fn link_natively<'a, B: ArchiveBuilder<'a>>(
 ) {
     info!("preparing {:?} to {:?}", crate_type, out_filename);
     let (linker_path, flavor) = linker_and_flavor(sess);
+    let mut native_libs = NativeLibRecorder::new(sess);
     let mut cmd = linker_with_args::<B>(
         &linker_path,
         flavor,
@@ -671,6 +674,7 @@ fn link_natively<'a, B: ArchiveBuilder<'a>>(
         tmpdir,
         out_filename,
         codegen_results,
+        &mut native_libs,
     );
 
     linker::disable_localization(&mut cmd);
@@ -685,6 +689,17 @@ fn link_natively<'a, B: ArchiveBuilder<'a>>(
     if sess.opts.prints.contains(&PrintRequest::LinkArgs) {
         println!("{:?}", &cmd);
     }
+    if sess.opts.prints.contains(&PrintRequest::LinkPlan) {
+        link_plan::print_link_plan(
+            sess,
+            crate_type,
+            out_filename,
+            flavor,
+            &cmd,
+            codegen_results,
+            native_libs,
+        );
+    }
 
     // May have not found libraries in the right formats.
     sess.abort_if_errors();
@@ -1480,7 +1495,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
-fn link_output_kind(sess: &Session, crate_type: CrateType) -> LinkOutputKind {
+pub(super) fn link_output_kind(sess: &Session, crate_type: CrateType) -> LinkOutputKind {
     let kind = match (crate_type, sess.crt_static(Some(crate_type)), sess.relocation_model()) {
         (CrateType::Executable, _, _) if sess.is_wasi_reactor() => LinkOutputKind::WasiReactorExe,
         (CrateType::Executable, false, RelocModel::Pic | RelocModel::Pie) => {
@@ -1532,7 +1547,7 @@ fn detect_self_contained_mingw(sess: &Session) -> bool {
 
 /// Whether we link to our own CRT objects instead of relying on gcc to pull them.
 /// We only provide such support for a very limited number of targets.
-fn crt_objects_fallback(sess: &Session, crate_type: CrateType) -> bool {
+pub(super) fn crt_objects_fallback(sess: &Session, crate_type: CrateType) -> bool {
     if let Some(self_contained) = sess.opts.cg.link_self_contained {
         return self_contained;
     }
@@ -1752,6 +1767,8 @@ fn add_rpath_args(
 /// to the linking process as a whole.
 /// Order-independent options may still override each other in order-dependent fashion,
 /// e.g `--foo=yes --foo=no` may be equivalent to `--foo=no`.
+///
+/// What happens to each native library is reported to `native_libs`.
 fn linker_with_args<'a, B: ArchiveBuilder<'a>>(
     path: &Path,
     flavor: LinkerFlavor,
@@ -1760,6 +1777,7 @@ fn linker_with_args<'a, B: ArchiveBuilder<'a>>(
     tmpdir: &Path,
     out_filename: &Path,
     codegen_results: &CodegenResults,
+    native_libs: &mut NativeLibRecorder,
 ) -> Command {
     let crt_objects_fallback = crt_objects_fallback(sess, crate_type);
     let cmd = &mut *super::linker::get_linker(
@@ -1845,11 +1863,14 @@ fn linker_with_args<'a, B: ArchiveBuilder<'a>>(
     // This change is somewhat breaking in practice due to local static libraries being linked
     // as whole-archive (#85144), so removing whole-archive may be a pre-requisite.
     if sess.opts.debugging_opts.link_native_libraries {
-        add_local_native_libraries(cmd, sess, codegen_results, crate_type);
+        add_local_native_libraries(cmd, sess, codegen_results, crate_type, native_libs);
+    } else {
+        let libs = &codegen_results.crate_info.used_libraries;
+        native_libs.skip_all(LOCAL_CRATE, libs, "-Z link-native-libraries=no");
     }
 
     // Upstream rust libraries and their nobundle static libraries
-    add_upstream_rust_crates::<B>(cmd, sess, codegen_results, crate_type, tmpdir);
+    add_upstream_rust_crates::<B>(cmd, sess, codegen_results, crate_type, tmpdir, native_libs);
 
     // Upstream dynamic native libraries linked with `#[link]` attributes at and `-l`
     // command line options.
@@ -1857,7 +1878,14 @@ fn linker_with_args<'a, B: ArchiveBuilder<'a>>(
     // external build system already has the native dependencies defined, and it
     // will provide them to the linker itself.
     if sess.opts.debugging_opts.link_native_libraries {
-        add_upstream_native_libraries(cmd, sess, codegen_results);
+        add_upstream_native_libraries(cmd, sess, codegen_results, native_libs);
+    } else {
+        for &cnum in &codegen_results.crate_info.used_crates {
+            let libs = codegen_results.crate_info.native_libraries[&cnum]
+                .iter()
+                .filter(|lib| !matches!(lib.kind, NativeLibKind::Static { .. }));
+            native_libs.skip_all(cnum, libs, "-Z link-native-libraries=no");
+        }
     }
 
     // Library linking above uses some global state for things like `-Bstatic`/`-Bdynamic` to make
@@ -2014,9 +2042,34 @@ fn add_order_independent_options(
         cmd.control_flow_guard();
     }
 
+    if sess.target.is_like_windows {
+        add_pe_image_args(cmd, sess);
+    }
+
+    if let SwitchWithOptPath::Enabled(ref path) = sess.opts.cg.link_map {
+        match path {
+            Some(path) => cmd.link_map(path),
+            None => cmd.link_map(&out_filename.with_extension("map")),
+        }
+    }
+
     add_rpath_args(cmd, sess, codegen_results, out_filename);
 }
 
+/// Passes the PE header settings (`-C image-base`, `-C stack-size` and
+/// `-C large-address-aware`) down to the linker.
+fn add_pe_image_args(cmd: &mut dyn Linker, sess: &Session) {
+    if let Some(base) = sess.opts.cg.image_base {
+        cmd.image_base(base);
+    }
+    if let Some(stack_size) = sess.opts.cg.stack_size {
+        cmd.stack_size(stack_size.reserve, stack_size.commit);
+    }
+    if let Some(enabled) = sess.opts.cg.large_address_aware {
+        cmd.large_address_aware(enabled);
+    }
+}
+
 // A dylib may reexport symbols from the linked rlib or native static library.
 // Even if some symbol is reexported it's still not necessarily counted as used and may be
 // dropped, at least with `ld`-like ELF linkers. So we have to link some rlibs and static
@@ -2032,6 +2085,19 @@ fn default_to_whole_archive(sess: &Session, crate_type: CrateType, cmd: &dyn Lin
     "winmm", "winspool", "vfw32", "secur32", "oleacc", "oledlg", "sensapi",
 ];
 
+// Reasons for leaving a native library out, as reported in `--print link-plan`.
+const CFG_MISMATCH: &str = "cfg does not match";
+const SAME_AS_PREVIOUS: &str = "same as the previous library";
+const UNICOWS_WRAPPED: &str = "wrapped by unicows";
+const NOT_LINKED_STATICALLY: &str = "crate is not linked statically";
+
+/// Libraries that are wrapped by `unicows.lib` on rust9x targets. They are not added as regular
+/// native libraries so that `unicows.lib` can be placed in front of them.
+fn is_unicows_wrapped(sess: &Session, lib: &NativeLib) -> bool {
+    sess.target.vendor == "rust9x"
+        && lib.name.map_or(false, |name| UNICOWS_LIBS.contains(&name.as_str()))
+}
+
 /// # Native library linking
 ///
 /// User-supplied library search paths (-L on the command line). These are the same paths used to
@@ -2046,9 +2112,8 @@ fn add_local_native_libraries(
     sess: &Session,
     codegen_results: &CodegenResults,
     crate_type: CrateType,
+    native_libs: &mut NativeLibRecorder,
 ) {
-    let is_target_oldpc = sess.target.vendor == "rust9x";
-
     let filesearch = sess.target_filesearch(PathKind::All);
     for search_path in filesearch.search_paths() {
         match search_path.kind {
@@ -2061,38 +2126,46 @@ fn add_local_native_libraries(
         }
     }
 
-    let relevant_libs =
-        codegen_results.crate_info.used_libraries.iter().filter(|l| relevant_lib(sess, l));
-
     let search_path = OnceCell::new();
     let mut last = (None, NativeLibKind::Unspecified, None);
-    for lib in relevant_libs {
+    for lib in &codegen_results.crate_info.used_libraries {
         let Some(name) = lib.name else {
             continue;
         };
+        if !relevant_lib(sess, lib) {
+            native_libs.record(LOCAL_CRATE, lib, NativeLibAction::Skip(CFG_MISMATCH));
+            continue;
+        }
 
         // Skip if this library is the same as the last.
         last = if (lib.name, lib.kind, lib.verbatim) == last {
+            native_libs.record(LOCAL_CRATE, lib, NativeLibAction::Skip(SAME_AS_PREVIOUS));
             continue;
         } else {
             (lib.name, lib.kind, lib.verbatim)
         };
 
-        if is_target_oldpc && UNICOWS_LIBS.contains(&name.as_str()) {
+        if is_unicows_wrapped(sess, lib) {
             // skip adding unicows-wrapped libraries in order to properly support adding
             // `unicows.lib` before them
+            native_libs.record(LOCAL_CRATE, lib, NativeLibAction::Skip(UNICOWS_WRAPPED));
             continue;
         }
 
         let verbatim = lib.verbatim.unwrap_or(false);
 
-        match lib.kind {
+        let action = match lib.kind {
             NativeLibKind::Dylib { as_needed } => {
-                cmd.link_dylib(name, verbatim, as_needed.unwrap_or(true))
+                cmd.link_dylib(name, verbatim, as_needed.unwrap_or(true));
+                NativeLibAction::Link
+            }
+            NativeLibKind::Unspecified => {
+                cmd.link_dylib(name, verbatim, true);
+                NativeLibAction::Link
             }
-            NativeLibKind::Unspecified => cmd.link_dylib(name, verbatim, true),
             NativeLibKind::Framework { as_needed } => {
-                cmd.link_framework(name, as_needed.unwrap_or(true))
+                cmd.link_framework(name, as_needed.unwrap_or(true));
+                NativeLibAction::Link
             }
             NativeLibKind::Static { whole_archive, bundle, .. } => {
                 if whole_archive == Some(true)
@@ -2108,15 +2181,18 @@ fn add_local_native_libraries(
                         verbatim,
                         &search_path.get_or_init(|| archive_search_paths(sess)),
                     );
+                    NativeLibAction::LinkWholeArchive
                 } else {
-                    cmd.link_staticlib(name, verbatim)
+                    cmd.link_staticlib(name, verbatim);
+                    NativeLibAction::Link
                 }
             }
             NativeLibKind::RawDylib => {
                 // FIXME(#58713): Proper handling for raw dylibs.
                 bug!("raw_dylib feature not yet implemented");
             }
-        }
+        };
+        native_libs.record(LOCAL_CRATE, lib, action);
     }
 }
 
@@ -2130,6 +2206,7 @@ fn add_upstream_rust_crates<'a, B: ArchiveBuilder<'a>>(
     codegen_results: &CodegenResults,
     crate_type: CrateType,
     tmpdir: &Path,
+    native_libs: &mut NativeLibRecorder,
 ) {
     // All of the heavy lifting has previously been accomplished by the
     // dependency_format module of the compiler. This is just crawling the
@@ -2213,6 +2290,10 @@ fn add_upstream_rust_crates<'a, B: ArchiveBuilder<'a>>(
         // appear statically in an existing dylib, meaning we'll pick up all the
         // symbols from the dylib.
         let src = &codegen_results.crate_info.used_crate_source[&cnum];
+        let crate_native_libs = &codegen_results.crate_info.native_libraries[&cnum];
+        let nobundle_libs = crate_native_libs
+            .iter()
+            .filter(|lib| matches!(lib.kind, NativeLibKind::Static { bundle: Some(false), .. }));
         match data[cnum.as_usize() - 1] {
             _ if codegen_results.crate_info.profiler_runtime == Some(cnum) => {
                 add_static_crate::<B>(cmd, sess, codegen_results, tmpdir, crate_type, cnum);
@@ -2223,9 +2304,16 @@ fn add_upstream_rust_crates<'a, B: ArchiveBuilder<'a>>(
                 assert!(compiler_builtins.is_none());
                 compiler_builtins = Some(cnum);
             }
-            Linkage::NotLinked | Linkage::IncludedFromDylib => {}
+            Linkage::NotLinked | Linkage::IncludedFromDylib => {
+                native_libs.skip_all(cnum, nobundle_libs, NOT_LINKED_STATICALLY);
+            }
             Linkage::Static => {
                 add_static_crate::<B>(cmd, sess, codegen_results, tmpdir, crate_type, cnum);
+                for lib in crate_native_libs {
+                    if let NativeLibKind::Static { bundle: None | Some(true), .. } = lib.kind {
+                        native_libs.record(cnum, lib, NativeLibAction::Bundled);
+                    }
+                }
 
                 // Link static native libs with "-bundle" modifier only if the crate they originate from
                 // is being linked statically to the current crate.  If it's linked dynamically
@@ -2237,42 +2325,56 @@ fn add_upstream_rust_crates<'a, B: ArchiveBuilder<'a>>(
                 // will provide them to the linker itself.
                 if sess.opts.debugging_opts.link_native_libraries {
                     let mut last = (None, NativeLibKind::Unspecified, None);
-                    for lib in &codegen_results.crate_info.native_libraries[&cnum] {
+                    for lib in crate_native_libs {
                         let Some(name) = lib.name else {
                             continue;
                         };
+                        let NativeLibKind::Static { bundle: Some(false), whole_archive } = lib.kind
+                        else {
+                            // Other libraries are not linked here, but still count as the last
+                            // library.
+                            if relevant_lib(sess, lib) {
+                                last = (lib.name, lib.kind, lib.verbatim);
+                            }
+                            continue;
+                        };
                         if !relevant_lib(sess, lib) {
+                            native_libs.record(cnum, lib, NativeLibAction::Skip(CFG_MISMATCH));
                             continue;
                         }
 
                         // Skip if this library is the same as the last.
                         last = if (lib.name, lib.kind, lib.verbatim) == last {
+                            native_libs.record(cnum, lib, NativeLibAction::Skip(SAME_AS_PREVIOUS));
                             continue;
                         } else {
                             (lib.name, lib.kind, lib.verbatim)
                         };
 
-                        if let NativeLibKind::Static { bundle: Some(false), whole_archive } =
-                            lib.kind
+                        let verbatim = lib.verbatim.unwrap_or(false);
+                        if whole_archive == Some(true)
+                            || (whole_archive == None
+                                && default_to_whole_archive(sess, crate_type, cmd))
                         {
-                            let verbatim = lib.verbatim.unwrap_or(false);
-                            if whole_archive == Some(true)
-                                || (whole_archive == None
-                                    && default_to_whole_archive(sess, crate_type, cmd))
-                            {
-                                cmd.link_whole_staticlib(
-                                    name,
-                                    verbatim,
-                                    search_path.get_or_init(|| archive_search_paths(sess)),
-                                );
-                            } else {
-                                cmd.link_staticlib(name, verbatim);
-                            }
+                            cmd.link_whole_staticlib(
+                                name,
+                                verbatim,
+                                search_path.get_or_init(|| archive_search_paths(sess)),
+                            );
+                            native_libs.record(cnum, lib, NativeLibAction::LinkWholeArchive);
+                        } else {
+                            cmd.link_staticlib(name, verbatim);
+                            native_libs.record(cnum, lib, NativeLibAction::Link);
                         }
                     }
+                } else {
+                    native_libs.skip_all(cnum, nobundle_libs, "-Z link-native-libraries=no");
                 }
             }
-            Linkage::Dynamic => add_dynamic_crate(cmd, sess, &src.dylib.as_ref().unwrap().0),
+            Linkage::Dynamic => {
+                add_dynamic_crate(cmd, sess, &src.dylib.as_ref().unwrap().0);
+                native_libs.skip_all(cnum, nobundle_libs, NOT_LINKED_STATICALLY);
+            }
         }
 
         if group_end == Some(cnum) {
@@ -2437,29 +2539,39 @@ fn add_upstream_native_libraries(
     cmd: &mut dyn Linker,
     sess: &Session,
     codegen_results: &CodegenResults,
+    native_libs: &mut NativeLibRecorder,
 ) {
-    let is_target_oldpc = sess.target.vendor == "rust9x";
-
     let mut last = (None, NativeLibKind::Unspecified, None);
     for &cnum in &codegen_results.crate_info.used_crates {
         for lib in codegen_results.crate_info.native_libraries[&cnum].iter() {
             let Some(name) = lib.name else {
                 continue;
             };
+            // Static libraries are reported by `add_upstream_rust_crates`.
+            let is_static = matches!(lib.kind, NativeLibKind::Static { .. });
             if !relevant_lib(sess, &lib) {
+                if !is_static {
+                    native_libs.record(cnum, lib, NativeLibAction::Skip(CFG_MISMATCH));
+                }
                 continue;
             }
 
             // Skip if this library is the same as the last.
             last = if (lib.name, lib.kind, lib.verbatim) == last {
+                if !is_static {
+                    native_libs.record(cnum, lib, NativeLibAction::Skip(SAME_AS_PREVIOUS));
+                }
                 continue;
             } else {
                 (lib.name, lib.kind, lib.verbatim)
             };
 
-            if is_target_oldpc && UNICOWS_LIBS.contains(&name.as_str()) {
+            if is_unicows_wrapped(sess, lib) {
                 // skip adding unicows-wrapped libraries in order to properly support adding
                 // `unicows.lib` before them
+                if !is_static {
+                    native_libs.record(cnum, lib, NativeLibAction::Skip(UNICOWS_WRAPPED));
+                }
                 continue;
             }
 
@@ -2475,9 +2587,13 @@ fn add_upstream_native_libraries(
                 // ignore static native libraries here as we've
                 // already included them in add_local_native_libraries and
                 // add_upstream_rust_crates
-                NativeLibKind::Static { .. } => {}
-                NativeLibKind::RawDylib => {}
+                NativeLibKind::Static { .. } => continue,
+                NativeLibKind::RawDylib => {
+                    native_libs.record(cnum, lib, NativeLibAction::Skip("raw-dylib"));
+                    continue;
+                }
             }
+            native_libs.record(cnum, lib, NativeLibAction::Link);
         }
     }
 }
@@ -0,0 +1,179 @@
+//! Support for `--print link-plan`.
+//!
+//! The link plan is a JSON description of the decisions `back::link` makes while building the
+//! linker command line: which linker and flavor are used, the full list of arguments it is run
+//! with, and which native libraries are handed to the linker, in order, together with the reason
+//! for every library that is left out. It is printed right before the linker is invoked, from the
+//! same `Command` that is then run. With `-Z no-link` no command line is built and nothing is
+//! printed.
+//!
+//! The native libraries are not walked here: `back::link` reports what it does with each of them
+//! to a `NativeLibRecorder` while it builds the command line, so the plan cannot disagree with
+//! the actual link.
+
+use super::command::Command;
+use super::link::{crt_objects_fallback, link_output_kind};
+use crate::{CodegenResults, NativeLib};
+
+use rustc_hir::def_id::{CrateNum, LOCAL_CRATE};
+use rustc_serialize::json;
+use rustc_session::config::{CrateType, PrintRequest};
+use rustc_session::utils::NativeLibKind;
+use rustc_session::Session;
+use rustc_target::spec::LinkerFlavor;
+
+use std::path::Path;
+
+#[derive(Encodable)]
+struct LinkPlan {
+    crate_type: String,
+    output: String,
+    linker: String,
+    flavor: String,
+    output_kind: String,
+    self_contained: bool,
+    /// Every argument the linker is run with, in order, including the ones `rustc` adds itself.
+    args: Vec<String>,
+    native_libraries: Vec<NativeLibPlan>,
+}
+
+#[derive(Encodable)]
+struct NativeLibPlan {
+    name: String,
+    /// The crate whose `#[link]` attribute or `-l` flag requested the library.
+    from_crate: String,
+    kind: &'static str,
+    modifiers: Vec<&'static str>,
+    /// One of `link`, `link-whole-archive`, `bundled` or `skip`.
+    action: &'static str,
+    /// Why a library is bundled or skipped.
+    reason: Option<&'static str>,
+}
+
+/// What `back::link` does with a native library.
+#[derive(Clone, Copy)]
+pub enum NativeLibAction {
+    Link,
+    LinkWholeArchive,
+    /// The library is part of the rlib of the crate that requested it.
+    Bundled,
+    /// The library is left out, for the given reason.
+    Skip(&'static str),
+}
+
+/// Collects the decisions `back::link` makes about native libraries, in command line order.
+/// Nothing is recorded unless `--print link-plan` was requested.
+pub struct NativeLibRecorder {
+    libs: Option<Vec<(CrateNum, NativeLib, NativeLibAction)>>,
+}
+
+impl NativeLibRecorder {
+    pub fn new(sess: &Session) -> Self {
+        let enabled = sess.opts.prints.contains(&PrintRequest::LinkPlan);
+        NativeLibRecorder { libs: enabled.then(Vec::new) }
+    }
+
+    pub fn record(&mut self, cnum: CrateNum, lib: &NativeLib, action: NativeLibAction) {
+        if let Some(libs) = &mut self.libs {
+            if lib.name.is_some() {
+                libs.push((cnum, lib.clone(), action));
+            }
+        }
+    }
+
+    pub fn skip_all<'b>(
+        &mut self,
+        cnum: CrateNum,
+        libs: impl IntoIterator<Item = &'b NativeLib>,
+        reason: &'static str,
+    ) {
+        for lib in libs {
+            self.record(cnum, lib, NativeLibAction::Skip(reason));
+        }
+    }
+}
+
+pub fn print_link_plan(
+    sess: &Session,
+    crate_type: CrateType,
+    out_filename: &Path,
+    flavor: LinkerFlavor,
+    cmd: &Command,
+    codegen_results: &CodegenResults,
+    native_libs: NativeLibRecorder,
+) {
+    let command = cmd.command();
+    let plan = LinkPlan {
+        crate_type: crate_type.to_string(),
+        output: out_filename.display().to_string(),
+        linker: Path::new(command.get_program()).display().to_string(),
+        flavor: flavor.desc().to_string(),
+        output_kind: link_output_kind(sess, crate_type).to_string(),
+        self_contained: crt_objects_fallback(sess, crate_type),
+        args: command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+        native_libraries: native_library_plan(codegen_results, native_libs),
+    };
+    println!("{}", json::as_pretty_json(&plan));
+}
+
+fn native_library_plan(
+    codegen_results: &CodegenResults,
+    native_libs: NativeLibRecorder,
+) -> Vec<NativeLibPlan> {
+    let info = &codegen_results.crate_info;
+    let libs = native_libs.libs.unwrap_or_default();
+    libs.iter()
+        .map(|(cnum, lib, action)| {
+            let from_crate = if *cnum == LOCAL_CRATE {
+                info.local_crate_name.to_string()
+            } else {
+                info.crate_name[cnum].clone()
+            };
+            let (action, reason) = match *action {
+                NativeLibAction::Link => ("link", None),
+                NativeLibAction::LinkWholeArchive => ("link-whole-archive", None),
+                NativeLibAction::Bundled => ("bundled", Some("included in the crate's rlib")),
+                NativeLibAction::Skip(reason) => ("skip", Some(reason)),
+            };
+            NativeLibPlan {
+                name: lib.name.map_or(String::new(), |name| name.to_string()),
+                from_crate,
+                kind: kind_name(lib.kind),
+                modifiers: modifiers(lib),
+                action,
+                reason,
+            }
+        })
+        .collect()
+}
+
+fn kind_name(kind: NativeLibKind) -> &'static str {
+    match kind {
+        NativeLibKind::Static { .. } => "static",
+        NativeLibKind::Dylib { .. } => "dylib",
+        NativeLibKind::RawDylib => "raw-dylib",
+        NativeLibKind::Framework { .. } => "framework",
+        NativeLibKind::Unspecified => "unspecified",
+    }
+}
+
+fn modifiers(lib: &NativeLib) -> Vec<&'static str> {
+    let mut modifiers = Vec::new();
+    let mut push = |value: Option<bool>, enabled, disabled| match value {
+        Some(true) => modifiers.push(enabled),
+        Some(false) => modifiers.push(disabled),
+        None => {}
+    };
+    match lib.kind {
+        NativeLibKind::Static { bundle, whole_archive } => {
+            push(bundle, "+bundle", "-bundle");
+            push(whole_archive, "+whole-archive", "-whole-archive");
+        }
+        NativeLibKind::Dylib { as_needed } | NativeLibKind::Framework { as_needed } => {
+            push(as_needed, "+as-needed", "-as-needed");
+        }
+        NativeLibKind::RawDylib | NativeLibKind::Unspecified => {}
+    }
+    push(lib.verbatim, "+verbatim", "-verbatim");
+    modifiers
+}
@@ -197,6 +197,10 @@ fn add_eh_frame_header(&mut self) {}
     fn add_no_exec(&mut self) {}
     fn add_as_needed(&mut self) {}
     fn reset_per_library_state(&mut self) {}
+    fn image_base(&mut self, _base: u64) {}
+    fn stack_size(&mut self, _reserve: u64, _commit: Option<u64>) {}
+    fn large_address_aware(&mut self, _enabled: bool) {}
+    fn link_map(&mut self, _path: &Path) {}
 }
 
 impl dyn Linker + '_ {
@@ -789,6 +793,37 @@ fn add_as_needed(&mut self) {
             self.linker_args(&["-z", "ignore"]);
         }
     }
+
+    // The PE options below are understood by GNU ld and by lld's MinGW driver alike.
+    fn image_base(&mut self, base: u64) {
+        self.linker_args(&["--image-base", &format!("{:#x}", base)]);
+    }
+
+    fn stack_size(&mut self, reserve: u64, commit: Option<u64>) {
+        let size = match commit {
+            Some(commit) => format!("{:#x},{:#x}", reserve, commit),
+            None => format!("{:#x}", reserve),
+        };
+        self.linker_args(&["--stack", &size]);
+    }
+
+    fn large_address_aware(&mut self, enabled: bool) {
+        self.linker_arg(if enabled {
+            "--large-address-aware"
+        } else {
+            "--disable-large-address-aware"
+        });
+    }
+
+    fn link_map(&mut self, path: &Path) {
+        if self.sess.target.is_like_osx {
+            self.linker_args(&[OsStr::new("-map"), path.as_os_str()]);
+        } else {
+            let mut arg = OsString::from("-Map=");
+            arg.push(path);
+            self.linker_arg(arg);
+        }
+    }
 }
 
 pub struct MsvcLinker<'a> {
@@ -1029,6 +1064,27 @@ fn linker_plugin_lto(&mut self) {
     fn add_no_exec(&mut self) {
         self.cmd.arg("/NXCOMPAT");
     }
+
+    fn image_base(&mut self, base: u64) {
+        self.cmd.arg(format!("/BASE:{:#x}", base));
+    }
+
+    fn stack_size(&mut self, reserve: u64, commit: Option<u64>) {
+        match commit {
+            Some(commit) => self.cmd.arg(format!("/STACK:{:#x},{:#x}", reserve, commit)),
+            None => self.cmd.arg(format!("/STACK:{:#x}", reserve)),
+        };
+    }
+
+    fn large_address_aware(&mut self, enabled: bool) {
+        self.cmd.arg(if enabled { "/LARGEADDRESSAWARE" } else { "/LARGEADDRESSAWARE:NO" });
+    }
+
+    fn link_map(&mut self, path: &Path) {
+        let mut arg = OsString::from("/MAP:");
+        arg.push(path);
+        self.cmd.arg(arg);
+    }
 }
 
 pub struct EmLinker<'a> {
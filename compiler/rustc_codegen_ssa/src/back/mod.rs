@@ -1,9 +1,13 @@
 pub mod archive;
 pub mod command;
 pub mod link;
+pub mod link_plan;
 pub mod linker;
 pub mod lto;
 pub mod metadata;
 pub mod rpath;
+pub mod stack_usage;
 pub mod symbol_export;
+pub mod symbol_map;
+pub mod verify_cpu;
 pub mod write;
@@ -0,0 +1,301 @@
+//! Worst-case stack usage analysis for `-Z print-stack-usage`.
+//!
+//! The backend reports the size of the stack frame of every function that it compiles, but a
+//! single frame says little about how much stack a thread needs. This pass combines those sizes
+//! with a call graph built from the MIR of the mono items of the crate, and prints for every
+//! function that nothing in the crate calls directly, such as the entry point, exported functions
+//! and the closures that are passed to `std::thread::spawn`, the deepest path through the call
+//! graph and the stack that it uses.
+//!
+//! The result is a bound only if everything reachable is known: recursion makes the usage
+//! unbounded, and calls through function pointers and trait objects, as well as functions whose
+//! frame size isn't known (because they are not codegened in this crate, or were inlined into all
+//! their callers), make it a lower bound. All of these are pointed out in the output. Calls that
+//! are only made to panic, for failed assertions and overflow checks, are not part of the call
+//! graph.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
+use rustc_data_structures::graph::scc::Sccs;
+use rustc_data_structures::graph::vec_graph::VecGraph;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::TerminatorKind;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceDef, TyCtxt};
+use rustc_session::Session;
+
+use std::sync::{Arc, Mutex};
+
+/// The frame sizes of the functions that the backend compiled, by symbol name.
+pub type FrameSizes = Arc<Mutex<FxHashMap<String, u64>>>;
+
+/// The functions that are codegened in the local crate and the functions that they call.
+pub struct CallGraph {
+    /// The functions by symbol name, which is how the backend refers to them.
+    functions: FxIndexMap<String, Function>,
+    /// The entry point of the crate, which is reported even if it is called in the crate.
+    entry: Option<usize>,
+    /// Filled in by the backend during codegen.
+    pub frame_sizes: FrameSizes,
+}
+
+struct Function {
+    name: String,
+    /// Whether the function is codegened in this crate, so that its frame size is recorded.
+    local: bool,
+    /// The functions that this function calls directly.
+    callees: Vec<usize>,
+    /// Whether the function calls function pointers or methods of trait objects.
+    indirect_calls: bool,
+}
+
+impl CallGraph {
+    pub fn new(tcx: TyCtxt<'_>) -> CallGraph {
+        let mut graph = CallGraph {
+            functions: Default::default(),
+            entry: None,
+            frame_sizes: Default::default(),
+        };
+        let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+        for cgu in codegen_units {
+            for mono_item in cgu.items().keys() {
+                if let MonoItem::Fn(instance) = *mono_item {
+                    graph.add_local_function(tcx, instance);
+                }
+            }
+        }
+        if let Some((def_id, _)) = tcx.entry_fn(()) {
+            graph.entry = Some(graph.function(tcx, Instance::mono(tcx, def_id)));
+        }
+        graph
+    }
+
+    fn function<'tcx>(&mut self, tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> usize {
+        let entry = self.functions.entry(tcx.symbol_name(instance).name.to_owned());
+        let index = entry.index();
+        entry.or_insert_with(|| Function {
+            name: with_no_trimmed_paths!(instance.to_string()),
+            local: false,
+            callees: vec![],
+            indirect_calls: false,
+        });
+        index
+    }
+
+    /// Adds the calls that `instance` makes, the same way as codegen resolves them.
+    fn add_local_function<'tcx>(&mut self, tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) {
+        let index = self.function(tcx, instance);
+        if self.functions[index].local {
+            // Already added for another codegen unit that the function is inlined into.
+            return;
+        }
+
+        let param_env = ty::ParamEnv::reveal_all();
+        let monomorphize =
+            |ty| instance.subst_mir_and_normalize_erasing_regions(tcx, param_env, ty);
+        let body = tcx.instance_mir(instance.def);
+        let mut callees = vec![];
+        let mut indirect_calls = false;
+        for block in body.basic_blocks() {
+            match block.terminator().kind {
+                TerminatorKind::Call { ref func, .. } => {
+                    match *monomorphize(func.ty(body, tcx)).kind() {
+                        ty::FnDef(def_id, substs) => {
+                            let callee = Instance::resolve(tcx, param_env, def_id, substs)
+                                .unwrap()
+                                .unwrap()
+                                .polymorphize(tcx);
+                            match callee.def {
+                                // Intrinsics are codegened inline.
+                                InstanceDef::Intrinsic(_) => {}
+                                InstanceDef::Virtual(..) => indirect_calls = true,
+                                _ => callees.push(self.function(tcx, callee)),
+                            }
+                        }
+                        _ => indirect_calls = true,
+                    }
+                }
+                TerminatorKind::Drop { ref place, .. }
+                | TerminatorKind::DropAndReplace { ref place, .. } => {
+                    let ty = monomorphize(place.ty(body, tcx).ty);
+                    let callee = Instance::resolve_drop_in_place(tcx, ty);
+                    match (ty.kind(), callee.def) {
+                        (ty::Dynamic(..), _) => indirect_calls = true,
+                        (_, InstanceDef::DropGlue(_, None)) => {}
+                        _ => callees.push(self.function(tcx, callee)),
+                    }
+                }
+                _ => {}
+            }
+        }
+        callees.sort_unstable();
+        callees.dedup();
+
+        let function = &mut self.functions[index];
+        function.local = true;
+        function.callees = callees;
+        function.indirect_calls = indirect_calls;
+    }
+}
+
+/// The deepest path from a function, ignoring the calls that recurse.
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    /// The stack used by the function and the functions on the path.
+    bytes: u64,
+    /// The next function on the path.
+    next: Option<usize>,
+}
+
+pub fn print_stack_usage(sess: &Session, graph: &CallGraph) {
+    let _timer = sess.timer("print_stack_usage");
+
+    let frame_sizes = graph.frame_sizes.lock().unwrap();
+    if frame_sizes.is_empty() {
+        sess.warn("`-Z print-stack-usage` got no stack sizes, which this backend doesn't report");
+        return;
+    }
+    // ThinLTO renames the local functions that it makes visible to other codegen units, by adding
+    // a `.llvm.<hash>` suffix to their symbol names.
+    let mut sizes_by_symbol: FxHashMap<&str, u64> = Default::default();
+    for (symbol, &size) in frame_sizes.iter() {
+        let symbol = symbol.split(".llvm.").next().unwrap();
+        let frame_size = sizes_by_symbol.entry(symbol).or_default();
+        *frame_size = (*frame_size).max(size);
+    }
+    // Local functions that were inlined into all their callers are not compiled anymore, and
+    // their size is unknown like the one of functions of other crates, even though their frames
+    // are part of the callers'.
+    let frame_size = |node: usize| {
+        let (symbol, function) = graph.functions.get_index(node).unwrap();
+        if function.local { sizes_by_symbol.get(symbol.as_str()).copied() } else { None }
+    };
+
+    let edges = graph
+        .functions
+        .values()
+        .enumerate()
+        .flat_map(|(caller, function)| function.callees.iter().map(move |&callee| (caller, callee)))
+        .collect();
+    let call_graph = VecGraph::new(graph.functions.len(), edges);
+    let sccs: Sccs<usize, usize> = Sccs::new(&call_graph);
+    let mut nodes_by_scc = vec![vec![]; sccs.num_sccs()];
+    for node in 0..graph.functions.len() {
+        nodes_by_scc[sccs.scc(node)].push(node);
+    }
+
+    // The SCCs are numbered so that the callees of a function are done before it.
+    let mut usage = vec![Usage::default(); graph.functions.len()];
+    let mut recursive = vec![false; graph.functions.len()];
+    for nodes in &nodes_by_scc {
+        for &node in nodes {
+            let mut deepest = Usage::default();
+            for &callee in &graph.functions[node].callees {
+                if sccs.scc(callee) == sccs.scc(node) {
+                    recursive[node] = true;
+                } else if deepest.next.is_none() || usage[callee].bytes > deepest.bytes {
+                    deepest = Usage { bytes: usage[callee].bytes, next: Some(callee) };
+                }
+            }
+            deepest.bytes += frame_size(node).unwrap_or(0);
+            usage[node] = deepest;
+        }
+    }
+
+    let mut called = FxHashSet::default();
+    for function in graph.functions.values().filter(|function| function.local) {
+        called.extend(function.callees.iter().copied());
+    }
+    let mut roots: Vec<_> = (0..graph.functions.len())
+        .filter(|&node| graph.functions[node].local)
+        .filter(|&node| !called.contains(&node) || graph.entry == Some(node))
+        .map(|node| (node, Reachable::new(graph, node, &recursive, &frame_size)))
+        .collect();
+    // The roots that need the most stack first.
+    roots.sort_by(|(node1, reachable1), (node2, reachable2)| {
+        let key = |node: usize, reachable: &Reachable| {
+            (reachable.recursive.is_empty(), std::cmp::Reverse(usage[node].bytes))
+        };
+        key(*node1, reachable1)
+            .cmp(&key(*node2, reachable2))
+            .then_with(|| graph.functions[*node1].name.cmp(&graph.functions[*node2].name))
+    });
+
+    for (root, reachable) in roots {
+        let root_name = &graph.functions[root].name;
+        if !reachable.recursive.is_empty() {
+            println!("print-stack-usage root `{root_name}`: unbounded");
+        } else if reachable.indirect_calls.is_empty() && reachable.unknown.is_empty() {
+            println!("print-stack-usage root `{root_name}`: {} bytes", usage[root].bytes);
+        } else {
+            println!("print-stack-usage root `{root_name}`: at least {} bytes", usage[root].bytes);
+        }
+
+        let mut next = Some(root);
+        while let Some(node) = next {
+            let name = &graph.functions[node].name;
+            match frame_size(node) {
+                Some(frame_size) => println!("print-stack-usage     `{name}`: {frame_size} bytes"),
+                None => println!("print-stack-usage     `{name}`: unknown"),
+            }
+            next = usage[node].next;
+        }
+
+        for node in reachable.recursive {
+            let name = &graph.functions[node].name;
+            println!("print-stack-usage     note: `{name}` is recursive");
+        }
+        for node in reachable.indirect_calls {
+            let name = &graph.functions[node].name;
+            println!(
+                "print-stack-usage     note: `{name}` calls function pointers or trait objects"
+            );
+        }
+        for node in reachable.unknown {
+            let name = &graph.functions[node].name;
+            println!("print-stack-usage     note: the stack usage of `{name}` is unknown");
+        }
+    }
+}
+
+/// The functions reachable from a root that make its stack usage unbounded, or only a lower bound.
+struct Reachable {
+    recursive: Vec<usize>,
+    indirect_calls: Vec<usize>,
+    unknown: Vec<usize>,
+}
+
+impl Reachable {
+    fn new(
+        graph: &CallGraph,
+        root: usize,
+        recursive: &[bool],
+        frame_size: impl Fn(usize) -> Option<u64>,
+    ) -> Reachable {
+        let mut reachable =
+            Reachable { recursive: vec![], indirect_calls: vec![], unknown: vec![] };
+        let mut visited = FxHashSet::default();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let function = &graph.functions[node];
+            if recursive[node] {
+                reachable.recursive.push(node);
+            }
+            if function.indirect_calls {
+                reachable.indirect_calls.push(node);
+            }
+            if frame_size(node).is_none() {
+                reachable.unknown.push(node);
+            }
+            stack.extend(function.callees.iter().copied());
+        }
+
+        let by_name = |node: &usize| graph.functions[*node].name.clone();
+        reachable.recursive.sort_by_key(by_name);
+        reachable.indirect_calls.sort_by_key(by_name);
+        reachable.unknown.sort_by_key(by_name);
+        reachable
+    }
+}
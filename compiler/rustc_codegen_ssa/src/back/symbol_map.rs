@@ -0,0 +1,153 @@
+//! Writing of the `--emit symbol-map` output.
+//!
+//! The symbol map lists every symbol defined in the object files of the local crate together
+//! with the section it lives in and its offset and size within that section. Combined with a
+//! linker map (`-C link-map`), which records where each object's sections ended up in the final
+//! image, this is enough to resolve a raw crash address to a Rust function without a PDB or
+//! any other debug information.
+
+use super::write::CompiledModules;
+use crate::CrateInfo;
+
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind, SymbolSection};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_session::config::{OutputFilenames, OutputType};
+use rustc_session::Session;
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+struct SymbolEntry {
+    section_index: usize,
+    section: String,
+    section_size: u64,
+    offset: u64,
+    size: u64,
+    binding: &'static str,
+    name: String,
+}
+
+pub fn write_symbol_map(
+    sess: &Session,
+    compiled_modules: &CompiledModules,
+    crate_info: &CrateInfo,
+    outputs: &OutputFilenames,
+) {
+    let _timer = sess.timer("write_symbol_map");
+
+    let exported: FxHashSet<&str> =
+        crate_info.exported_symbols.values().flatten().map(|s| s.as_str()).collect();
+
+    let path = outputs.path(OutputType::SymbolMap);
+    let res: io::Result<()> = try {
+        let mut out = BufWriter::new(fs::File::create(&path)?);
+        writeln!(out, "# symbol map for crate `{}`", crate_info.local_crate_name)?;
+        writeln!(out, "# object\tsection\toffset\tsize\tbinding\tsymbol\tdemangled")?;
+
+        let modules = compiled_modules.modules.iter().chain(&compiled_modules.allocator_module);
+        for module in modules {
+            let Some(ref object) = module.object else { continue };
+            let entries = match read_symbols(sess, object, &exported) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    sess.warn(&format!("skipping `{}` in the symbol map: {}", object.display(), e));
+                    continue;
+                }
+            };
+            let object_name = object.file_name().unwrap_or_default().to_string_lossy();
+            for entry in entries {
+                writeln!(
+                    out,
+                    "{}\t{}\t{:#010x}\t{:#x}\t{}\t{}\t{:#}",
+                    object_name,
+                    entry.section,
+                    entry.offset,
+                    entry.size,
+                    entry.binding,
+                    entry.name,
+                    rustc_demangle::demangle(&entry.name),
+                )?;
+            }
+        }
+        out.flush()?;
+    };
+    if let Err(e) = res {
+        sess.err(&format!("failed to write symbol map `{}`: {}", path.display(), e));
+    } else if sess.opts.json_artifact_notifications {
+        sess.parse_sess.span_diagnostic.emit_artifact_notification(&path, "symbol-map");
+    }
+}
+
+/// The prefix that 32-bit Windows and Apple targets add to the names of C symbols.
+pub(super) fn global_symbol_prefix(sess: &Session) -> &'static str {
+    if (sess.target.is_like_windows && sess.target.arch == "x86") || sess.target.is_like_osx {
+        "_"
+    } else {
+        ""
+    }
+}
+
+/// Collects the defined code and data symbols of a single object file, sorted by section and
+/// offset within the section.
+fn read_symbols(
+    sess: &Session,
+    path: &Path,
+    exported: &FxHashSet<&str>,
+) -> Result<Vec<SymbolEntry>, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let file = object::File::parse(&*data).map_err(|e| e.to_string())?;
+
+    // The prefix is not part of the names recorded in `exported_symbols`.
+    let global_prefix = global_symbol_prefix(sess);
+
+    let mut entries = Vec::new();
+    for symbol in file.symbols() {
+        if !matches!(symbol.kind(), SymbolKind::Text | SymbolKind::Data) {
+            continue;
+        }
+        let SymbolSection::Section(index) = symbol.section() else { continue };
+        let Ok(section) = file.section_by_index(index) else { continue };
+        let Ok(name) = symbol.name() else { continue };
+        if name.is_empty() {
+            continue;
+        }
+
+        let unprefixed = name.strip_prefix(global_prefix).unwrap_or(name);
+        let binding = if !symbol.is_global() {
+            "local"
+        } else if exported.contains(unprefixed) {
+            "export"
+        } else {
+            "global"
+        };
+
+        entries.push(SymbolEntry {
+            section_index: index.0,
+            section: section.name().unwrap_or("?").to_string(),
+            section_size: section.size(),
+            offset: symbol.address().saturating_sub(section.address()),
+            size: symbol.size(),
+            binding,
+            name: name.to_string(),
+        });
+    }
+
+    entries.sort_by_key(|e| (e.section_index, e.offset));
+
+    // COFF does not record symbol sizes. Since functions and statics are usually emitted into
+    // their own COMDAT sections, the size of such a symbol is the distance to the next symbol
+    // in its section, or to the end of that section.
+    for i in 0..entries.len() {
+        if entries[i].size != 0 {
+            continue;
+        }
+        let end = match entries.get(i + 1) {
+            Some(next) if next.section_index == entries[i].section_index => next.offset,
+            _ => entries[i].section_size,
+        };
+        entries[i].size = end.saturating_sub(entries[i].offset);
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,215 @@
+//! Post-codegen verification for `-Z verify-target-cpu`.
+//!
+//! Setting `-C target-cpu` only controls which instructions the code generator chooses on its
+//! own. Inline assembly, `#[target_feature]` functions and code that was instantiated from a
+//! differently configured upstream crate can still contain instructions that the target CPU
+//! does not implement, which only shows up as an illegal instruction fault on real hardware.
+//! This pass disassembles the object files of the local crate and reports every function that
+//! contains such an instruction.
+
+use super::symbol_map::global_symbol_prefix;
+use super::write::CompiledModules;
+
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind, SymbolSection};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_session::Session;
+
+use std::fs;
+use std::path::Path;
+
+/// A disassembler for the code generated for the session's target, provided by the backend.
+pub trait TargetDisassembler {
+    /// Decodes the instruction at the start of `bytes`, which is located at `address`. Returns
+    /// the length of the instruction and its text in Intel syntax, or `None` if `bytes` does not
+    /// start with a valid instruction.
+    fn decode(&mut self, bytes: &[u8], address: u64) -> Option<(usize, String)>;
+
+    /// Whether the target CPU, including the features enabled with `-C target-feature`,
+    /// supports `feature`. Feature names are the ones used by LLVM.
+    fn has_feature(&self, feature: &str) -> bool;
+}
+
+pub fn verify_target_cpu(
+    sess: &Session,
+    compiled_modules: &CompiledModules,
+    target_cpu: &str,
+    disassembler: &mut dyn TargetDisassembler,
+) {
+    let _timer = sess.timer("verify_target_cpu");
+
+    if !matches!(&*sess.target.arch, "x86" | "x86_64") {
+        sess.warn(&format!(
+            "`-Z verify-target-cpu` is not supported for the `{}` architecture",
+            sess.target.arch
+        ));
+        return;
+    }
+
+    let modules = compiled_modules.modules.iter().chain(&compiled_modules.allocator_module);
+    for module in modules {
+        let Some(ref object) = module.object else { continue };
+        if let Err(e) = verify_object(sess, object, target_cpu, disassembler) {
+            sess.warn(&format!(
+                "could not verify `{}` for the target CPU: {}",
+                object.display(),
+                e
+            ));
+        }
+    }
+}
+
+fn verify_object(
+    sess: &Session,
+    path: &Path,
+    target_cpu: &str,
+    disassembler: &mut dyn TargetDisassembler,
+) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let file = object::File::parse(&*data).map_err(|e| e.to_string())?;
+
+    for section in file.sections() {
+        if section.kind() != SectionKind::Text {
+            continue;
+        }
+        let code = section.data().map_err(|e| e.to_string())?;
+
+        // Every function extends to the next function symbol in its section, or to the end of
+        // the section. This also covers COFF, which does not record symbol sizes.
+        let mut functions: Vec<_> = file
+            .symbols()
+            .filter(|symbol| {
+                symbol.kind() == SymbolKind::Text
+                    && symbol.section() == SymbolSection::Section(section.index())
+            })
+            .filter_map(|symbol| {
+                let name = symbol.name().ok().filter(|name| !name.is_empty())?;
+                Some((symbol.address().saturating_sub(section.address()), name))
+            })
+            .collect();
+        functions.sort();
+        functions.dedup_by_key(|&mut (offset, _)| offset);
+
+        for (i, &(start, name)) in functions.iter().enumerate() {
+            let end = functions.get(i + 1).map_or(code.len() as u64, |&(next, _)| next);
+            let Some(bytes) = code.get(start as usize..end as usize) else { continue };
+            verify_function(sess, path, name, start, bytes, target_cpu, disassembler);
+        }
+    }
+    Ok(())
+}
+
+fn verify_function(
+    sess: &Session,
+    path: &Path,
+    name: &str,
+    start: u64,
+    bytes: &[u8],
+    target_cpu: &str,
+    disassembler: &mut dyn TargetDisassembler,
+) {
+    // Only the first use of every missing feature is reported.
+    let mut reported = FxHashSet::default();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let address = start + offset as u64;
+        let Some((len, text)) = disassembler.decode(&bytes[offset..], address) else {
+            // Not an instruction, e.g. data embedded in the code. Resynchronize on the next byte.
+            offset += 1;
+            continue;
+        };
+        offset += len.max(1);
+
+        let Some(feature) = required_x86_feature(&text) else { continue };
+        if disassembler.has_feature(feature) || !reported.insert(feature) {
+            continue;
+        }
+        let name = name.strip_prefix(global_symbol_prefix(sess)).unwrap_or(name);
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        sess.struct_err(&format!(
+            "function `{:#}` contains an instruction that is not supported by the target CPU",
+            rustc_demangle::demangle(name),
+        ))
+        .note(&format!(
+            "`{}` requires the `{}` feature, which target CPU `{}` does not have",
+            text, feature, target_cpu,
+        ))
+        .note(&format!("found at offset {:#x} in `{}`", address, path.display()))
+        .emit();
+    }
+}
+
+/// Returns the CPU feature that an x86 instruction, in Intel syntax, depends on. Instructions
+/// of the original i386 and i486 instruction sets return `None`.
+///
+/// The classification is exact for the extensions up to SSE2 that older CPUs lack. Newer
+/// vector extensions are only told apart by register width, so e.g. an SSSE3 instruction is
+/// reported as requiring SSE2.
+fn required_x86_feature(text: &str) -> Option<&'static str> {
+    const PREFIXES: &[&str] =
+        &["lock", "rep", "repe", "repz", "repne", "repnz", "data16", "cs", "ds", "notrack", "bnd"];
+
+    let mut tokens = text.split_whitespace().skip_while(|token| PREFIXES.contains(token));
+    let mnemonic = tokens.next()?;
+    let operands: Vec<&str> = tokens
+        .flat_map(|token| token.split(|c: char| !c.is_ascii_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let uses_register = |prefix: &str| operands.iter().any(|word| word.starts_with(prefix));
+
+    if uses_register("zmm") {
+        return Some("avx512f");
+    }
+    if uses_register("ymm") {
+        // Integer operations on 256-bit vectors were only added by AVX2, together with a few
+        // lane-crossing shuffles and gathers. AVX itself already had some `vp` instructions.
+        return Some(match mnemonic {
+            "vperm2f128" | "vpermilps" | "vpermilpd" | "vptest" => "avx",
+            "vpclmulqdq" => "vpclmulqdq",
+            "vbroadcasti128" | "vextracti128" | "vinserti128" | "vperm2i128" | "vmovntdqa" => {
+                "avx2"
+            }
+            _ if mnemonic.starts_with("vp") || mnemonic.starts_with("vgather") => "avx2",
+            _ => "avx",
+        });
+    }
+    if uses_register("xmm") {
+        if mnemonic.starts_with('v') {
+            return Some("avx");
+        }
+        // SSE only operates on single precision floats; double precision and integer
+        // operations were added by SSE2.
+        let sse2 = mnemonic.ends_with("pd")
+            || mnemonic.ends_with("sd")
+            || mnemonic.ends_with("dq")
+            || mnemonic.starts_with('p')
+            || matches!(mnemonic, "movd" | "movq" | "cvtsi2sd" | "cvtsd2si" | "cvttsd2si");
+        return Some(if sse2 { "sse2" } else { "sse" });
+    }
+    if mnemonic == "emms" || operands.iter().any(|word| is_mmx_register(word)) {
+        return Some("mmx");
+    }
+
+    Some(match mnemonic {
+        // The one-byte `nop` has always existed, but the multi-byte `nop` with a memory operand
+        // (NOPL, `0f 1f /0`) was added by the P6 and is what LLVM pads code with for `i686`.
+        "nop" if !operands.is_empty() => "nopl",
+        _ if mnemonic.starts_with("cmov") || mnemonic.starts_with("fcmov") => "cmov",
+        "fcomi" | "fcomip" | "fucomi" | "fucomip" => "cmov",
+        "cmpxchg8b" => "cx8",
+        "cmpxchg16b" => "cx16",
+        "sfence" | "prefetchnta" | "prefetcht0" | "prefetcht1" | "prefetcht2" => "sse",
+        "lfence" | "mfence" | "clflush" | "movnti" => "sse2",
+        "popcnt" => "popcnt",
+        "lzcnt" => "lzcnt",
+        "tzcnt" | "andn" | "bextr" | "blsi" | "blsmsk" | "blsr" => "bmi",
+        "bzhi" | "pdep" | "pext" | "mulx" | "rorx" | "sarx" | "shlx" | "shrx" => "bmi2",
+        "movbe" => "movbe",
+        "rdrand" => "rdrnd",
+        "rdseed" => "rdseed",
+        _ => return None,
+    })
+}
+
+fn is_mmx_register(word: &str) -> bool {
+    matches!(word.as_bytes(), [b'm', b'm', b'0'..=b'7'])
+}
@@ -1,6 +1,9 @@
 use super::link::{self, ensure_removed};
 use super::lto::{self, SerializedModule};
+use super::stack_usage::{self, CallGraph, FrameSizes};
 use super::symbol_export::symbol_name_for_instance_in_crate;
+use super::symbol_map;
+use super::verify_cpu;
 
 use crate::{
     CachedModuleCodegen, CodegenResults, CompiledModule, CrateInfo, ModuleCodegen, ModuleKind,
@@ -137,7 +140,10 @@ macro_rules! if_regular {
 
         let should_emit_obj = sess.opts.output_types.contains_key(&OutputType::Exe)
             || match kind {
-                ModuleKind::Regular => sess.opts.output_types.contains_key(&OutputType::Object),
+                ModuleKind::Regular => {
+                    sess.opts.output_types.contains_key(&OutputType::Object)
+                        || sess.opts.output_types.contains_key(&OutputType::SymbolMap)
+                }
                 ModuleKind::Allocator => false,
                 ModuleKind::Metadata => sess.opts.output_types.contains_key(&OutputType::Metadata),
             };
@@ -349,6 +355,9 @@ pub struct CodegenContext<B: WriteBackendMethods> {
     pub cgu_reuse_tracker: CguReuseTracker,
     // Channel back to the main control thread to send messages to
     pub coordinator_send: Sender<Box<dyn Any + Send>>,
+    // Where the backend records the frame sizes of the functions it compiles,
+    // for `-Z print-stack-usage`
+    pub frame_sizes: Option<FrameSizes>,
 }
 
 impl<B: WriteBackendMethods> CodegenContext<B> {
@@ -451,6 +460,7 @@ pub fn start_async_codegen<B: ExtraBackendMethods>(
 
     let (shared_emitter, shared_emitter_main) = SharedEmitter::new();
     let (codegen_worker_send, codegen_worker_receive) = channel();
+    let call_graph = sess.opts.debugging_opts.print_stack_usage.then(|| CallGraph::new(tcx));
 
     let coordinator_thread = start_executing_work(
         backend.clone(),
@@ -465,6 +475,7 @@ pub fn start_async_codegen<B: ExtraBackendMethods>(
         Arc::new(metadata_config),
         Arc::new(allocator_config),
         coordinator_send.clone(),
+        call_graph.as_ref().map(|call_graph| call_graph.frame_sizes.clone()),
     );
 
     OngoingCodegen {
@@ -478,6 +489,7 @@ pub fn start_async_codegen<B: ExtraBackendMethods>(
         shared_emitter_main,
         future: coordinator_thread,
         output_filenames: tcx.output_filenames(()).clone(),
+        call_graph,
     }
 }
 
@@ -587,7 +599,11 @@ fn produce_final_output_artifacts(
                 user_wants_objects = true;
                 copy_if_one_unit(OutputType::Object, true);
             }
-            OutputType::Mir | OutputType::Metadata | OutputType::Exe | OutputType::DepInfo => {}
+            OutputType::Mir
+            | OutputType::Metadata
+            | OutputType::Exe
+            | OutputType::DepInfo
+            | OutputType::SymbolMap => {}
         }
     }
 
@@ -972,6 +988,7 @@ fn start_executing_work<B: ExtraBackendMethods>(
     metadata_config: Arc<ModuleConfig>,
     allocator_config: Arc<ModuleConfig>,
     tx_to_llvm_workers: Sender<Box<dyn Any + Send>>,
+    frame_sizes: Option<FrameSizes>,
 ) -> thread::JoinHandle<Result<CompiledModules, ()>> {
     let coordinator_send = tx_to_llvm_workers;
     let sess = tcx.sess;
@@ -1065,6 +1082,7 @@ fn start_executing_work<B: ExtraBackendMethods>(
         debuginfo: tcx.sess.opts.debuginfo,
         split_debuginfo: tcx.sess.split_debuginfo(),
         split_dwarf_kind: tcx.sess.opts.debugging_opts.split_dwarf_kind,
+        frame_sizes,
     };
 
     // This is the "main loop" of parallel work happening for parallel codegen.
@@ -1804,6 +1822,8 @@ pub struct OngoingCodegen<B: ExtraBackendMethods> {
     pub shared_emitter_main: SharedEmitterMain,
     pub future: thread::JoinHandle<Result<CompiledModules, ()>>,
     pub output_filenames: Arc<OutputFilenames>,
+    /// The call graph for `-Z print-stack-usage`, which needs the frame sizes of codegen.
+    pub call_graph: Option<CallGraph>,
 }
 
 impl<B: ExtraBackendMethods> OngoingCodegen<B> {
@@ -1825,10 +1845,41 @@ pub fn join(self, sess: &Session) -> (CodegenResults, FxHashMap<WorkProductId, W
 
         sess.cgu_reuse_tracker.check_expected_reuse(sess.diagnostic());
 
+        if let Some(format) = sess.opts.debugging_opts.incremental_explain {
+            if sess.opts.incremental.is_some() {
+                print!("{}", sess.cgu_reuse_tracker.explain(format));
+            }
+        }
+
         sess.abort_if_errors();
 
         let work_products =
             copy_all_cgu_workproducts_to_incr_comp_cache_dir(sess, &compiled_modules);
+        if sess.opts.output_types.contains_key(&OutputType::SymbolMap) {
+            // Needs to happen before `produce_final_output_artifacts` removes the objects.
+            symbol_map::write_symbol_map(
+                sess,
+                &compiled_modules,
+                &self.crate_info,
+                &self.output_filenames,
+            );
+        }
+        if sess.opts.debugging_opts.verify_target_cpu {
+            match self.backend.target_disassembler(sess) {
+                Some(mut disassembler) => verify_cpu::verify_target_cpu(
+                    sess,
+                    &compiled_modules,
+                    self.backend.target_cpu(sess),
+                    &mut *disassembler,
+                ),
+                None => {
+                    sess.warn("`-Z verify-target-cpu` is not supported by this codegen backend")
+                }
+            }
+        }
+        if let Some(call_graph) = &self.call_graph {
+            stack_usage::print_stack_usage(sess, call_graph);
+        }
         produce_final_output_artifacts(sess, &compiled_modules, &self.output_filenames);
 
         // FIXME: time_llvm_passes support - does this use a global context or
@@ -22,14 +22,17 @@
 use rustc_hir::lang_items::LangItem;
 use rustc_index::vec::Idx;
 use rustc_metadata::EncodedMetadata;
+use rustc_middle::dep_graph::{DepNode, DepNodeExt};
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrs;
 use rustc_middle::middle::exported_symbols;
 use rustc_middle::middle::lang_items;
 use rustc_middle::mir::mono::{CodegenUnit, CodegenUnitNameBuilder, MonoItem};
 use rustc_middle::ty::layout::{HasTyCtxt, LayoutOf, TyAndLayout};
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
-use rustc_session::cgu_reuse_tracker::CguReuse;
+use rustc_query_system::dep_graph::FingerprintStyle;
+use rustc_session::cgu_reuse_tracker::{CguRecompileReason, CguReuse};
 use rustc_session::config::{self, EntryFnType, OutputType};
 use rustc_session::Session;
 use rustc_span::symbol::sym;
@@ -929,10 +932,21 @@ fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) -> CguR
         return CguReuse::No;
     }
 
+    let explain = tcx.sess.opts.debugging_opts.incremental_explain.is_some();
+
     let work_product_id = &cgu.work_product_id();
     if tcx.dep_graph.previous_work_product(work_product_id).is_none() {
         // We don't have anything cached for this CGU. This can happen
         // if the CGU did not exist in the previous session.
+        if explain {
+            let reason = if tcx.dep_graph.prev_fingerprint_of(&cgu.codegen_dep_node(tcx)).is_some()
+            {
+                CguRecompileReason::MissingWorkProduct
+            } else {
+                CguRecompileReason::NewCgu
+            };
+            tcx.sess.cgu_reuse_tracker.set_recompile_reason(cgu.name().as_str(), reason);
+        }
         return CguReuse::No;
     }
 
@@ -963,6 +977,27 @@ fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) -> CguR
             _ => CguReuse::PreLto,
         }
     } else {
+        if explain {
+            let chain = tcx.dep_graph.red_dependency_chain(&dep_node);
+            let chain = chain.iter().map(|dep_node| describe_dep_node(tcx, dep_node)).collect();
+            tcx.sess
+                .cgu_reuse_tracker
+                .set_recompile_reason(cgu.name().as_str(), CguRecompileReason::Changed(chain));
+        }
         CguReuse::No
     }
 }
+
+/// Describes a dep-node for `-Z incremental-explain`.
+fn describe_dep_node(tcx: TyCtxt<'_>, dep_node: &DepNode) -> String {
+    match dep_node.kind.fingerprint_style(tcx) {
+        FingerprintStyle::Unit => format!("{:?}(())", dep_node.kind),
+        // Only the nodes that were recomputed are known to refer to an item that still exists.
+        FingerprintStyle::DefPathHash if tcx.dep_graph.is_red(dep_node) => {
+            let def_id = dep_node.extract_def_id(tcx).unwrap();
+            format!("{:?}({})", dep_node.kind, with_no_trimmed_paths!(tcx.def_path_str(def_id)))
+        }
+        FingerprintStyle::DefPathHash => format!("{:?}({})", dep_node.kind, dep_node.hash),
+        FingerprintStyle::Opaque => format!("{:?}", dep_node),
+    }
+}
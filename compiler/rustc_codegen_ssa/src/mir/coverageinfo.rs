@@ -47,6 +47,14 @@ pub fn codegen_coverage(&self, bx: &mut Bx, coverage: Coverage, scope: SourceSco
             CoverageKind::Expression { id, lhs, op, rhs } => {
                 bx.add_coverage_counter_expression(instance, id, lhs, op, rhs, code_region);
             }
+            CoverageKind::Branch { true_counter, false_counter } => {
+                bx.add_coverage_branch(
+                    instance,
+                    true_counter,
+                    false_counter,
+                    code_region.expect("branch regions always have code regions"),
+                );
+            }
             CoverageKind::Unreachable => {
                 bx.add_coverage_unreachable(
                     instance,
@@ -1,5 +1,6 @@
 use super::write::WriteBackendMethods;
 use super::CodegenObject;
+use crate::back::verify_cpu::TargetDisassembler;
 use crate::back::write::TargetMachineFactoryFn;
 use crate::{CodegenResults, ModuleCodegen};
 
@@ -139,6 +140,12 @@ fn target_machine_factory(
     fn target_cpu<'b>(&self, sess: &'b Session) -> &'b str;
     fn tune_cpu<'b>(&self, sess: &'b Session) -> Option<&'b str>;
 
+    /// Creates a disassembler for the session's target, used by `-Z verify-target-cpu`.
+    /// Returns `None` if the backend cannot disassemble code for the target.
+    fn target_disassembler(&self, _sess: &Session) -> Option<Box<dyn TargetDisassembler>> {
+        None
+    }
+
     fn spawn_thread<F, T>(_time_trace: bool, f: F) -> std::thread::JoinHandle<T>
     where
         F: FnOnce() -> T,
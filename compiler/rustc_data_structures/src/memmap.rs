@@ -17,6 +17,14 @@ impl Mmap {
     pub unsafe fn map(file: File) -> io::Result<Self> {
         memmap2::Mmap::map(&file).map(Mmap)
     }
+
+    /// Copies `bytes` into a read-only anonymous memory map, for data that has to be
+    /// transformed after being read from a file, e.g. decompressed.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut mmap = memmap2::MmapMut::map_anon(bytes.len())?;
+        mmap.copy_from_slice(bytes);
+        mmap.make_read_only().map(Mmap)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -29,6 +37,10 @@ pub unsafe fn map(mut file: File) -> io::Result<Self> {
         file.read_to_end(&mut data)?;
         Ok(Mmap(data))
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Mmap(bytes.to_vec()))
+    }
 }
 
 impl Deref for Mmap {
@@ -31,7 +31,7 @@
 use rustc_save_analysis as save;
 use rustc_save_analysis::DumpHandler;
 use rustc_serialize::json::ToJson;
-use rustc_session::config::{nightly_options, CG_OPTIONS, DB_OPTIONS};
+use rustc_session::config::{nightly_options, PrintTypeSizes, CG_OPTIONS, DB_OPTIONS};
 use rustc_session::config::{ErrorOutputType, Input, OutputType, PrintRequest, TrimmedDefPaths};
 use rustc_session::cstore::MetadataLoader;
 use rustc_session::getopts;
@@ -405,8 +405,15 @@ fn run_compiler(
 
             queries.ongoing_codegen()?;
 
-            if sess.opts.debugging_opts.print_type_sizes {
-                sess.code_stats.print_type_sizes();
+            match sess.opts.debugging_opts.print_type_sizes {
+                Some(PrintTypeSizes::Text) => sess.code_stats.print_type_sizes(),
+                Some(PrintTypeSizes::Json) => sess.code_stats.print_type_sizes_json(),
+                None => {}
+            }
+            if let Some(path) = &sess.opts.debugging_opts.compare_type_sizes {
+                if let Err(e) = sess.code_stats.compare_type_sizes(path) {
+                    sess.err(&format!("failed to read type sizes from `{}`: {e}", path.display()));
+                }
             }
 
             let linker = queries.linker()?;
@@ -637,9 +644,9 @@ fn print_crate_info(
     temps_dir: &Option<PathBuf>,
 ) -> Compilation {
     use rustc_session::config::PrintRequest::*;
-    // NativeStaticLibs and LinkArgs are special - printed during linking
+    // NativeStaticLibs, LinkArgs and LinkPlan are special - printed during linking
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|&p| p == NativeStaticLibs || p == LinkArgs) {
+    if sess.opts.prints.iter().all(|&p| p == NativeStaticLibs || p == LinkArgs || p == LinkPlan) {
         return Compilation::Continue;
     }
 
@@ -731,6 +738,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            LinkPlan => {}
         }
     }
     Compilation::Stop
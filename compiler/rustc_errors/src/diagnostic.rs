@@ -39,6 +39,10 @@ pub struct Diagnostic {
     /// If diagnostic is from Lint, custom hash function ignores notes
     /// otherwise hash is based on the all the fields
     pub is_lint: bool,
+
+    /// The path of the item that a lint was reported in, if known. Only used to
+    /// match the diagnostic against a lint baseline.
+    pub lint_item: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Encodable, Decodable)]
@@ -119,6 +123,7 @@ pub fn new_with_code(level: Level, code: Option<DiagnosticId>, message: &str) ->
             suggestions: Ok(vec![]),
             sort_span: DUMMY_SP,
             is_lint: false,
+            lint_item: None,
         }
     }
 
@@ -712,6 +717,11 @@ pub fn set_is_lint(&mut self) -> &mut Self {
         self
     }
 
+    pub fn set_lint_item(&mut self, item: String) -> &mut Self {
+        self.lint_item = Some(item);
+        self
+    }
+
     pub fn code(&mut self, s: DiagnosticId) -> &mut Self {
         self.code = Some(s);
         self
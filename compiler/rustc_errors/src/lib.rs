@@ -51,8 +51,10 @@
 mod diagnostic_builder;
 pub mod emitter;
 pub mod json;
+mod lint_baseline;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 pub use snippet::Style;
@@ -402,6 +404,7 @@ impl error::Error for ExplicitBug {}
 
 pub use diagnostic::{Diagnostic, DiagnosticId, DiagnosticStyledString, SubDiagnostic};
 pub use diagnostic_builder::{DiagnosticBuilder, EmissionGuarantee};
+pub use lint_baseline::LintBaseline;
 use std::backtrace::Backtrace;
 
 /// A handler deals with errors and other compiler output.
@@ -469,6 +472,10 @@ struct HandlerInner {
     ///
     /// [RFC-2383]: https://rust-lang.github.io/rfcs/2383-lint-reasons.html
     fulfilled_expectations: FxHashSet<LintExpectationId>,
+
+    /// The lint diagnostics that are suppressed because they were already
+    /// there (rustc: see `--lint-baseline`).
+    lint_baseline: Option<LintBaseline>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -596,6 +603,7 @@ pub fn with_emitter_and_flags(
                 future_breakage_diagnostics: Vec::new(),
                 unstable_expect_diagnostics: Vec::new(),
                 fulfilled_expectations: Default::default(),
+                lint_baseline: None,
             }),
         }
     }
@@ -900,6 +908,19 @@ pub fn print_error_count(&self, registry: &Registry) {
         self.inner.borrow_mut().print_error_count(registry)
     }
 
+    /// Suppresses the lint diagnostics that `baseline` already knows about.
+    pub fn set_lint_baseline(&self, baseline: LintBaseline) {
+        self.inner.borrow_mut().lint_baseline = Some(baseline);
+    }
+
+    /// Writes out the lint baseline, if one is being recorded.
+    pub fn write_lint_baseline(&self) -> std::io::Result<()> {
+        match &mut self.inner.borrow_mut().lint_baseline {
+            Some(baseline) => baseline.write(),
+            None => Ok(()),
+        }
+    }
+
     pub fn take_future_breakage_diagnostics(&self) -> Vec<Diagnostic> {
         std::mem::take(&mut self.inner.borrow_mut().future_breakage_diagnostics)
     }
@@ -1058,6 +1079,12 @@ fn emit_diagnostic(&mut self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
             return None;
         }
 
+        if let Some(baseline) = &mut self.lint_baseline {
+            if baseline.suppresses(diagnostic, self.emitter.source_map().map(|sm| &**sm)) {
+                return None;
+            }
+        }
+
         if let Some(ref code) = diagnostic.code {
             self.emitted_diagnostic_codes.insert(code.clone());
         }
@@ -1143,6 +1170,8 @@ fn print_error_count(&mut self, registry: &Registry) {
 
         match (errors.len(), warnings.len()) {
             (0, 0) => return,
+            // Formats like SARIF only report the diagnostics themselves.
+            _ if !self.emitter.should_show_error_count() => {}
             (0, _) => self.emitter.emit_diagnostic(&Diagnostic::new(Level::Warning, &warnings)),
             (_, 0) => {
                 let _ = self.fatal(&errors);
@@ -0,0 +1,130 @@
+//! Lint baselines, which let a crate with many existing warnings gate on new
+//! lints only (rustc: see `--lint-baseline` and `--write-lint-baseline`).
+//!
+//! A baseline file lists the lint diagnostics that a crate already had, one per
+//! line, as the lint name, the path of the item it was reported in and a
+//! fingerprint, separated by tabs. The fingerprint is a hash of the message
+//! and of the source text of the primary spans, so it does not depend on where
+//! in the file the code is: lines can be added above a known warning without
+//! it turning into a new one. Identical diagnostics in the same item are listed
+//! once per occurrence.
+//!
+//! Lint diagnostics that match an entry are suppressed, every entry matching at
+//! most one diagnostic. Everything else is emitted with its usual level.
+
+use crate::{Diagnostic, DiagnosticId, Level};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_span::source_map::SourceMap;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "# rustc lint baseline: lint, item, fingerprint\n";
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+struct Entry {
+    lint: String,
+    item: String,
+    fingerprint: String,
+}
+
+#[derive(Default)]
+pub struct LintBaseline {
+    /// The entries read from `--lint-baseline`, with the number of diagnostics
+    /// that each of them may still suppress.
+    known: FxHashMap<Entry, usize>,
+    /// Where to write every lint diagnostic seen, for `--write-lint-baseline`.
+    output: Option<PathBuf>,
+    recorded: Vec<Entry>,
+}
+
+impl LintBaseline {
+    /// Reads the baseline at `path`, if any, and prepares to write a new one to
+    /// `output`.
+    pub fn new(path: Option<&Path>, output: Option<PathBuf>) -> io::Result<LintBaseline> {
+        let mut baseline = LintBaseline { output, ..Default::default() };
+        if let Some(path) = path {
+            for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let mut fields = line.split('\t');
+                let entry = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                    (Some(lint), Some(item), Some(fingerprint), None) => Entry {
+                        lint: lint.to_owned(),
+                        item: item.to_owned(),
+                        fingerprint: fingerprint.to_owned(),
+                    },
+                    _ => {
+                        let msg = format!("malformed entry on line {}: `{}`", i + 1, line);
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+                    }
+                };
+                *baseline.known.entry(entry).or_default() += 1;
+            }
+        }
+        Ok(baseline)
+    }
+
+    /// Returns whether `diag` is a lint diagnostic that the baseline covers,
+    /// and records it if a new baseline is being written.
+    crate fn suppresses(&mut self, diag: &Diagnostic, sm: Option<&SourceMap>) -> bool {
+        let lint = match &diag.code {
+            Some(DiagnosticId::Lint { name, .. }) => name,
+            _ => return false,
+        };
+        if !matches!(diag.level, Level::Warning | Level::Error { lint: true }) {
+            return false;
+        }
+        let entry = Entry {
+            lint: lint.clone(),
+            item: diag.lint_item.clone().unwrap_or_default(),
+            fingerprint: fingerprint(diag, sm),
+        };
+
+        let known = match self.known.get_mut(&entry) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        };
+        if self.output.is_some() {
+            self.recorded.push(entry);
+        }
+        known
+    }
+
+    /// Writes out the lint diagnostics seen so far, if asked to.
+    pub fn write(&mut self) -> io::Result<()> {
+        let path = match self.output.take() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        self.recorded.sort();
+        let mut contents = HEADER.to_owned();
+        for Entry { lint, item, fingerprint } in self.recorded.drain(..) {
+            let _ = writeln!(contents, "{}\t{}\t{}", lint, item, fingerprint);
+        }
+        fs::write(path, contents)
+    }
+}
+
+/// Hashes the message of `diag` and the source text of its primary spans, with
+/// runs of whitespace collapsed so that reindenting the code doesn't matter.
+fn fingerprint(diag: &Diagnostic, sm: Option<&SourceMap>) -> String {
+    let mut hasher = StableHasher::new();
+    diag.message().hash(&mut hasher);
+    for &span in diag.span.primary_spans() {
+        let snippet = sm.and_then(|sm| sm.span_to_snippet(span).ok()).unwrap_or_default();
+        let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+        snippet.hash(&mut hasher);
+    }
+    let hash: u64 = hasher.finish();
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,317 @@
+//! An emitter that writes errors as a [SARIF 2.1.0] log, the format that code
+//! scanning tools consume.
+//!
+//! Unlike the JSON emitter, which prints every diagnostic as it comes in, a
+//! SARIF log is a single document. Diagnostics are converted to results as they
+//! are emitted, and the whole log is written out once the emitter is dropped.
+//!
+//! Each diagnostic becomes one result: its primary spans are the result's
+//! locations, its secondary spans and the children that have a span are related
+//! locations, and children without a span are appended to the message.
+//! Suggestions that are `MachineApplicable` become fixes, the other suggestions
+//! are reported as related locations.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::{CodeSuggestion, DiagnosticId, Level, SubDiagnostic};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_serialize::json::{Json, Object};
+use rustc_span::{FileName, Span};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const ERROR_INDEX: &str = "https://doc.rust-lang.org/error-index.html";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    sm: Lrc<SourceMap>,
+    pretty: bool,
+    /// The rules that the results refer to, by id, in the order they were first seen.
+    rules: FxIndexMap<String, Json>,
+    results: Vec<Json>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(source_map: Lrc<SourceMap>, pretty: bool) -> SarifEmitter {
+        SarifEmitter::new(Box::new(io::BufWriter::new(io::stderr())), source_map, pretty)
+    }
+
+    pub fn basic(pretty: bool) -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(Lrc::new(SourceMap::new(file_path_mapping)), pretty)
+    }
+
+    pub fn new(dst: Box<dyn Write + Send>, source_map: Lrc<SourceMap>, pretty: bool) -> Self {
+        SarifEmitter { dst, sm: source_map, pretty, rules: Default::default(), results: vec![] }
+    }
+
+    fn rule_id(&mut self, code: &DiagnosticId) -> String {
+        let id = match code {
+            DiagnosticId::Error(code) => code,
+            DiagnosticId::Lint { name, .. } => name,
+        };
+        self.rules.entry(id.clone()).or_insert_with(|| {
+            let mut rule = Object::new();
+            rule.insert("id".to_owned(), Json::String(id.clone()));
+            if let DiagnosticId::Error(code) = code {
+                let uri = format!("{}#{}", ERROR_INDEX, code);
+                rule.insert("helpUri".to_owned(), Json::String(uri));
+            }
+            Json::Object(rule)
+        });
+        id.clone()
+    }
+
+    fn result(&mut self, diag: &crate::Diagnostic) -> Option<Json> {
+        let level = match diag.level {
+            Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+            Level::Warning => "warning",
+            Level::Note | Level::OnceNote | Level::Help => "note",
+            // "For more information about this error" and the like, which only
+            // make sense next to the human readable output.
+            Level::FailureNote => return None,
+            Level::Allow | Level::Expect(_) => return None,
+        };
+
+        let mut result = Object::new();
+        if let Some(code) = &diag.code {
+            result.insert("ruleId".to_owned(), Json::String(self.rule_id(code)));
+        }
+        result.insert("level".to_owned(), Json::String(level.to_owned()));
+
+        let mut message = diag.message();
+        let mut locations = vec![];
+        let mut related = vec![];
+        for span_label in diag.span.span_labels() {
+            let location = self.location(span_label.span, span_label.label);
+            if span_label.is_primary {
+                locations.extend(location);
+            } else {
+                related.extend(location);
+            }
+        }
+        for child in &diag.children {
+            self.child(child, &mut message, &mut related);
+        }
+
+        let mut fixes = vec![];
+        for sugg in diag.suggestions.iter().flatten() {
+            if sugg.applicability == Applicability::MachineApplicable {
+                fixes.extend(sugg.substitutions.iter().map(|substitution| {
+                    let parts = substitution.parts.iter().map(|part| (part.span, &part.snippet));
+                    self.fix(&sugg.msg, parts)
+                }));
+            } else {
+                self.suggestion(sugg, &mut related);
+            }
+        }
+
+        result.insert("message".to_owned(), text(message));
+        if !locations.is_empty() {
+            result.insert("locations".to_owned(), Json::Array(locations));
+        }
+        if !related.is_empty() {
+            for (id, location) in related.iter_mut().enumerate() {
+                if let Json::Object(location) = location {
+                    location.insert("id".to_owned(), Json::U64(id as u64));
+                }
+            }
+            result.insert("relatedLocations".to_owned(), Json::Array(related));
+        }
+        if !fixes.is_empty() {
+            result.insert("fixes".to_owned(), Json::Array(fixes));
+        }
+        Some(Json::Object(result))
+    }
+
+    /// Adds a note or help to its parent result, as a related location when it
+    /// points somewhere and as part of the message otherwise.
+    fn child(&self, child: &SubDiagnostic, message: &mut String, related: &mut Vec<Json>) {
+        let msg = format!("{}: {}", child.level, child.message());
+        let span = child.render_span.as_ref().unwrap_or(&child.span);
+        let locations: Vec<_> = span
+            .primary_spans()
+            .iter()
+            .filter_map(|&span| self.location(span, Some(msg.clone())))
+            .collect();
+        if locations.is_empty() {
+            let _ = write!(message, "\n{}", msg);
+        } else {
+            related.extend(locations);
+        }
+    }
+
+    /// Reports a suggestion that can't be applied automatically, as a related
+    /// location for every part of it.
+    fn suggestion(&self, sugg: &CodeSuggestion, related: &mut Vec<Json>) {
+        for part in sugg.substitutions.iter().flat_map(|substitution| &substitution.parts) {
+            let msg = if part.snippet.is_empty() {
+                format!("help: {}: remove this", sugg.msg)
+            } else {
+                format!("help: {}: `{}`", sugg.msg, part.snippet)
+            };
+            related.extend(self.location(part.span, Some(msg)));
+        }
+    }
+
+    /// Turns one substitution of a suggestion into a fix, with the replacements
+    /// grouped by the file they apply to.
+    fn fix<'a>(&self, msg: &str, parts: impl Iterator<Item = (Span, &'a String)>) -> Json {
+        let mut changes: BTreeMap<String, Vec<Json>> = BTreeMap::new();
+        for (span, snippet) in parts {
+            let (uri, region) = match self.region(span) {
+                Some(location) => location,
+                None => continue,
+            };
+            let mut replacement = Object::new();
+            replacement.insert("deletedRegion".to_owned(), region);
+            replacement.insert("insertedContent".to_owned(), text(snippet.clone()));
+            changes.entry(uri).or_default().push(Json::Object(replacement));
+        }
+
+        let changes = changes
+            .into_iter()
+            .map(|(uri, replacements)| {
+                let mut change = Object::new();
+                change.insert("artifactLocation".to_owned(), artifact_location(uri));
+                change.insert("replacements".to_owned(), Json::Array(replacements));
+                Json::Object(change)
+            })
+            .collect();
+        let mut fix = Object::new();
+        fix.insert("description".to_owned(), text(msg.to_owned()));
+        fix.insert("artifactChanges".to_owned(), Json::Array(changes));
+        Json::Object(fix)
+    }
+
+    fn location(&self, span: Span, label: Option<String>) -> Option<Json> {
+        let (uri, region) = self.region(span)?;
+        let mut physical = Object::new();
+        physical.insert("artifactLocation".to_owned(), artifact_location(uri));
+        physical.insert("region".to_owned(), region);
+        let mut location = Object::new();
+        location.insert("physicalLocation".to_owned(), Json::Object(physical));
+        if let Some(label) = label {
+            location.insert("message".to_owned(), text(label));
+        }
+        Some(Json::Object(location))
+    }
+
+    /// Returns the URI of the file that `span` is in and the SARIF region it
+    /// covers, with 1-based lines and columns counted in characters.
+    fn region(&self, span: Span) -> Option<(String, Json)> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let mut region = Object::new();
+        region.insert("startLine".to_owned(), Json::U64(start.line as u64));
+        region.insert("startColumn".to_owned(), Json::U64(start.col.0 as u64 + 1));
+        region.insert("endLine".to_owned(), Json::U64(end.line as u64));
+        region.insert("endColumn".to_owned(), Json::U64(end.col.0 as u64 + 1));
+        Some((self.uri(&start.file.name), Json::Object(region)))
+    }
+
+    fn uri(&self, name: &FileName) -> String {
+        let path = self.sm.filename_for_diagnostics(name).to_string().replace('\\', "/");
+        let mut uri = String::with_capacity(path.len());
+        if path.starts_with('/') {
+            uri.push_str("file://");
+        } else if path.as_bytes().get(1) == Some(&b':') {
+            // A Windows path with a drive letter.
+            uri.push_str("file:///");
+        }
+        for b in path.bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => uri.push(b as char),
+                b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'$' => uri.push(b as char),
+                _ => {
+                    let _ = write!(uri, "%{:02X}", b);
+                }
+            }
+        }
+        uri
+    }
+
+    fn log(&mut self) -> Json {
+        let mut driver = Object::new();
+        driver.insert("name".to_owned(), Json::String("rustc".to_owned()));
+        driver.insert(
+            "informationUri".to_owned(),
+            Json::String("https://www.rust-lang.org/".to_owned()),
+        );
+        let rules = std::mem::take(&mut self.rules).into_iter().map(|(_, rule)| rule).collect();
+        driver.insert("rules".to_owned(), Json::Array(rules));
+        let mut tool = Object::new();
+        tool.insert("driver".to_owned(), Json::Object(driver));
+
+        let mut run = Object::new();
+        run.insert("tool".to_owned(), Json::Object(tool));
+        run.insert("columnKind".to_owned(), Json::String("unicodeCodePoints".to_owned()));
+        run.insert("results".to_owned(), Json::Array(std::mem::take(&mut self.results)));
+
+        let mut log = Object::new();
+        log.insert("$schema".to_owned(), Json::String(SCHEMA.to_owned()));
+        log.insert("version".to_owned(), Json::String("2.1.0".to_owned()));
+        log.insert("runs".to_owned(), Json::Array(vec![Json::Object(run)]));
+        Json::Object(log)
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        if let Some(result) = self.result(diag) {
+            self.results.push(result);
+        }
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    fn should_show_error_count(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let log = self.log();
+        let result = if self.pretty {
+            writeln!(&mut self.dst, "{}", log.pretty())
+        } else {
+            writeln!(&mut self.dst, "{}", log)
+        }
+        .and_then(|_| self.dst.flush());
+        if let Err(e) = result {
+            // Panicking in a destructor that runs while unwinding from a fatal
+            // error would abort, so only complain.
+            eprintln!("failed to print diagnostics: {:?}", e);
+        }
+    }
+}
+
+fn text(text: String) -> Json {
+    let mut message = Object::new();
+    message.insert("text".to_owned(), Json::String(text));
+    Json::Object(message)
+}
+
+fn artifact_location(uri: String) -> Json {
+    let mut location = Object::new();
+    location.insert("uri".to_owned(), Json::String(uri));
+    Json::Object(location)
+}
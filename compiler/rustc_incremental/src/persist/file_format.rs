@@ -8,10 +8,14 @@
 //! Since we can always just ignore the incremental compilation cache and
 //! compiler versions don't change frequently for the typical user, being
 //! conservative here practically has no downside.
+//!
+//! With `-Z incremental-compress`, files can also be stored compressed, header
+//! included, behind a different magic number. Reading such a file
+//! transparently decompresses it.
 
 use std::env;
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
 use rustc_data_structures::memmap::Mmap;
@@ -22,6 +26,10 @@
 /// The first few bytes of files generated by incremental compilation.
 const FILE_MAGIC: &[u8] = b"RSIC";
 
+/// The first few bytes of compressed files generated by incremental compilation.
+/// They are followed by the compressed contents of a file starting with `FILE_MAGIC`.
+const COMPRESSED_FILE_MAGIC: &[u8] = b"RSIZ";
+
 /// Change this if the header format changes.
 const HEADER_FORMAT_VERSION: u16 = 0;
 
@@ -104,6 +112,43 @@ pub(crate) fn save_in<F>(sess: &Session, path_buf: PathBuf, name: &str, encode:
     debug!("save: data written to disk successfully");
 }
 
+/// Compresses the file at `src_path`, which must start with a file header, into
+/// `dst_path`, and removes `src_path`.
+pub(crate) fn compress_file(sess: &Session, src_path: &Path, dst_path: &Path, name: &str) {
+    debug!("compress: compressing {} into {}", src_path.display(), dst_path.display());
+
+    let compress = || -> io::Result<()> {
+        let mut src = fs::File::open(src_path)?;
+
+        // Same as in `save_in`, the old file might be a shared hard-link.
+        match fs::remove_file(dst_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        let mut dst = io::BufWriter::new(fs::File::create(dst_path)?);
+        dst.write_all(COMPRESSED_FILE_MAGIC)?;
+        let mut dst = snap::write::FrameEncoder::new(dst);
+        io::copy(&mut src, &mut dst)?;
+        dst.flush()?;
+
+        fs::remove_file(src_path)
+    };
+
+    if let Err(err) = compress() {
+        sess.err(&format!("failed to compress {} into `{}`: {}", name, dst_path.display(), err));
+        return;
+    }
+
+    if let Ok(metadata) = fs::metadata(dst_path) {
+        sess.prof.artifact_size(
+            &format!("compressed_{}", name.replace(' ', "_")),
+            dst_path.file_name().unwrap().to_string_lossy(),
+            metadata.len(),
+        );
+    }
+}
+
 /// Reads the contents of a file with a file header as defined in this module.
 ///
 /// - Returns `Ok(Some(data, pos))` if the file existed and was generated by a
@@ -131,6 +176,16 @@ pub fn read_file(
     // There is no way to prevent another process from modifying this file.
     let mmap = unsafe { Mmap::map(file) }?;
 
+    // Decompress the whole file up front, as its contents are decoded from a memory map.
+    let mmap = match mmap.strip_prefix(COMPRESSED_FILE_MAGIC) {
+        Some(compressed) => {
+            let mut bytes = Vec::new();
+            snap::read::FrameDecoder::new(compressed).read_to_end(&mut bytes)?;
+            Mmap::from_bytes(&bytes)?
+        }
+        None => mmap,
+    };
+
     let mut file = io::Cursor::new(&*mmap);
 
     // Check FILE_MAGIC
@@ -50,6 +50,14 @@
 //! any more and will delete those. It will also delete any finalized session
 //! directories for a given crate except for the most recent one.
 //!
+//! This keeps one session directory per crate, which still adds up for
+//! incremental compilation directories shared by many crates. With
+//! `-Z incremental-cache-budget`, the compiler additionally deletes the
+//! finalized session directories of other crates, least recently used first,
+//! once the whole incremental compilation directory exceeds the given size. If
+//! that is not enough, it deletes the work products of its own session, which
+//! the next session will then have to recompile.
+//!
 //! ## Synchronization
 //!
 //! There is some synchronization needed in order for the compiler to be able to
@@ -126,6 +134,7 @@
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
 const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
 const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+const STAGING_QUERY_CACHE_FILENAME: &str = "query-cache.part.bin";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -151,6 +160,11 @@ pub fn work_products_path(sess: &Session) -> PathBuf {
 pub fn query_cache_path(sess: &Session) -> PathBuf {
     in_incr_comp_dir_sess(sess, QUERY_CACHE_FILENAME)
 }
+/// Returns the path that a session's query cache is written to before being
+/// compressed, with `-Z incremental-compress`.
+pub fn staging_query_cache_path(sess: &Session) -> PathBuf {
+    in_incr_comp_dir_sess(sess, STAGING_QUERY_CACHE_FILENAME)
+}
 
 /// Locks a given session directory.
 pub fn lock_file_path(session_dir: &Path) -> PathBuf {
@@ -394,6 +408,10 @@ pub fn finalize_session_directory(sess: &Session, svh: Svh) {
     }
 
     let _ = garbage_collect_session_directories(sess);
+
+    if let Some(budget) = sess.opts.debugging_opts.incremental_cache_budget {
+        let _ = enforce_cache_budget(sess, budget);
+    }
 }
 
 pub fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()> {
@@ -903,6 +921,162 @@ pub fn garbage_collect_session_directories(sess: &Session) -> io::Result<()> {
     Ok(())
 }
 
+/// Deletes session directories and work products until the incremental
+/// compilation directory takes at most `budget` bytes.
+///
+/// The finalized session directories of all crates are deleted first, least
+/// recently used first, as the timestamp of a session directory is the last
+/// time that the crate was compiled. Directories that are locked by another
+/// compiler instance are left alone. Then, the work products of the current
+/// session are deleted, the largest first. Its dep-graph and query result cache
+/// are always kept.
+fn enforce_cache_budget(sess: &Session, budget: u64) -> io::Result<()> {
+    let _timer = sess.timer("incr_comp_enforce_cache_budget");
+
+    let incr_comp_dir = sess.opts.incremental.as_ref().unwrap();
+    let session_directory = sess.incr_comp_session_dir().clone();
+    debug!("enforce_cache_budget() - budget: {} bytes", budget);
+
+    let mut total_size = 0;
+    let mut eviction_candidates = vec![];
+
+    for crate_dir_entry in incr_comp_dir.read_dir()? {
+        let Ok(crate_dir_entry) = crate_dir_entry else {
+            // Ignore any errors
+            continue;
+        };
+        let Ok(dir_entries) = crate_dir_entry.path().read_dir() else {
+            // Not a crate directory
+            continue;
+        };
+
+        for dir_entry in dir_entries {
+            let Ok(dir_entry) = dir_entry else {
+                continue;
+            };
+
+            let entry_name = dir_entry.file_name();
+            let entry_name = entry_name.to_string_lossy();
+            if !is_session_directory(&entry_name) {
+                continue;
+            }
+
+            let path = dir_entry.path();
+            let size = dir_size(&path);
+            total_size += size;
+
+            if path == session_directory || !is_finalized(&entry_name) {
+                continue;
+            }
+            let Ok(timestamp) = extract_timestamp_from_session_dir(&entry_name) else {
+                continue;
+            };
+            eviction_candidates.push((timestamp, path, size));
+        }
+    }
+
+    debug!("enforce_cache_budget() - total size: {} bytes", total_size);
+
+    eviction_candidates.sort_by_key(|&(timestamp, ..)| timestamp);
+    for (_, path, size) in eviction_candidates {
+        if total_size <= budget {
+            return Ok(());
+        }
+
+        let lock_file_path = lock_file_path(&path);
+        // get an exclusive lock
+        let Ok(lock) = flock::Lock::new(&lock_file_path, false, false, true) else {
+            debug!("enforce_cache_budget() - not evicting `{}`, still in use", path.display());
+            continue;
+        };
+
+        debug!("enforce_cache_budget() - evicting `{}`", path.display());
+        if let Err(err) = safe_remove_dir_all(&path) {
+            sess.warn(&format!(
+                "Failed to evict incremental compilation session directory `{}`: {}",
+                path.display(),
+                err
+            ));
+        } else {
+            delete_session_dir_lock_file(sess, &lock_file_path);
+            total_size -= size;
+        }
+
+        mem::drop(lock);
+    }
+
+    if total_size <= budget {
+        return Ok(());
+    }
+
+    let session_dir_name = session_directory.file_name().unwrap().to_string_lossy();
+    if !is_finalized(&session_dir_name) {
+        // The session was invalidated, there is nothing to keep.
+        return Ok(());
+    }
+
+    // Another compiler instance might be copying from the session directory
+    // now that it is finalized.
+    let Ok(lock) = flock::Lock::new(&lock_file_path(&session_directory), false, false, true) else {
+        debug!("enforce_cache_budget() - not evicting work products, session in use");
+        return Ok(());
+    };
+
+    let mut work_products = vec![];
+    for dir_entry in session_directory.read_dir()? {
+        let Ok(dir_entry) = dir_entry else {
+            continue;
+        };
+        let file_name = dir_entry.file_name();
+        if [DEP_GRAPH_FILENAME, WORK_PRODUCTS_FILENAME, QUERY_CACHE_FILENAME]
+            .iter()
+            .any(|&name| file_name == name)
+        {
+            continue;
+        }
+        match dir_entry.metadata() {
+            Ok(metadata) if metadata.is_file() => {
+                work_products.push((metadata.len(), dir_entry.path()))
+            }
+            _ => {}
+        }
+    }
+
+    work_products.sort_by(|a, b| b.cmp(a));
+    for (size, path) in work_products {
+        if total_size <= budget {
+            break;
+        }
+
+        debug!("enforce_cache_budget() - evicting work product `{}`", path.display());
+        if let Err(err) = safe_remove_file(&path) {
+            sess.warn(&format!(
+                "Failed to evict incremental compilation work product `{}`: {}",
+                path.display(),
+                err
+            ));
+        } else {
+            total_size -= size;
+        }
+    }
+
+    mem::drop(lock);
+
+    Ok(())
+}
+
+/// Returns the total size of the files in the given session directory.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(dir_entries) = path.read_dir() else {
+        return 0;
+    };
+    dir_entries
+        .filter_map(|dir_entry| dir_entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
 fn delete_old(sess: &Session, path: &Path) {
     debug!("garbage_collect_session_directories() - deleting `{}`", path.display());
 
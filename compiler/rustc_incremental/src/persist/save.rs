@@ -33,8 +33,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
         }
 
         let query_cache_path = query_cache_path(sess);
+        let staging_query_cache_path = staging_query_cache_path(sess);
         let dep_graph_path = dep_graph_path(sess);
         let staging_dep_graph_path = staging_dep_graph_path(sess);
+        let compress = sess.opts.debugging_opts.incremental_compress;
 
         sess.time("assert_dep_graph", || crate::assert_dep_graph(tcx));
         sess.time("check_dirty_clean", || dirty_clean::check_dirty_clean_annotations(tcx));
@@ -51,9 +53,24 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
                         odc.drop_serialized_data(tcx);
                     }
 
-                    file_format::save_in(sess, query_cache_path, "query cache", |e| {
-                        encode_query_cache(tcx, e)
-                    });
+                    if compress {
+                        file_format::save_in(
+                            sess,
+                            staging_query_cache_path.clone(),
+                            "query cache",
+                            |e| encode_query_cache(tcx, e),
+                        );
+                        file_format::compress_file(
+                            sess,
+                            &staging_query_cache_path,
+                            &query_cache_path,
+                            "query cache",
+                        );
+                    } else {
+                        file_format::save_in(sess, query_cache_path, "query cache", |e| {
+                            encode_query_cache(tcx, e)
+                        });
+                    }
                 });
             },
             move || {
@@ -65,7 +82,14 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
                             err
                         ));
                     }
-                    if let Err(err) = fs::rename(&staging_dep_graph_path, &dep_graph_path) {
+                    if compress {
+                        file_format::compress_file(
+                            sess,
+                            &staging_dep_graph_path,
+                            &dep_graph_path,
+                            "dependency graph",
+                        );
+                    } else if let Err(err) = fs::rename(&staging_dep_graph_path, &dep_graph_path) {
                         sess.err(&format!(
                             "failed to move dependency graph from `{}` to `{}`: {}",
                             staging_dep_graph_path.display(),
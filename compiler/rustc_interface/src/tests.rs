@@ -3,10 +3,11 @@
 use rustc_data_structures::fx::FxHashSet;
 use rustc_errors::{emitter::HumanReadableErrorType, registry, ColorConfig};
 use rustc_session::config::InstrumentCoverage;
-use rustc_session::config::Strip;
+use rustc_session::config::{StackSize, Strip};
 use rustc_session::config::{build_configuration, build_session_options, to_crate_config};
 use rustc_session::config::{
-    rustc_optgroups, ErrorOutputType, ExternLocation, LocationDetail, Options, Passes,
+    rustc_optgroups, DumpMonoStatsFormat, ErrorOutputType, ExternLocation,
+    IncrementalExplainFormat, LocationDetail, Options, Passes, PrintTypeSizes,
 };
 use rustc_session::config::{
     BranchProtection, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey, PacRet,
@@ -544,9 +545,12 @@ macro_rules! untracked {
     untracked!(codegen_units, Some(42));
     untracked!(default_linker_libraries, true);
     untracked!(extra_filename, String::from("extra-filename"));
+    untracked!(image_base, Some(0x400000));
     untracked!(incremental, Some(String::from("abc")));
+    untracked!(large_address_aware, Some(true));
     // `link_arg` is omitted because it just forwards to `link_args`.
     untracked!(link_args, vec![String::from("abc"), String::from("def")]);
+    untracked!(link_map, SwitchWithOptPath::Enabled(None));
     untracked!(link_self_contained, Some(true));
     untracked!(linker, Some(PathBuf::from("linker")));
     untracked!(linker_flavor, Some(LinkerFlavor::Gcc));
@@ -554,6 +558,7 @@ macro_rules! untracked {
     untracked!(remark, Passes::Some(vec![String::from("pass1"), String::from("pass2")]));
     untracked!(rpath, true);
     untracked!(save_temps, true);
+    untracked!(stack_size, Some(StackSize { reserve: 0x100000, commit: Some(0x1000) }));
     untracked!(strip, Strip::Debuginfo);
 
     macro_rules! tracked {
@@ -624,6 +629,8 @@ macro_rules! tracked {
         real_rust_source_base_dir,
         Some("/home/bors/rust/.rustup/toolchains/nightly/lib/rustlib/src/rust".into())
     );
+    tracked!(lint_baseline, Some("lints.baseline".into()));
+    tracked!(write_lint_baseline, Some("lints.baseline".into()));
 }
 
 #[test]
@@ -645,6 +652,7 @@ macro_rules! untracked {
     untracked!(ast_json, true);
     untracked!(ast_json_noexpand, true);
     untracked!(borrowck, String::from("other"));
+    untracked!(compare_type_sizes, Some(PathBuf::from("type-sizes.json")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dep_tasks, true);
     untracked!(dlltool, Some(PathBuf::from("custom_dlltool.exe")));
@@ -655,10 +663,14 @@ macro_rules! untracked {
     untracked!(dump_mir_dir, String::from("abc"));
     untracked!(dump_mir_exclude_pass_number, true);
     untracked!(dump_mir_graphviz, true);
+    untracked!(dump_mono_stats, Some(DumpMonoStatsFormat::Json));
     untracked!(emit_stack_sizes, true);
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
+    untracked!(incremental_cache_budget, Some(1 << 30));
+    untracked!(incremental_compress, true);
+    untracked!(incremental_explain, Some(IncrementalExplainFormat::Json));
     untracked!(incremental_ignore_spans, true);
     untracked!(incremental_info, true);
     untracked!(incremental_verify_ich, true);
@@ -681,7 +693,7 @@ macro_rules! untracked {
     untracked!(profile_closures, true);
     untracked!(print_llvm_passes, true);
     untracked!(print_mono_items, Some(String::from("abc")));
-    untracked!(print_type_sizes, true);
+    untracked!(print_type_sizes, Some(PrintTypeSizes::Json));
     untracked!(proc_macro_backtrace, true);
     untracked!(query_dep_graph, true);
     untracked!(save_analysis, true);
@@ -702,6 +714,7 @@ macro_rules! untracked {
     untracked!(unstable_options, true);
     untracked!(validate_mir, true);
     untracked!(verbose, true);
+    untracked!(verify_target_cpu, true);
 
     macro_rules! tracked {
         ($name: ident, $non_default_value: expr) => {
@@ -767,6 +780,7 @@ macro_rules! tracked {
     tracked!(polonius, true);
     tracked!(precise_enum_drop_elaboration, false);
     tracked!(print_fuel, Some("abc".to_string()));
+    tracked!(print_stack_usage, true);
     tracked!(profile, true);
     tracked!(profile_emit, Some(PathBuf::from("abc")));
     tracked!(profiler_runtime, "abc".to_string());
@@ -575,6 +575,9 @@ pub struct LateContext<'tcx> {
 pub struct EarlyContext<'a> {
     pub builder: LintLevelsBuilder<'a>,
     pub buffered: LintBuffer,
+    /// The path of the item being checked, which lints are recorded with when
+    /// lint baselines are used.
+    pub(crate) item_path: Vec<String>,
 }
 
 pub trait LintPassObject: Sized {}
@@ -877,6 +880,7 @@ pub(crate) fn new(
                 registered_tools,
             ),
             buffered,
+            item_path: Vec::new(),
         }
     }
 }
@@ -926,7 +930,12 @@ fn lookup<S: Into<MultiSpan>>(
         span: Option<S>,
         decorate: impl for<'a> FnOnce(LintDiagnosticBuilder<'a, ()>),
     ) {
-        self.builder.struct_lint(lint, span.map(|s| s.into()), decorate)
+        self.builder.struct_lint(lint, span.map(|s| s.into()), |mut lint| {
+            if self.sess().uses_lint_baseline() {
+                lint.set_item(self.item_path.join("::"));
+            }
+            decorate(lint)
+        })
     }
 }
 
@@ -20,10 +20,11 @@
 use rustc_ast::visit::{self as ast_visit, Visitor};
 use rustc_ast::AstLike;
 use rustc_ast::{self as ast, walk_list};
+use rustc_ast_pretty::pprust;
 use rustc_middle::ty::RegisteredTools;
 use rustc_session::lint::{BufferedEarlyLint, LintBuffer, LintPass};
 use rustc_session::Session;
-use rustc_span::symbol::Ident;
+use rustc_span::symbol::{kw, Ident};
 use rustc_span::Span;
 
 use std::slice;
@@ -70,6 +71,21 @@ fn with_lint_attrs<F>(&mut self, id: ast::NodeId, attrs: &'a [ast::Attribute], f
         self.context.builder.pop(push);
     }
 
+    /// Adds the name of an item to the path that lints are recorded with for
+    /// lint baselines while calling the provided function.
+    fn with_item_name<F>(&mut self, name: impl FnOnce() -> Option<String>, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let name = if self.context.sess().uses_lint_baseline() { name() } else { None };
+        let pushed = name.is_some();
+        self.context.item_path.extend(name);
+        f(self);
+        if pushed {
+            self.context.item_path.pop();
+        }
+    }
+
     fn enter_attrs(&mut self, attrs: &'a [ast::Attribute]) {
         debug!("early context: enter_attrs({:?})", attrs);
         run_early_pass!(self, enter_lint_attrs, attrs);
@@ -90,19 +106,29 @@ fn visit_param(&mut self, param: &'a ast::Param) {
     }
 
     fn visit_item(&mut self, it: &'a ast::Item) {
-        self.with_lint_attrs(it.id, &it.attrs, |cx| {
-            run_early_pass!(cx, check_item, it);
-            ast_visit::walk_item(cx, it);
-            run_early_pass!(cx, check_item_post, it);
-        })
+        self.with_item_name(
+            || item_name(it),
+            |cx| {
+                cx.with_lint_attrs(it.id, &it.attrs, |cx| {
+                    run_early_pass!(cx, check_item, it);
+                    ast_visit::walk_item(cx, it);
+                    run_early_pass!(cx, check_item_post, it);
+                })
+            },
+        )
     }
 
     fn visit_foreign_item(&mut self, it: &'a ast::ForeignItem) {
-        self.with_lint_attrs(it.id, &it.attrs, |cx| {
-            run_early_pass!(cx, check_foreign_item, it);
-            ast_visit::walk_foreign_item(cx, it);
-            run_early_pass!(cx, check_foreign_item_post, it);
-        })
+        self.with_item_name(
+            || Some(it.ident.to_string()),
+            |cx| {
+                cx.with_lint_attrs(it.id, &it.attrs, |cx| {
+                    run_early_pass!(cx, check_foreign_item, it);
+                    ast_visit::walk_foreign_item(cx, it);
+                    run_early_pass!(cx, check_foreign_item_post, it);
+                })
+            },
+        )
     }
 
     fn visit_pat(&mut self, p: &'a ast::Pat) {
@@ -259,18 +285,23 @@ fn visit_poly_trait_ref(&mut self, t: &'a ast::PolyTraitRef, m: &'a ast::TraitBo
     }
 
     fn visit_assoc_item(&mut self, item: &'a ast::AssocItem, ctxt: ast_visit::AssocCtxt) {
-        self.with_lint_attrs(item.id, &item.attrs, |cx| match ctxt {
-            ast_visit::AssocCtxt::Trait => {
-                run_early_pass!(cx, check_trait_item, item);
-                ast_visit::walk_assoc_item(cx, item, ctxt);
-                run_early_pass!(cx, check_trait_item_post, item);
-            }
-            ast_visit::AssocCtxt::Impl => {
-                run_early_pass!(cx, check_impl_item, item);
-                ast_visit::walk_assoc_item(cx, item, ctxt);
-                run_early_pass!(cx, check_impl_item_post, item);
-            }
-        });
+        self.with_item_name(
+            || Some(item.ident.to_string()),
+            |cx| {
+                cx.with_lint_attrs(item.id, &item.attrs, |cx| match ctxt {
+                    ast_visit::AssocCtxt::Trait => {
+                        run_early_pass!(cx, check_trait_item, item);
+                        ast_visit::walk_assoc_item(cx, item, ctxt);
+                        run_early_pass!(cx, check_trait_item_post, item);
+                    }
+                    ast_visit::AssocCtxt::Impl => {
+                        run_early_pass!(cx, check_impl_item, item);
+                        ast_visit::walk_assoc_item(cx, item, ctxt);
+                        run_early_pass!(cx, check_impl_item_post, item);
+                    }
+                })
+            },
+        );
     }
 
     fn visit_lifetime(&mut self, lt: &'a ast::Lifetime) {
@@ -299,6 +330,24 @@ fn visit_mac_call(&mut self, mac: &'a ast::MacCall) {
     }
 }
 
+/// Returns the name of `item` in the path of the items that contain a lint,
+/// written like the def paths that late lints are recorded with.
+fn item_name(item: &ast::Item) -> Option<String> {
+    match &item.kind {
+        ast::ItemKind::Impl(impl_) => {
+            let self_ty = pprust::ty_to_string(&impl_.self_ty);
+            Some(match &impl_.of_trait {
+                Some(trait_ref) => {
+                    format!("<impl {} for {}>", pprust::path_to_string(&trait_ref.path), self_ty)
+                }
+                None => format!("<impl {}>", self_ty),
+            })
+        }
+        _ if item.ident.name != kw::Empty => Some(item.ident.to_string()),
+        _ => None,
+    }
+}
+
 struct EarlyLintPassObjects<'a> {
     lints: &'a mut [EarlyLintPassObject],
 }
@@ -1063,6 +1063,17 @@ extern "C" void LLVMRustUnpackOptimizationDiagnostic(
   MessageOS << Opt->getMsg();
 }
 
+extern "C" void
+LLVMRustUnpackStackSizeDiagnostic(LLVMDiagnosticInfoRef DI,
+                                  LLVMValueRef *FunctionOut,
+                                  uint64_t *SizeOut) {
+  // Undefined to call this not on a stack size diagnostic!
+  llvm::DiagnosticInfoStackSize *SS =
+      static_cast<llvm::DiagnosticInfoStackSize *>(unwrap(DI));
+  *FunctionOut = wrap(&SS->getFunction());
+  *SizeOut = SS->getResourceSize();
+}
+
 enum class LLVMRustDiagnosticLevel {
     Error,
     Warning,
@@ -60,7 +60,8 @@ fn init() { }
         LLVMInitializeX86Target,
         LLVMInitializeX86TargetMC,
         LLVMInitializeX86AsmPrinter,
-        LLVMInitializeX86AsmParser
+        LLVMInitializeX86AsmParser,
+        LLVMInitializeX86Disassembler
     );
     init_target!(
         llvm_component = "arm",
@@ -232,6 +232,11 @@ pub fn build(mut self, msg: &str) -> DiagnosticBuilder<'a, G> {
         self.0
     }
 
+    /// Record the path of the item that the lint is reported in (see `--lint-baseline`).
+    pub fn set_item(&mut self, item: String) {
+        self.0.set_lint_item(item);
+    }
+
     /// Create a `LintDiagnosticBuilder` from some existing `DiagnosticBuilder`.
     pub fn new(err: DiagnosticBuilder<'a, G>) -> LintDiagnosticBuilder<'a, G> {
         LintDiagnosticBuilder(err)
@@ -112,6 +112,12 @@ pub enum CoverageKind {
         op: Op,
         rhs: ExpressionOperandId,
     },
+    /// The outcomes of a boolean condition, counted by the counters or expressions of the edges
+    /// taken when the condition is true and when it is false.
+    Branch {
+        true_counter: ExpressionOperandId,
+        false_counter: ExpressionOperandId,
+    },
     Unreachable,
 }
 
@@ -121,6 +127,7 @@ pub fn as_operand_id(&self) -> ExpressionOperandId {
         match *self {
             Counter { id, .. } => ExpressionOperandId::from(id),
             Expression { id, .. } => ExpressionOperandId::from(id),
+            Branch { .. } => bug!("Branch coverage cannot be part of an expression"),
             Unreachable => bug!("Unreachable coverage cannot be part of an expression"),
         }
     }
@@ -143,6 +150,12 @@ fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
                 if *op == Op::Add { "+" } else { "-" },
                 rhs.index(),
             ),
+            Branch { true_counter, false_counter } => write!(
+                fmt,
+                "Branch(true: {}, false: {})",
+                true_counter.index(),
+                false_counter.index(),
+            ),
             Unreachable => write!(fmt, "Unreachable"),
         }
     }
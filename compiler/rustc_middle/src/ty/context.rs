@@ -13,6 +13,7 @@
 };
 use crate::thir::Thir;
 use crate::traits;
+use crate::ty::print::with_no_trimmed_paths;
 use crate::ty::query::{self, TyCtxtAt};
 use crate::ty::subst::{GenericArg, GenericArgKind, InternalSubsts, Subst, SubstsRef, UserSubsts};
 use crate::ty::TyKind::*;
@@ -2747,7 +2748,10 @@ pub fn struct_span_lint_hir(
         decorate: impl for<'a> FnOnce(LintDiagnosticBuilder<'a, ()>),
     ) {
         let (level, src) = self.lint_level_at_node(lint, hir_id);
-        struct_lint_level(self.sess, lint, level, src, Some(span.into()), decorate);
+        struct_lint_level(self.sess, lint, level, src, Some(span.into()), |mut lint| {
+            self.set_lint_item(&mut lint, hir_id);
+            decorate(lint)
+        });
     }
 
     pub fn struct_lint_node(
@@ -2757,7 +2761,19 @@ pub fn struct_lint_node(
         decorate: impl for<'a> FnOnce(LintDiagnosticBuilder<'a, ()>),
     ) {
         let (level, src) = self.lint_level_at_node(lint, id);
-        struct_lint_level(self.sess, lint, level, src, None, decorate);
+        struct_lint_level(self.sess, lint, level, src, None, |mut lint| {
+            self.set_lint_item(&mut lint, id);
+            decorate(lint)
+        });
+    }
+
+    /// Records the item that `id` is in on a lint, so that lint baselines can
+    /// tell apart identical lints in different items.
+    fn set_lint_item(self, lint: &mut LintDiagnosticBuilder<'_, ()>, id: HirId) {
+        if self.sess.uses_lint_baseline() {
+            let item = with_no_trimmed_paths!(self.def_path_str(id.owner.to_def_id()));
+            lint.set_item(item);
+        }
     }
 
     pub fn in_scope_traits(self, id: HirId) -> Option<&'tcx [TraitCandidate]> {
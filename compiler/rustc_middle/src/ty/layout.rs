@@ -10,7 +10,7 @@
 use rustc_hir::lang_items::LangItem;
 use rustc_index::bit_set::BitSet;
 use rustc_index::vec::{Idx, IndexVec};
-use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use rustc_span::symbol::Symbol;
 use rustc_span::{Span, DUMMY_SP};
 use rustc_target::abi::call::{
@@ -1773,9 +1773,9 @@ fn generator_layout(
     /// layout of each type.
     #[inline(always)]
     fn record_layout_for_printing(&self, layout: TyAndLayout<'tcx>) {
-        // If we are running with `-Zprint-type-sizes`, maybe record layouts
-        // for dumping later.
-        if self.tcx.sess.opts.debugging_opts.print_type_sizes {
+        // If we are running with `-Zprint-type-sizes` or `-Zcompare-type-sizes`,
+        // maybe record layouts for dumping later.
+        if self.tcx.sess.records_type_sizes() {
             self.record_layout_for_printing_outlined(layout)
         }
     }
@@ -1789,8 +1789,13 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
         }
 
         // (delay format until we actually need it)
-        let record = |kind, packed, opt_discr_size, variants| {
+        let record = |kind, packed, opt_discr_size, niche_discr, variants| {
             let type_desc = format!("{:?}", layout.ty);
+            let largest_niche = layout.largest_niche.map(|niche| NicheInfo {
+                offset: niche.offset.bytes(),
+                size: niche.scalar.value.size(self).bytes(),
+                values: niche.available(self),
+            });
             self.tcx.sess.code_stats.record_type_size(
                 kind,
                 type_desc,
@@ -1798,6 +1803,8 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
                 layout.size,
                 packed,
                 opt_discr_size,
+                niche_discr,
+                largest_niche,
                 variants,
             );
         };
@@ -1810,7 +1817,7 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
 
             ty::Closure(..) => {
                 debug!("print-type-size t: `{:?}` record closure", layout.ty);
-                record(DataTypeKind::Closure, false, None, vec![]);
+                record(DataTypeKind::Closure, false, None, None, vec![]);
                 return;
             }
 
@@ -1867,16 +1874,17 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
                         adt_kind.into(),
                         adt_packed,
                         None,
+                        None,
                         vec![build_variant_info(Some(variant_def.name), &fields, layout)],
                     );
                 } else {
                     // (This case arises for *empty* enums; so give it
                     // zero variants.)
-                    record(adt_kind.into(), adt_packed, None, vec![]);
+                    record(adt_kind.into(), adt_packed, None, None, vec![]);
                 }
             }
 
-            Variants::Multiple { tag, ref tag_encoding, .. } => {
+            Variants::Multiple { tag, ref tag_encoding, tag_field, .. } => {
                 debug!(
                     "print-type-size `{:#?}` adt general variants def {}",
                     layout.ty,
@@ -1901,6 +1909,16 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
                         TagEncoding::Direct => Some(tag.value.size(self)),
                         _ => None,
                     },
+                    match tag_encoding {
+                        TagEncoding::Direct => None,
+                        TagEncoding::Niche { niche_variants, .. } => Some(NicheInfo {
+                            offset: layout.fields.offset(tag_field).bytes(),
+                            size: tag.value.size(self).bytes(),
+                            values: u128::from(
+                                niche_variants.end().as_u32() - niche_variants.start().as_u32(),
+                            ) + 1,
+                        }),
+                    },
                     variant_infos,
                 );
             }
@@ -160,6 +160,13 @@ impl<'a, 'tcx> Builder<'a, 'tcx> {
                 // [block: If(lhs)] -false-> [else_block: dest = (rhs)]
                 //        | (true)
                 //  [shortcurcuit_block: dest = true]
+                //
+                // With branch coverage, `else_block` branches on `rhs` instead, so that the
+                // `InstrumentCoverage` pass counts how many times each operand was true and false:
+                //
+                // [else_block: If(rhs)] -true-> [dest = true]
+                //        | (false)
+                //  [dest = false]
 
                 let (shortcircuit_block, mut else_block, join_block) = (
                     this.cfg.start_new_block(),
@@ -190,9 +197,30 @@ impl<'a, 'tcx> Builder<'a, 'tcx> {
                 );
                 this.cfg.goto(shortcircuit_block, source_info, join_block);
 
+                let rhs_span = this.thir[rhs].span;
                 let rhs = unpack!(else_block = this.as_local_operand(else_block, &this.thir[rhs]));
-                this.cfg.push_assign(else_block, source_info, destination, Rvalue::Use(rhs));
-                this.cfg.goto(else_block, source_info, join_block);
+                if this.tcx.sess.instrument_coverage_branch() {
+                    let (true_block, false_block) =
+                        (this.cfg.start_new_block(), this.cfg.start_new_block());
+                    let term = TerminatorKind::if_(this.tcx, rhs, true_block, false_block);
+                    this.cfg.terminate(else_block, this.source_info(rhs_span), term);
+                    for (block, value) in [(true_block, true), (false_block, false)] {
+                        this.cfg.push_assign_constant(
+                            block,
+                            source_info,
+                            destination,
+                            Constant {
+                                span: rhs_span,
+                                user_ty: None,
+                                literal: ConstantKind::from_bool(this.tcx, value),
+                            },
+                        );
+                        this.cfg.goto(block, source_info, join_block);
+                    }
+                } else {
+                    this.cfg.push_assign(else_block, source_info, destination, Rvalue::Use(rhs));
+                    this.cfg.goto(else_block, source_info, join_block);
+                }
 
                 join_block.unit()
             }
@@ -306,6 +306,11 @@ pub fn format_counter(&self, counter_kind: &CoverageKind) -> String {
             CoverageKind::Expression { .. } => {
                 format!("Expression({})", self.format_counter_kind(counter_kind))
             }
+            CoverageKind::Branch { true_counter, false_counter } => format!(
+                "Branch(true: {}, false: {})",
+                self.format_operand(true_counter),
+                self.format_operand(false_counter),
+            ),
             CoverageKind::Unreachable { .. } => "Unreachable".to_owned(),
         }
     }
@@ -9,7 +9,7 @@
 mod tests;
 
 use counters::CoverageCounters;
-use graph::{BasicCoverageBlock, BasicCoverageBlockData, CoverageGraph};
+use graph::{BasicCoverageBlock, BasicCoverageBlockData, BcbBranch, CoverageGraph};
 use spans::{CoverageSpan, CoverageSpans};
 
 use crate::MirPass;
@@ -43,6 +43,15 @@ pub fn from_string<T>(message: String) -> Result<T, Error> {
     }
 }
 
+/// A `BasicCoverageBlock` ending in a `SwitchInt` on a `bool` condition, reported as a branch
+/// region with `-C instrument-coverage=branch`.
+struct BranchCondition {
+    bcb: BasicCoverageBlock,
+    true_branch: BcbBranch,
+    false_branch: BcbBranch,
+    span: Span,
+}
+
 /// Inserts `StatementKind::Coverage` statements that either instrument the binary with injected
 /// counters, via intrinsic `llvm.instrprof.increment`, and/or inject metadata used during codegen
 /// to construct the coverage map.
@@ -193,6 +202,18 @@ fn inject_counters(&'a mut self) {
             );
         }
 
+        ////////////////////////////////////////////////////
+        // With `-C instrument-coverage=branch`, find the `bool` conditions to be reported as
+        // branch regions. Their `BasicCoverageBlock`s, and the branches from them, must be counted
+        // even if they have no `CoverageSpan`.
+        let branch_conditions = if tcx.sess.instrument_coverage_branch() {
+            self.branch_conditions()
+        } else {
+            Vec::new()
+        };
+        let branching_bcbs =
+            branch_conditions.iter().map(|condition| condition.bcb).collect::<Vec<_>>();
+
         ////////////////////////////////////////////////////
         // Create an optimized mix of `Counter`s and `Expression`s for the `CoverageGraph`. Ensure
         // every `CoverageSpan` has a `Counter` or `Expression` assigned to its `BasicCoverageBlock`
@@ -201,9 +222,11 @@ fn inject_counters(&'a mut self) {
         //
         // Intermediate expressions (used to compute other `Expression` values), which have no
         // direct associate to any `BasicCoverageBlock`, are returned in the method `Result`.
-        let intermediate_expressions_or_error = self
-            .coverage_counters
-            .make_bcb_counters(&mut self.basic_coverage_blocks, &coverage_spans);
+        let intermediate_expressions_or_error = self.coverage_counters.make_bcb_counters(
+            &mut self.basic_coverage_blocks,
+            &coverage_spans,
+            &branching_bcbs,
+        );
 
         let (result, intermediate_expressions) = match intermediate_expressions_or_error {
             Ok(intermediate_expressions) => {
@@ -215,6 +238,11 @@ fn inject_counters(&'a mut self) {
                     }
                 }
 
+                ////////////////////////////////////////////////////
+                // Look up the counters of the true and false branches of each branch condition,
+                // before the injection steps below take them from the `CoverageGraph`.
+                let branch_regions = self.make_branch_regions(branch_conditions);
+
                 ////////////////////////////////////////////////////
                 // Remove the counter or edge counter from of each `CoverageSpan`s associated
                 // `BasicCoverageBlock`, and inject a `Coverage` statement into the MIR.
@@ -239,6 +267,12 @@ fn inject_counters(&'a mut self) {
                 // their own independent code region's coverage.
                 self.inject_indirect_counters(&mut graphviz_data, &mut debug_used_expressions);
 
+                ////////////////////////////////////////////////////
+                // Inject a `Branch` statement, with the code region of its condition, for each
+                // branch condition. These add branch regions to the coverage map, but do not
+                // generate executable code.
+                self.inject_branch_regions(branch_regions);
+
                 // Intermediate expressions will be injected as the final step, after generating
                 // debug output, if any.
                 ////////////////////////////////////////////////////
@@ -416,6 +450,112 @@ fn inject_indirect_counters(
         }
     }
 
+    /// Returns each `BasicCoverageBlock` ending in a `SwitchInt` on a `bool`, with its true and
+    /// false branches and the span of its condition.
+    fn branch_conditions(&self) -> Vec<BranchCondition> {
+        let mut branch_conditions = Vec::new();
+        for (bcb, bcb_data) in self.basic_coverage_blocks.iter_enumerated() {
+            let bb = bcb_data.last_bb();
+            let kind = &self.mir_body[bb].terminator().kind;
+            let TerminatorKind::SwitchInt { discr, switch_ty, targets } = kind else {
+                continue;
+            };
+            if !switch_ty.is_bool() {
+                continue;
+            }
+            let Some(span) = self.condition_span(bb, discr) else {
+                continue;
+            };
+            // A `bool` switch goes to the false target on `0`, and to `otherwise` on `1`.
+            let false_bcb = self.basic_coverage_blocks.bcb_from_bb(targets.target_for_value(0));
+            let true_bcb = self.basic_coverage_blocks.bcb_from_bb(targets.otherwise());
+            let (Some(true_bcb), Some(false_bcb)) = (true_bcb, false_bcb) else {
+                continue;
+            };
+            if true_bcb == false_bcb {
+                continue;
+            }
+            branch_conditions.push(BranchCondition {
+                bcb,
+                true_branch: BcbBranch::from_to(bcb, true_bcb, &self.basic_coverage_blocks),
+                false_branch: BcbBranch::from_to(bcb, false_bcb, &self.basic_coverage_blocks),
+                span,
+            });
+        }
+        branch_conditions
+    }
+
+    /// Returns the span of the condition tested by the `SwitchInt` terminating `bb`, or `None` if
+    /// it is not within the function body. This is the span of the statement in `bb` assigning
+    /// the `discr` operand, if it is within the span of the terminator; for example, the
+    /// `SwitchInt` on the left-hand operand of `&&` has the span of the whole `&&` expression.
+    /// With branch coverage, MIR building also branches on the right-hand operand of `&&` and
+    /// `||`, instead of copying it into the result, so that it gets a branch region too.
+    fn condition_span(&self, bb: BasicBlock, discr: &mir::Operand<'tcx>) -> Option<Span> {
+        let body_span = self.body_span;
+        let data = &self.mir_body[bb];
+        let terminator_span =
+            spans::function_source_span(data.terminator().source_info.span, body_span);
+        if terminator_span == body_span {
+            return None;
+        }
+        let assigned_local = discr.place().and_then(|place| place.as_local());
+        let assignment_span =
+            data.statements.iter().rev().find_map(|statement| match statement.kind {
+                StatementKind::Assign(box (place, _))
+                    if assigned_local.is_some() && place.as_local() == assigned_local =>
+                {
+                    Some(spans::function_source_span(statement.source_info.span, body_span))
+                }
+                _ => None,
+            });
+        match assignment_span {
+            Some(span) if terminator_span.contains(span) => Some(span),
+            _ => Some(terminator_span),
+        }
+    }
+
+    /// Makes a `CoverageKind::Branch` for each branch condition, from the counters that
+    /// `make_bcb_counters()` assigned to its true and false branches.
+    fn make_branch_regions(
+        &self,
+        branch_conditions: Vec<BranchCondition>,
+    ) -> Vec<(BasicCoverageBlock, Span, CoverageKind)> {
+        let counter_operand = |branch: BcbBranch| {
+            branch
+                .counter(&self.basic_coverage_blocks)
+                .expect("every branch from a branch condition should have a counter")
+                .as_operand_id()
+        };
+        branch_conditions
+            .into_iter()
+            .map(|BranchCondition { bcb, true_branch, false_branch, span }| {
+                let branch = CoverageKind::Branch {
+                    true_counter: counter_operand(true_branch),
+                    false_counter: counter_operand(false_branch),
+                };
+                debug!("{:?} gets a branch region: {:?}", bcb, branch);
+                (bcb, span, branch)
+            })
+            .collect()
+    }
+
+    /// Injects each `CoverageKind::Branch` into the `BasicBlock` ending in the `SwitchInt` of its
+    /// condition.
+    fn inject_branch_regions(
+        &mut self,
+        branch_regions: Vec<(BasicCoverageBlock, Span, CoverageKind)>,
+    ) {
+        let source_map = self.tcx.sess.source_map();
+        let body_span = self.body_span;
+        let file_name = Symbol::intern(&self.source_file.name.prefer_remapped().to_string_lossy());
+        for (bcb, span, branch) in branch_regions {
+            let code_region =
+                make_code_region(source_map, file_name, &self.source_file, span, body_span);
+            inject_statement(self.mir_body, branch, self.bcb_last_bb(bcb), Some(code_region));
+        }
+    }
+
     #[inline]
     fn bcb_leader_bb(&self, bcb: BasicCoverageBlock) -> BasicBlock {
         self.bcb_data(bcb).leader_bb()
@@ -103,6 +103,10 @@ fn visit_coverage(&mut self, coverage: &Coverage) {
                     self.update_from_expression_operand(u32::from(lhs));
                     self.update_from_expression_operand(u32::from(rhs));
                 }
+                CoverageKind::Branch { true_counter, false_counter } => {
+                    self.update_from_expression_operand(u32::from(true_counter));
+                    self.update_from_expression_operand(u32::from(false_counter));
+                }
                 _ => {}
             }
         } else {
@@ -143,7 +147,11 @@ fn covered_code_regions<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> Vec<&'tcx Cod
         .flat_map(|data| {
             data.statements.iter().filter_map(|statement| match statement.kind {
                 StatementKind::Coverage(box ref coverage) => {
-                    if is_inlined(body, statement) {
+                    // Branch regions are only meaningful with live counters, so an unused
+                    // function reports its conditions through the regions of its code instead.
+                    if is_inlined(body, statement)
+                        || matches!(coverage.kind, CoverageKind::Branch { .. })
+                    {
                         None
                     } else {
                         coverage.code_region.as_ref() // may be None
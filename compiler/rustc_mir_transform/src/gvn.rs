@@ -0,0 +1,478 @@
+//! Global value numbering.
+//!
+//! MIR building produces a lot of computations that are repeated verbatim: every `x[i]` reads
+//! the length of `x` again, every `match` reads the discriminant again and every use of `*r`
+//! loads through `r` again. This pass assigns a *value number* to each rvalue, so that two
+//! rvalues that provably compute the same value get the same number. When an rvalue computes a
+//! value that is already stored in a local, and that local is available at this point, the
+//! rvalue is replaced by a copy of that local:
+//!
+//! ```text
+//! _4 = Len((*_1));                    _4 = Len((*_1));
+//! _5 = Lt(_3, _4);                    _5 = Lt(_3, _4);
+//! ...                         =>      ...
+//! _8 = Len((*_1));                    _8 = _4;
+//! _9 = Lt(_7, _8);                    _9 = _5;
+//! ```
+//!
+//! Values are only tracked for SSA locals: locals that are assigned exactly once (or are
+//! arguments and never assigned), are never borrowed, and whose assignment dominates all their
+//! uses. The value of such a local is the same wherever it can be used. Everything else is
+//! considered opaque, except loads through a shared reference to a `Freeze` type, which cannot
+//! change while the reference is usable.
+//!
+//! Only pure rvalues get a value number. References, raw pointers, thread-local references and
+//! box allocations create a new pointer each time they are evaluated, so they are never
+//! deduplicated.
+//!
+//! Locals used as a replacement may now be used after they were moved from, or outside of their
+//! storage range, so their moves are turned into copies and their storage markers are removed.
+
+use crate::MirPass;
+use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
+use rustc_data_structures::graph::dominators::Dominators;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::interpret::Scalar;
+use rustc_middle::mir::tcx::PlaceTy;
+use rustc_middle::mir::visit::{
+    MutVisitor, MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor,
+};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, ScalarInt, Ty, TyCtxt};
+use rustc_span::DUMMY_SP;
+
+pub struct GVN;
+
+impl<'tcx> MirPass<'tcx> for GVN {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 3
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        debug!("Running GVN on `{:?}`", body.source);
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let ssa = SsaLocals::new(body);
+        let dominators = body.dominators();
+        let reverse_postorder: Vec<BasicBlock> =
+            traversal::reverse_postorder(body).map(|(bb, _)| bb).collect();
+        let args: Vec<Local> = body.args_iter().collect();
+
+        let (basic_blocks, local_decls) = body.basic_blocks_and_local_decls_mut();
+        let mut state = VnState::new(tcx, param_env, local_decls, &ssa);
+        for arg in args {
+            if ssa.is_ssa(arg) {
+                let value = state.new_opaque();
+                state.assign(arg, value);
+            }
+        }
+
+        let mut reused_locals = BitSet::new_empty(local_decls.len());
+        for bb in reverse_postorder {
+            let data = &mut basic_blocks[bb];
+            for (statement_index, statement) in data.statements.iter_mut().enumerate() {
+                let StatementKind::Assign(box (ref place, ref mut rvalue)) = statement.kind else {
+                    continue;
+                };
+                let location = Location { block: bb, statement_index };
+                let value = state.simplify_rvalue(rvalue);
+
+                if let Some(value) = value
+                    && !is_trivial(rvalue)
+                    && let Some(local) = state.try_as_local(
+                        value,
+                        rvalue.ty(state.local_decls, tcx),
+                        location,
+                        &dominators,
+                    )
+                {
+                    debug!("replacing {:?} at {:?} by a copy of {:?}", rvalue, location, local);
+                    *rvalue = Rvalue::Use(Operand::Copy(Place::from(local)));
+                    reused_locals.insert(local);
+                }
+
+                if let Some(local) = place.as_local() && ssa.is_ssa(local) {
+                    let value = value.unwrap_or_else(|| state.new_opaque());
+                    state.assign(local, value);
+                }
+            }
+        }
+
+        if !reused_locals.is_empty() {
+            StorageRemover { tcx, reused_locals }.visit_body(body);
+        }
+    }
+}
+
+/// Whether replacing this rvalue by a copy of a local would not make it any cheaper.
+fn is_trivial(rvalue: &Rvalue<'_>) -> bool {
+    match rvalue {
+        Rvalue::Use(Operand::Constant(_)) => true,
+        Rvalue::Use(Operand::Copy(place) | Operand::Move(place)) => place.as_local().is_some(),
+        _ => false,
+    }
+}
+
+/// How a local is assigned, as found by `SsaLocals::new`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Assignment {
+    /// The local is never assigned. This is fine for arguments, which are initialized on entry.
+    None,
+    /// The local is assigned exactly once, by this statement.
+    Once(Location),
+    /// The local is assigned several times, borrowed, or used where it may be uninitialized.
+    Many,
+}
+
+struct SsaLocals {
+    assignments: IndexVec<Local, Assignment>,
+}
+
+impl SsaLocals {
+    fn new(body: &Body<'_>) -> SsaLocals {
+        let assignments = IndexVec::from_elem(Assignment::None, &body.local_decls);
+        let mut visitor = SsaVisitor { assignments, uses: Vec::new() };
+        visitor.visit_body(body);
+
+        let SsaVisitor { mut assignments, uses } = visitor;
+        let dominators = body.dominators();
+        for (local, location) in uses {
+            if let Assignment::Once(assignment) = assignments[local] {
+                // The assignment must happen strictly before every use.
+                if assignment == location || !assignment.dominates(location, &dominators) {
+                    assignments[local] = Assignment::Many;
+                }
+            }
+        }
+
+        // An argument that is assigned in the body holds two different values.
+        for local in body.args_iter() {
+            if assignments[local] != Assignment::None {
+                assignments[local] = Assignment::Many;
+            }
+        }
+        for local in body.vars_and_temps_iter() {
+            if assignments[local] == Assignment::None {
+                assignments[local] = Assignment::Many;
+            }
+        }
+        // The return place is read by the `return` terminator without a visible use.
+        assignments[RETURN_PLACE] = Assignment::Many;
+
+        SsaLocals { assignments }
+    }
+
+    fn is_ssa(&self, local: Local) -> bool {
+        self.assignments[local] != Assignment::Many
+    }
+
+    /// Whether the value of `local` is available at `location`.
+    fn is_available_at(
+        &self,
+        local: Local,
+        location: Location,
+        dominators: &Dominators<BasicBlock>,
+    ) -> bool {
+        match self.assignments[local] {
+            Assignment::None => true,
+            Assignment::Once(assignment) => {
+                assignment != location && assignment.dominates(location, dominators)
+            }
+            Assignment::Many => false,
+        }
+    }
+}
+
+struct SsaVisitor {
+    assignments: IndexVec<Local, Assignment>,
+    /// Every read of a local, to check that the assignment dominates it.
+    uses: Vec<(Local, Location)>,
+}
+
+impl<'tcx> Visitor<'tcx> for SsaVisitor {
+    fn visit_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Some(local) = place.as_local() {
+            self.assignments[local] = match self.assignments[local] {
+                Assignment::None => Assignment::Once(location),
+                _ => Assignment::Many,
+            };
+            self.visit_rvalue(rvalue, location);
+        } else {
+            self.super_assign(place, rvalue, location);
+        }
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if !place.projection.is_empty() && context.is_use() {
+            // Going through a pointer leaves the pointer itself untouched, but writing to or
+            // borrowing a part of the local may change it.
+            if place.projection[0] != PlaceElem::Deref
+                && (context.is_mutating_use() || is_borrow_or_address_of(context))
+            {
+                self.assignments[place.local] = Assignment::Many;
+            }
+            self.uses.push((place.local, location));
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, location: Location) {
+        match context {
+            // Handled in `visit_place`.
+            PlaceContext::MutatingUse(MutatingUseContext::Projection)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Projection) => {}
+            PlaceContext::MutatingUse(_) => self.assignments[local] = Assignment::Many,
+            _ if is_borrow_or_address_of(context) => self.assignments[local] = Assignment::Many,
+            PlaceContext::NonMutatingUse(_) => self.uses.push((local, location)),
+            PlaceContext::NonUse(_) => {}
+        }
+    }
+}
+
+fn is_borrow_or_address_of(context: PlaceContext) -> bool {
+    context.is_borrow()
+        || matches!(
+            context,
+            PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf)
+                | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+        )
+}
+
+rustc_index::newtype_index! {
+    struct VnIndex {
+        DEBUG_FORMAT = "_v{}"
+    }
+}
+
+/// A value computed by an rvalue, in terms of the values of its operands.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Value<'tcx> {
+    /// A value we know nothing about, like the result of a call or the contents of a local that
+    /// is assigned several times. Each opaque value is different from every other value.
+    Opaque(usize),
+    Constant(ScalarInt, Ty<'tcx>),
+    Aggregate(AggregateKind<'tcx>, Vec<VnIndex>),
+    Repeat(VnIndex, ty::Const<'tcx>),
+    Len(VnIndex),
+    Cast(CastKind, VnIndex, Ty<'tcx>),
+    BinaryOp(BinOp, VnIndex, VnIndex),
+    CheckedBinaryOp(BinOp, VnIndex, VnIndex),
+    NullaryOp(NullOp, Ty<'tcx>),
+    UnaryOp(UnOp, VnIndex),
+    Discriminant(VnIndex),
+    /// The contents of a place, seen as a projection from the value of its base.
+    Projection(VnIndex, ProjectionElem<VnIndex, Ty<'tcx>>),
+}
+
+struct VnState<'body, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    local_decls: &'body LocalDecls<'tcx>,
+    ssa: &'body SsaLocals,
+    values: FxIndexSet<Value<'tcx>>,
+    /// The value of each SSA local, once its assignment has been visited.
+    locals: IndexVec<Local, Option<VnIndex>>,
+    /// The SSA locals holding each value, in the order of their assignments.
+    rev_locals: FxHashMap<VnIndex, Vec<Local>>,
+    next_opaque: usize,
+}
+
+impl<'body, 'tcx> VnState<'body, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+        local_decls: &'body LocalDecls<'tcx>,
+        ssa: &'body SsaLocals,
+    ) -> VnState<'body, 'tcx> {
+        VnState {
+            tcx,
+            param_env,
+            local_decls,
+            ssa,
+            values: FxIndexSet::default(),
+            locals: IndexVec::from_elem(None, local_decls),
+            rev_locals: FxHashMap::default(),
+            next_opaque: 0,
+        }
+    }
+
+    fn insert(&mut self, value: Value<'tcx>) -> VnIndex {
+        let (index, _) = self.values.insert_full(value);
+        VnIndex::from_usize(index)
+    }
+
+    fn new_opaque(&mut self) -> VnIndex {
+        let value = Value::Opaque(self.next_opaque);
+        self.next_opaque += 1;
+        self.insert(value)
+    }
+
+    fn get(&self, index: VnIndex) -> &Value<'tcx> {
+        &self.values[index.index()]
+    }
+
+    fn assign(&mut self, local: Local, value: VnIndex) {
+        self.locals[local] = Some(value);
+        self.rev_locals.entry(value).or_default().push(local);
+    }
+
+    /// Returns an SSA local of type `ty` that holds `value` at `location` and can be copied.
+    fn try_as_local(
+        &self,
+        value: VnIndex,
+        ty: Ty<'tcx>,
+        location: Location,
+        dominators: &Dominators<BasicBlock>,
+    ) -> Option<Local> {
+        let candidates = self.rev_locals.get(&value)?;
+        candidates.iter().copied().find(|&local| {
+            self.ssa.is_available_at(local, location, dominators)
+                && self.local_decls[local].ty == ty
+                && ty.is_copy_modulo_regions(self.tcx.at(DUMMY_SP), self.param_env)
+        })
+    }
+
+    fn simplify_rvalue(&mut self, rvalue: &Rvalue<'tcx>) -> Option<VnIndex> {
+        let value = match *rvalue {
+            Rvalue::Use(ref operand) => return self.simplify_operand(operand),
+            Rvalue::Repeat(ref operand, amount) => {
+                Value::Repeat(self.simplify_operand(operand)?, amount)
+            }
+            Rvalue::Len(place) => Value::Len(self.simplify_place(place)?),
+            Rvalue::Cast(kind, ref operand, ty) => {
+                Value::Cast(kind, self.simplify_operand(operand)?, ty)
+            }
+            Rvalue::BinaryOp(op, box (ref lhs, ref rhs)) => {
+                Value::BinaryOp(op, self.simplify_operand(lhs)?, self.simplify_operand(rhs)?)
+            }
+            Rvalue::CheckedBinaryOp(op, box (ref lhs, ref rhs)) => {
+                Value::CheckedBinaryOp(op, self.simplify_operand(lhs)?, self.simplify_operand(rhs)?)
+            }
+            Rvalue::NullaryOp(op, ty) => Value::NullaryOp(op, ty),
+            Rvalue::UnaryOp(op, ref operand) => Value::UnaryOp(op, self.simplify_operand(operand)?),
+            Rvalue::Discriminant(place) => Value::Discriminant(self.simplify_place(place)?),
+            Rvalue::Aggregate(box ref kind, ref fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.simplify_operand(field))
+                    .collect::<Option<Vec<_>>>()?;
+                Value::Aggregate(kind.clone(), fields)
+            }
+            // Each evaluation of these creates a new pointer or a new allocation.
+            Rvalue::Ref(..)
+            | Rvalue::AddressOf(..)
+            | Rvalue::ThreadLocalRef(..)
+            | Rvalue::ShallowInitBox(..) => return None,
+        };
+        Some(self.insert(value))
+    }
+
+    fn simplify_operand(&mut self, operand: &Operand<'tcx>) -> Option<VnIndex> {
+        match *operand {
+            Operand::Copy(place) | Operand::Move(place) => self.simplify_place(place),
+            Operand::Constant(ref constant) => {
+                // Constants containing pointers may point to different allocations, even if
+                // they are equal.
+                match constant.literal.try_to_scalar()? {
+                    Scalar::Int(int) => Some(self.insert(Value::Constant(int, constant.ty()))),
+                    Scalar::Ptr(..) => None,
+                }
+            }
+        }
+    }
+
+    fn simplify_place(&mut self, place: Place<'tcx>) -> Option<VnIndex> {
+        let mut value = self.locals[place.local]?;
+        let mut place_ty = PlaceTy::from_ty(self.local_decls[place.local].ty);
+        for elem in place.projection.iter() {
+            value = self.project(place_ty, value, elem)?;
+            place_ty = place_ty.projection_ty(self.tcx, elem);
+        }
+        Some(value)
+    }
+
+    fn project(
+        &mut self,
+        place_ty: PlaceTy<'tcx>,
+        value: VnIndex,
+        elem: PlaceElem<'tcx>,
+    ) -> Option<VnIndex> {
+        let elem = match elem {
+            ProjectionElem::Deref => match *place_ty.ty.kind() {
+                // The pointee of a shared reference cannot change while the reference is live,
+                // unless it has interior mutability.
+                ty::Ref(_, pointee, Mutability::Not)
+                    if pointee.is_freeze(self.tcx.at(DUMMY_SP), self.param_env) =>
+                {
+                    ProjectionElem::Deref
+                }
+                _ => return None,
+            },
+            ProjectionElem::Field(field, ty) => {
+                if let Value::Aggregate(ref kind, ref fields) = *self.get(value) {
+                    match *kind {
+                        // Reading another field than the one that was written reinterprets the
+                        // bytes of the union.
+                        AggregateKind::Adt(.., Some(active_field)) => {
+                            if active_field == field.index() {
+                                return Some(fields[0]);
+                            }
+                        }
+                        // The fields of a generator are not its upvars.
+                        AggregateKind::Generator(..) => {}
+                        _ => return Some(fields[field.index()]),
+                    }
+                }
+                ProjectionElem::Field(field, ty)
+            }
+            ProjectionElem::Downcast(name, variant) => match *self.get(value) {
+                // Looking at the variant that was written gives back the aggregate itself.
+                Value::Aggregate(AggregateKind::Adt(_, written_variant, ..), _)
+                    if written_variant == variant =>
+                {
+                    return Some(value);
+                }
+                _ => ProjectionElem::Downcast(name, variant),
+            },
+            ProjectionElem::Index(index) => ProjectionElem::Index(self.locals[index]?),
+            ProjectionElem::ConstantIndex { offset, min_length, from_end } => {
+                ProjectionElem::ConstantIndex { offset, min_length, from_end }
+            }
+            ProjectionElem::Subslice { from, to, from_end } => {
+                ProjectionElem::Subslice { from, to, from_end }
+            }
+        };
+        Some(self.insert(Value::Projection(value, elem)))
+    }
+}
+
+/// Makes the locals that were used as replacements valid wherever their value is available.
+struct StorageRemover<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    reused_locals: BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for StorageRemover<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, _: Location) {
+        if let Operand::Move(place) = *operand
+            && self.reused_locals.contains(place.local)
+        {
+            *operand = Operand::Copy(place);
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        match statement.kind {
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local)
+                if self.reused_locals.contains(local) =>
+            {
+                statement.make_nop()
+            }
+            _ => self.super_statement(statement, location),
+        }
+    }
+}
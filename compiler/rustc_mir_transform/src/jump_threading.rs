@@ -0,0 +1,344 @@
+//! Jump threading.
+//!
+//! After inlining, state machines often assign a constant to a local, or set the discriminant of
+//! an enum, and then jump to a block that switches on that value. When the value a `SwitchInt`
+//! branches on is known along one of the edges into its block, this pass redirects that edge to
+//! the target for that value, so the switch is skipped:
+//!
+//! ```text
+//! bb0: {                                          bb0: {
+//!     discriminant(_1) = 1;                           discriminant(_1) = 1;
+//!     goto -> bb2;                                    goto -> bb5;
+//! }                                               }
+//! bb1: {                                          bb1: {
+//!     _1 = move _3;                                   _1 = move _3;
+//!     goto -> bb2;                                    goto -> bb2;
+//! }                                       =>      }
+//! bb2: {                                          bb2: {
+//!     _2 = discriminant(_1);                          _2 = discriminant(_1);
+//!     switchInt(move _2) -> [0: bb3, 1: bb4];         switchInt(move _2) -> [0: bb3, 1: bb4];
+//! }                                               }
+//!                                                 bb5: {
+//!                                                     _2 = discriminant(_1);
+//!                                                     goto -> bb4;
+//!                                                 }
+//! ```
+//!
+//! The value is found by walking backwards from the switch, first through the switch block, then
+//! through each of its predecessors and, as long as the block being walked has a single
+//! predecessor, through the blocks before it, up to `MAX_BACKTRACK` blocks per edge. The walk
+//! follows copies between locals and reads of a discriminant, and gives up at any other write to
+//! the value it follows. Locals that are borrowed anywhere in the body are never followed, since
+//! they may be written through a pointer. A value that is already known within the switch block
+//! is left to `ConstProp`.
+//!
+//! The statements of the switch block still have to run on the threaded edge, so they are copied
+//! into a new block ending in a `goto`. `MAX_COST` bounds the number of statements copied for each
+//! edge, which bounds the growth of the body to that many statements per edge.
+
+use crate::MirPass;
+use rustc_index::bit_set::BitSet;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{ParamEnv, TyCtxt};
+use rustc_target::abi::VariantIdx;
+use smallvec::SmallVec;
+
+use super::simplify::simplify_cfg;
+
+pub struct JumpThreading;
+
+/// The maximum number of blocks walked back from a switch, along each edge into it, looking for
+/// the value it branches on.
+const MAX_BACKTRACK: usize = 5;
+
+/// The maximum number of statements copied to thread a single edge.
+const MAX_COST: usize = 8;
+
+impl<'tcx> MirPass<'tcx> for JumpThreading {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 3
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        debug!("Running JumpThreading on `{:?}`", body.source);
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+
+        let mut borrowed_locals = BorrowedLocals(BitSet::new_empty(body.local_decls.len()));
+        borrowed_locals.visit_body(body);
+        let finder = OpportunityFinder { tcx, param_env, body, borrowed: borrowed_locals.0 };
+        let opportunities = finder.find_opportunities();
+        if opportunities.is_empty() {
+            return;
+        }
+
+        // Redirecting an edge gives its new target another predecessor. An edge that was threaded
+        // by walking through a block with a single predecessor is only valid while that block
+        // keeps its single predecessor, so such edges are not threaded if any of the blocks they
+        // walked through is the new target of another edge.
+        let mut new_targets = BitSet::new_empty(body.basic_blocks().len());
+        for opportunity in &opportunities {
+            new_targets.insert(opportunity.target);
+        }
+
+        let mut threaded = false;
+        for opportunity in opportunities {
+            let ThreadingOpportunity { pred, switch_bb, target, walked } = opportunity;
+            if walked.iter().any(|&bb| new_targets.contains(bb)) {
+                continue;
+            }
+
+            let switch_data = &body[switch_bb];
+            let new_target = if switch_data.statements.is_empty() {
+                target
+            } else {
+                let copy = BasicBlockData {
+                    statements: switch_data.statements.clone(),
+                    terminator: Some(Terminator {
+                        source_info: switch_data.terminator().source_info,
+                        kind: TerminatorKind::Goto { target },
+                    }),
+                    is_cleanup: switch_data.is_cleanup,
+                };
+                body.basic_blocks_mut().push(copy)
+            };
+            debug!("threading {:?} -> {:?} to {:?}, via {:?}", pred, switch_bb, target, new_target);
+            for successor in body[pred].terminator_mut().successors_mut() {
+                if *successor == switch_bb {
+                    *successor = new_target;
+                }
+            }
+            threaded = true;
+        }
+
+        if threaded {
+            simplify_cfg(tcx, body);
+        }
+    }
+}
+
+/// An edge into a switch block, along which the value of the switch is known.
+struct ThreadingOpportunity {
+    /// The block the edge comes from.
+    pred: BasicBlock,
+    switch_bb: BasicBlock,
+    /// The target of the switch for the known value.
+    target: BasicBlock,
+    /// The blocks walked through to their single predecessor to find the value.
+    walked: SmallVec<[BasicBlock; MAX_BACKTRACK]>,
+}
+
+/// The value a switch branches on, as followed backwards from the switch.
+#[derive(Copy, Clone, Debug)]
+enum Tracked {
+    /// The value of a local.
+    Value(Local),
+    /// The discriminant of the enum in a local.
+    Discriminant(Local),
+}
+
+impl Tracked {
+    fn local(self) -> Local {
+        match self {
+            Tracked::Value(local) | Tracked::Discriminant(local) => local,
+        }
+    }
+}
+
+/// The effect of a statement on the tracked value, when walking backwards.
+enum Step {
+    /// The statement assigns a known value.
+    Known(u128),
+    /// The value is the one of `Tracked` before the statement.
+    Continue(Tracked),
+    /// The statement assigns a value that is not known.
+    Unknown,
+}
+
+struct OpportunityFinder<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    body: &'a Body<'tcx>,
+    borrowed: BitSet<Local>,
+}
+
+impl<'tcx> OpportunityFinder<'_, 'tcx> {
+    fn find_opportunities(&self) -> Vec<ThreadingOpportunity> {
+        let predecessors = self.body.predecessors();
+        let mut opportunities = Vec::new();
+        for (switch_bb, switch_data) in self.body.basic_blocks().iter_enumerated() {
+            // Unwind paths rely on each block having a unique parent, so leave them alone.
+            if switch_data.is_cleanup {
+                continue;
+            }
+            let TerminatorKind::SwitchInt { discr, targets, .. } = &switch_data.terminator().kind
+            else {
+                continue;
+            };
+            let Some(local) = discr.place().and_then(|place| place.as_local()) else {
+                continue;
+            };
+            if self.borrowed.contains(local) {
+                continue;
+            }
+
+            // Walk back to the start of the switch block, which gives the value to look for in
+            // its predecessors. A value that is known within the switch block itself is left to
+            // const propagation.
+            let Step::Continue(tracked) = self.walk_statements(Tracked::Value(local), switch_data)
+            else {
+                continue;
+            };
+
+            let cost = switch_data
+                .statements
+                .iter()
+                .filter(|statement| !matches!(statement.kind, StatementKind::Nop))
+                .count();
+            if cost > MAX_COST {
+                continue;
+            }
+
+            let mut preds = predecessors[switch_bb].clone();
+            preds.sort_unstable();
+            preds.dedup();
+            for pred in preds {
+                let mut walked = SmallVec::new();
+                let Some(value) = self.find_value(pred, tracked, &mut walked) else {
+                    continue;
+                };
+                let target = targets.target_for_value(value);
+                if target != switch_bb {
+                    opportunities.push(ThreadingOpportunity { pred, switch_bb, target, walked });
+                }
+            }
+        }
+        opportunities
+    }
+
+    /// Finds the value of `tracked` at the end of `bb`, walking backwards through `bb` and, while
+    /// the block being walked has a single predecessor, the blocks before it. The blocks left to
+    /// their single predecessor are pushed to `walked`.
+    fn find_value(
+        &self,
+        mut bb: BasicBlock,
+        mut tracked: Tracked,
+        walked: &mut SmallVec<[BasicBlock; MAX_BACKTRACK]>,
+    ) -> Option<u128> {
+        let predecessors = self.body.predecessors();
+        for _ in 0..MAX_BACKTRACK {
+            let data = &self.body[bb];
+            if data.is_cleanup {
+                return None;
+            }
+            let mut writes = LocalWrites { local: tracked.local(), found: false };
+            writes.visit_terminator(data.terminator(), self.body.terminator_loc(bb));
+            if writes.found {
+                return None;
+            }
+            match self.walk_statements(tracked, data) {
+                Step::Known(value) => return Some(value),
+                Step::Continue(before) => tracked = before,
+                Step::Unknown => return None,
+            }
+            let &[pred] = &predecessors[bb][..] else {
+                return None;
+            };
+            walked.push(bb);
+            bb = pred;
+        }
+        None
+    }
+
+    /// Walks backwards through the statements of `data`, from the value of `tracked` at the end of
+    /// the block.
+    fn walk_statements(&self, mut tracked: Tracked, data: &BasicBlockData<'tcx>) -> Step {
+        for statement in data.statements.iter().rev() {
+            match self.step(tracked, statement) {
+                Step::Continue(before) => tracked = before,
+                step => return step,
+            }
+        }
+        Step::Continue(tracked)
+    }
+
+    /// Returns the effect of `statement` on the tracked value, when walking backwards.
+    fn step(&self, tracked: Tracked, statement: &Statement<'tcx>) -> Step {
+        let untracked =
+            |place: &Place<'_>| place.as_local().filter(|&local| !self.borrowed.contains(local));
+        match (tracked, &statement.kind) {
+            (Tracked::Value(local), StatementKind::Assign(box (place, rvalue)))
+                if place.as_local() == Some(local) =>
+            {
+                match rvalue {
+                    Rvalue::Use(Operand::Constant(constant)) => constant
+                        .literal
+                        .try_eval_bits(self.tcx, self.param_env, constant.ty())
+                        .map_or(Step::Unknown, Step::Known),
+                    Rvalue::Use(Operand::Copy(place) | Operand::Move(place)) => untracked(place)
+                        .map_or(Step::Unknown, |source| Step::Continue(Tracked::Value(source))),
+                    Rvalue::Discriminant(place) => untracked(place)
+                        .map_or(Step::Unknown, |source| {
+                            Step::Continue(Tracked::Discriminant(source))
+                        }),
+                    _ => Step::Unknown,
+                }
+            }
+            (
+                Tracked::Discriminant(local),
+                StatementKind::SetDiscriminant { place, variant_index },
+            ) if place.as_local() == Some(local) => self.discriminant(local, *variant_index),
+            (
+                Tracked::Discriminant(local),
+                StatementKind::Assign(box (
+                    place,
+                    Rvalue::Aggregate(box AggregateKind::Adt(_, variant_index, ..), _),
+                )),
+            ) if place.as_local() == Some(local) => self.discriminant(local, *variant_index),
+            _ => {
+                let mut writes = LocalWrites { local: tracked.local(), found: false };
+                writes.visit_statement(statement, Location::START);
+                if writes.found {
+                    Step::Unknown
+                } else {
+                    Step::Continue(tracked)
+                }
+            }
+        }
+    }
+
+    fn discriminant(&self, local: Local, variant_index: VariantIdx) -> Step {
+        let ty = self.body.local_decls[local].ty;
+        if !ty.is_enum() {
+            return Step::Unknown;
+        }
+        ty.discriminant_for_variant(self.tcx, variant_index)
+            .map_or(Step::Unknown, |discr| Step::Known(discr.val))
+    }
+}
+
+/// Collects the locals that are borrowed or have their address taken.
+struct BorrowedLocals(BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for BorrowedLocals {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Ref(_, _, place) | Rvalue::AddressOf(_, place) = rvalue {
+            self.0.insert(place.local);
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
+
+/// Finds whether a statement or terminator may change the value of `local`.
+struct LocalWrites {
+    local: Local,
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalWrites {
+    fn visit_local(&mut self, local: &Local, context: PlaceContext, _: Location) {
+        if *local == self.local && (context.is_mutating_use() || context.is_storage_marker()) {
+            self.found = true;
+        }
+    }
+}
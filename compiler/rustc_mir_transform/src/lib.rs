@@ -59,8 +59,10 @@
 mod elaborate_drops;
 mod function_item_references;
 mod generator;
+mod gvn;
 mod inline;
 mod instcombine;
+mod jump_threading;
 mod lower_intrinsics;
 mod lower_slice_len;
 mod marker;
@@ -476,6 +478,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &multiple_return_terminators::MultipleReturnTerminators,
             &instcombine::InstCombine,
             &separate_const_switch::SeparateConstSwitch,
+            &gvn::GVN,
+            &jump_threading::JumpThreading,
             //
             // FIXME(#70073): This pass is responsible for both optimization as well as some lints.
             &const_prop::ConstProp,
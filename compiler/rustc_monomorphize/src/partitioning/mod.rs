@@ -97,13 +97,17 @@
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync;
-use rustc_hir::def_id::DefIdSet;
+use rustc_hir::def_id::{DefId, DefIdSet, LOCAL_CRATE};
 use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::mir::mono::{CodegenUnit, Linkage};
 use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::TyCtxt;
+use rustc_serialize::json::{Json, Object};
+use rustc_session::config::DumpMonoStatsFormat;
 use rustc_span::symbol::Symbol;
+use std::fmt::Write as _;
+use std::fs;
 
 use crate::collector::InliningMap;
 use crate::collector::{self, MonoItemCollectionMode};
@@ -404,6 +408,10 @@ fn collect_and_partition_mono_items<'tcx>(
         }
     }
 
+    if let Some(format) = tcx.sess.opts.debugging_opts.dump_mono_stats {
+        dump_mono_items_stats(tcx, format, codegen_units, &inlining_map);
+    }
+
     let mono_items: DefIdSet = items
         .iter()
         .filter_map(|mono_item| match *mono_item {
@@ -467,6 +475,170 @@ fn collect_and_partition_mono_items<'tcx>(
     (tcx.arena.alloc(mono_items), codegen_units)
 }
 
+/// A definition in the `-Z dump-mono-stats` report, with its instances.
+struct DefinitionStats {
+    name: String,
+    krate: String,
+    instances: Vec<InstanceStats>,
+}
+
+struct InstanceStats {
+    name: String,
+    size_estimate: usize,
+    /// The crates of the items that use this instance.
+    used_by: Vec<String>,
+    codegen_units: Vec<String>,
+}
+
+impl DefinitionStats {
+    fn copies(&self) -> usize {
+        self.instances.iter().map(|instance| instance.codegen_units.len()).sum()
+    }
+
+    fn total_size_estimate(&self) -> usize {
+        self.instances.iter().map(InstanceStats::total_size_estimate).sum()
+    }
+}
+
+impl InstanceStats {
+    /// The estimated size of every copy of the instance, one per codegen unit
+    /// that it is placed in.
+    fn total_size_estimate(&self) -> usize {
+        self.size_estimate * self.codegen_units.len()
+    }
+}
+
+/// Writes out how often every function was instantiated, how large the
+/// instances are estimated to be from their MIR, which crates the code that
+/// uses them is from and which codegen units they were placed in.
+fn dump_mono_items_stats<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    format: DumpMonoStatsFormat,
+    codegen_units: &[CodegenUnit<'tcx>],
+    inlining_map: &InliningMap<'tcx>,
+) {
+    let mut users: FxHashMap<MonoItem<'tcx>, FxHashSet<_>> = Default::default();
+    inlining_map.iter_accesses(|accessor, accessees| {
+        for &accessee in accessees {
+            users.entry(accessee).or_default().insert(accessor.krate());
+        }
+    });
+
+    let mut placements: FxHashMap<MonoItem<'tcx>, Vec<String>> = Default::default();
+    for cgu in codegen_units {
+        for &mono_item in cgu.items().keys() {
+            placements.entry(mono_item).or_default().push(cgu.name().to_string());
+        }
+    }
+
+    let mut instances_by_def_id: FxHashMap<DefId, Vec<InstanceStats>> = Default::default();
+    for (mono_item, mut codegen_units) in placements {
+        let MonoItem::Fn(instance) = mono_item else { continue };
+        // Nothing uses the roots of the collection, which are there because of
+        // the local crate.
+        let mut used_by: Vec<_> = match users.get(&mono_item) {
+            Some(crates) => crates.iter().map(|&cnum| tcx.crate_name(cnum).to_string()).collect(),
+            None => vec![tcx.crate_name(LOCAL_CRATE).to_string()],
+        };
+        used_by.sort();
+        codegen_units.sort();
+        instances_by_def_id.entry(instance.def_id()).or_default().push(InstanceStats {
+            name: with_no_trimmed_paths!(instance.to_string()),
+            size_estimate: mono_item.size_estimate(tcx),
+            used_by,
+            codegen_units,
+        });
+    }
+
+    let mut definitions: Vec<_> = instances_by_def_id
+        .into_iter()
+        .map(|(def_id, mut instances)| {
+            instances.sort_by(|a, b| {
+                b.total_size_estimate()
+                    .cmp(&a.total_size_estimate())
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            DefinitionStats {
+                name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+                krate: tcx.crate_name(def_id.krate).to_string(),
+                instances,
+            }
+        })
+        .collect();
+    // The definitions with the most code first.
+    definitions.sort_by(|a, b| {
+        b.total_size_estimate().cmp(&a.total_size_estimate()).then_with(|| a.name.cmp(&b.name))
+    });
+
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let contents = match format {
+        DumpMonoStatsFormat::Markdown => mono_items_stats_markdown(&definitions),
+        DumpMonoStatsFormat::Json => mono_items_stats_json(crate_name, &definitions),
+    };
+    let file_name = format!("{}.mono_items.{}", crate_name, format.extension());
+    let path = tcx.output_filenames(()).out_directory.join(file_name);
+    if let Err(e) = fs::write(&path, contents) {
+        tcx.sess.err(&format!("failed to write mono item stats to `{}`: {}", path.display(), e));
+    }
+}
+
+fn mono_items_stats_markdown(definitions: &[DefinitionStats]) -> String {
+    let mut output = String::from(
+        "| Definition | Crate | Instances | Copies | Total MIR size estimate |\n\
+         | --- | --- | ---: | ---: | ---: |\n",
+    );
+    for definition in definitions {
+        let _ = writeln!(
+            output,
+            "| `{}` | {} | {} | {} | {} |",
+            definition.name,
+            definition.krate,
+            definition.instances.len(),
+            definition.copies(),
+            definition.total_size_estimate()
+        );
+    }
+    output
+}
+
+fn mono_items_stats_json(crate_name: Symbol, definitions: &[DefinitionStats]) -> String {
+    let strings =
+        |strings: &[String]| Json::Array(strings.iter().cloned().map(Json::String).collect());
+    let definitions = definitions
+        .iter()
+        .map(|definition| {
+            let instances = definition
+                .instances
+                .iter()
+                .map(|instance| {
+                    let mut json = Object::new();
+                    json.insert("name".to_owned(), Json::String(instance.name.clone()));
+                    let size_estimate = Json::U64(instance.size_estimate as u64);
+                    json.insert("size_estimate".to_owned(), size_estimate);
+                    json.insert("used_by".to_owned(), strings(&instance.used_by));
+                    json.insert("codegen_units".to_owned(), strings(&instance.codegen_units));
+                    Json::Object(json)
+                })
+                .collect();
+
+            let mut json = Object::new();
+            json.insert("name".to_owned(), Json::String(definition.name.clone()));
+            json.insert("crate".to_owned(), Json::String(definition.krate.clone()));
+            json.insert("instance_count".to_owned(), Json::U64(definition.instances.len() as u64));
+            json.insert("copies".to_owned(), Json::U64(definition.copies() as u64));
+            let total_size_estimate = Json::U64(definition.total_size_estimate() as u64);
+            json.insert("total_size_estimate".to_owned(), total_size_estimate);
+            json.insert("instances".to_owned(), Json::Array(instances));
+            Json::Object(json)
+        })
+        .collect();
+
+    let mut report = Object::new();
+    report.insert("crate".to_owned(), Json::String(crate_name.to_string()));
+    report.insert("definitions".to_owned(), Json::Array(definitions));
+    format!("{}\n", Json::Object(report).pretty())
+}
+
 fn codegened_and_inlined_items<'tcx>(tcx: TyCtxt<'tcx>, (): ()) -> &'tcx DefIdSet {
     let (items, cgus) = tcx.collect_and_partition_mono_items(());
     let mut visited = DefIdSet::default();
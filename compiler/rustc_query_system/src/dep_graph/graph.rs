@@ -802,6 +802,47 @@ pub fn is_green(&self, dep_node: &DepNode<K>) -> bool {
         self.node_color(dep_node).map_or(false, |c| c.is_green())
     }
 
+    /// Returns the chain of dependencies that kept `dep_node` from being marked green:
+    /// the first dependency of `dep_node` that is not green, then the first dependency
+    /// of that node that is not green, and so on. Marking a node green stops at its first
+    /// dependency that is not green, so this is the chain that `try_mark_green` followed.
+    ///
+    /// Every node of the chain is red, except maybe the last one, which is not colored
+    /// if it could not be recomputed, e.g. because its key does not exist anymore.
+    pub fn red_dependency_chain(&self, dep_node: &DepNode<K>) -> Vec<DepNode<K>> {
+        let mut chain = vec![];
+        let Some(data) = &self.data else {
+            return chain;
+        };
+        let Some(mut prev_index) = data.previous.node_to_index_opt(dep_node) else {
+            return chain;
+        };
+
+        loop {
+            let not_green = data.previous.edge_targets_from(prev_index).iter().find_map(|&index| {
+                match data.colors.get(index) {
+                    Some(DepNodeColor::Green(_)) => None,
+                    color => Some((index, color)),
+                }
+            });
+
+            match not_green {
+                Some((index, Some(DepNodeColor::Red))) => {
+                    chain.push(data.previous.index_to_node(index));
+                    prev_index = index;
+                }
+                Some((index, _)) => {
+                    chain.push(data.previous.index_to_node(index));
+                    break;
+                }
+                // All the dependencies are green, so the node changed by itself.
+                None => break,
+            }
+        }
+
+        chain
+    }
+
     // This method loads all on-disk cacheable query results into memory, so
     // they can be written out to the new cache file again. Most query results
     // will already be in memory but in the case where we marked something as
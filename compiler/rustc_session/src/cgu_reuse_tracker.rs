@@ -2,8 +2,11 @@
 //! compilation. This is used for incremental compilation tests and debug
 //! output.
 
+use crate::config::IncrementalExplainFormat;
 use rustc_data_structures::fx::FxHashMap;
+use rustc_serialize::json::{Json, Object};
 use rustc_span::{Span, Symbol};
+use std::fmt::Write;
 use std::sync::{Arc, Mutex};
 use tracing::debug;
 
@@ -20,9 +23,23 @@ pub enum ComparisonKind {
     AtLeast,
 }
 
+/// Why a codegen unit could not be reused, for `-Z incremental-explain`.
+#[derive(Clone, Debug)]
+pub enum CguRecompileReason {
+    /// The codegen unit did not exist in the previous compilation session.
+    NewCgu,
+    /// The object file of the codegen unit was not kept in the incremental cache.
+    MissingWorkProduct,
+    /// The first chain of dep-nodes that could not be marked green, starting with
+    /// a dependency of the codegen unit. Every node is a dependency of the previous
+    /// one, and the last one changed by itself, or could not be recomputed.
+    Changed(Vec<String>),
+}
+
 struct TrackerData {
     actual_reuse: FxHashMap<String, CguReuse>,
     expected_reuse: FxHashMap<String, (String, SendSpan, CguReuse, ComparisonKind)>,
+    recompile_reasons: FxHashMap<String, CguRecompileReason>,
 }
 
 // Span does not implement `Send`, so we can't just store it in the shared
@@ -40,8 +57,11 @@ pub struct CguReuseTracker {
 
 impl CguReuseTracker {
     pub fn new() -> CguReuseTracker {
-        let data =
-            TrackerData { actual_reuse: Default::default(), expected_reuse: Default::default() };
+        let data = TrackerData {
+            actual_reuse: Default::default(),
+            expected_reuse: Default::default(),
+            recompile_reasons: Default::default(),
+        };
 
         CguReuseTracker { data: Some(Arc::new(Mutex::new(data))) }
     }
@@ -65,6 +85,14 @@ pub fn set_actual_reuse(&self, cgu_name: &str, kind: CguReuse) {
         }
     }
 
+    pub fn set_recompile_reason(&self, cgu_name: &str, reason: CguRecompileReason) {
+        if let Some(ref data) = self.data {
+            debug!("set_recompile_reason({cgu_name:?}, {reason:?})");
+
+            data.lock().unwrap().recompile_reasons.insert(cgu_name.to_string(), reason);
+        }
+    }
+
     pub fn set_expectation(
         &self,
         cgu_name: Symbol,
@@ -115,4 +143,101 @@ pub fn check_expected_reuse(&self, diag: &rustc_errors::Handler) {
             }
         }
     }
+
+    /// Returns the `-Z incremental-explain` report of the reuse of every codegen unit
+    /// and of why the ones that could not be reused were recompiled.
+    pub fn explain(&self, format: IncrementalExplainFormat) -> String {
+        let Some(ref data) = self.data else {
+            return String::new();
+        };
+        let data = data.lock().unwrap();
+
+        let mut cgus: Vec<_> = data
+            .actual_reuse
+            .iter()
+            .map(|(cgu_name, &reuse)| {
+                let reason = match reuse {
+                    CguReuse::No => data.recompile_reasons.get(cgu_name),
+                    CguReuse::PreLto | CguReuse::PostLto => None,
+                };
+                (cgu_name, reuse, reason)
+            })
+            .collect();
+        cgus.sort_by(|a, b| a.0.cmp(b.0));
+
+        match format {
+            IncrementalExplainFormat::Tree => explain_tree(&cgus),
+            IncrementalExplainFormat::Json => explain_json(&cgus),
+        }
+    }
+}
+
+fn explain_tree(cgus: &[(&String, CguReuse, Option<&CguRecompileReason>)]) -> String {
+    let recompiled = cgus.iter().filter(|&&(_, reuse, _)| reuse == CguReuse::No).count();
+    let mut out = String::new();
+    writeln!(out, "incremental-explain: {} of {} codegen units recompiled", recompiled, cgus.len())
+        .unwrap();
+
+    for (i, &(cgu_name, reuse, reason)) in cgus.iter().enumerate() {
+        let (branch, indent) =
+            if i + 1 == cgus.len() { ("└── ", "    ") } else { ("├── ", "│   ") };
+        let status = match (reuse, reason) {
+            (CguReuse::PreLto, _) => "reused (pre-LTO)",
+            (CguReuse::PostLto, _) => "reused",
+            (CguReuse::No, Some(CguRecompileReason::NewCgu)) => {
+                "recompiled, not in the previous session"
+            }
+            (CguReuse::No, Some(CguRecompileReason::MissingWorkProduct)) => {
+                "recompiled, object file missing from the incremental cache"
+            }
+            (CguReuse::No, _) => "recompiled",
+        };
+        writeln!(out, "{}`{}`: {}", branch, cgu_name, status).unwrap();
+
+        // Every node changed because the node below it did.
+        if let Some(CguRecompileReason::Changed(chain)) = reason {
+            for (depth, node) in chain.iter().enumerate() {
+                writeln!(out, "{}{}└── `{}` changed", indent, "    ".repeat(depth), node).unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+fn explain_json(cgus: &[(&String, CguReuse, Option<&CguRecompileReason>)]) -> String {
+    let cgus = cgus
+        .iter()
+        .map(|&(cgu_name, reuse, reason)| {
+            let reuse = match reuse {
+                CguReuse::No => "no",
+                CguReuse::PreLto => "pre-lto",
+                CguReuse::PostLto => "post-lto",
+            };
+            let (reason, chain) = match reason {
+                Some(CguRecompileReason::NewCgu) => (Json::String("new".to_string()), &[][..]),
+                Some(CguRecompileReason::MissingWorkProduct) => {
+                    (Json::String("missing-work-product".to_string()), &[][..])
+                }
+                Some(CguRecompileReason::Changed(chain)) => {
+                    (Json::String("changed".to_string()), &chain[..])
+                }
+                None => (Json::Null, &[][..]),
+            };
+
+            let mut cgu = Object::new();
+            cgu.insert("name".to_string(), Json::String(cgu_name.clone()));
+            cgu.insert("reuse".to_string(), Json::String(reuse.to_string()));
+            cgu.insert("reason".to_string(), reason);
+            cgu.insert(
+                "changed".to_string(),
+                Json::Array(chain.iter().map(|node| Json::String(node.clone())).collect()),
+            );
+            Json::Object(cgu)
+        })
+        .collect();
+
+    let mut report = Object::new();
+    report.insert("codegen_units".to_string(), Json::Array(cgus));
+    format!("{}\n", Json::Object(report).pretty())
 }
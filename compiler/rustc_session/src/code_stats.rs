@@ -1,7 +1,10 @@
-use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::Lock;
+use rustc_serialize::json::{self, Json, Object};
 use rustc_target::abi::{Align, Size};
 use std::cmp::{self, Ordering};
+use std::fs;
+use std::path::Path;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -26,6 +29,18 @@ pub struct FieldInfo {
     pub align: u64,
 }
 
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    /// The offset of the field that has the niche.
+    pub offset: u64,
+    /// The size of that field.
+    pub size: u64,
+    /// The number of invalid values of the field that are used: for the largest
+    /// niche of a type, those that are still free for an enclosing enum, and for
+    /// the discriminant of an enum, those that it takes.
+    pub values: u128,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum DataTypeKind {
     Struct,
@@ -42,9 +57,23 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    /// For enums that store their discriminant in a niche of one of their
+    /// variants, where it is.
+    pub niche_discr: Option<NicheInfo>,
+    /// The niche that enums containing this type can store their discriminant in.
+    pub largest_niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
+impl TypeSizeInfo {
+    fn is_struct_like(&self) -> bool {
+        match self.kind {
+            DataTypeKind::Struct | DataTypeKind::Closure => true,
+            DataTypeKind::Enum | DataTypeKind::Union => false,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
@@ -59,6 +88,8 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
+        niche_discr: Option<NicheInfo>,
+        largest_niche: Option<NicheInfo>,
         mut variants: Vec<VariantInfo>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
@@ -72,6 +103,8 @@ pub fn record_type_size<S: ToString>(
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            niche_discr,
+            largest_niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
@@ -79,20 +112,8 @@ pub fn record_type_size<S: ToString>(
 
     pub fn print_type_sizes(&self) {
         let type_sizes = self.type_sizes.borrow();
-        let mut sorted: Vec<_> = type_sizes.iter().collect();
-
-        // Primary sort: large-to-small.
-        // Secondary sort: description (dictionary order)
-        sorted.sort_by(|info1, info2| {
-            // (reversing cmp order to get large-to-small ordering)
-            match info2.overall_size.cmp(&info1.overall_size) {
-                Ordering::Equal => info1.type_description.cmp(&info2.type_description),
-                other => other,
-            }
-        });
-
-        for info in sorted {
-            let TypeSizeInfo { type_description, overall_size, align, kind, variants, .. } = info;
+        for info in sorted(&type_sizes) {
+            let TypeSizeInfo { type_description, overall_size, align, variants, .. } = info;
             println!(
                 "print-type-size type: `{type_description}`: {overall_size} bytes, alignment: {align} bytes"
             );
@@ -111,10 +132,7 @@ pub fn print_type_sizes(&self) {
             // to reflect the presence of the discriminant.
             let mut max_variant_size = discr_size;
 
-            let struct_like = match kind {
-                DataTypeKind::Struct | DataTypeKind::Closure => true,
-                DataTypeKind::Enum | DataTypeKind::Union => false,
-            };
+            let struct_like = info.is_struct_like();
             for (i, variant_info) in variants.into_iter().enumerate() {
                 let VariantInfo { ref name, kind: _, align: _, size, ref fields } = *variant_info;
                 let indent = if !struct_like {
@@ -178,4 +196,198 @@ pub fn print_type_sizes(&self) {
             }
         }
     }
+
+    /// Prints the layouts as a JSON report, which `-Z compare-type-sizes` can
+    /// compare a later compilation against.
+    pub fn print_type_sizes_json(&self) {
+        let type_sizes = self.type_sizes.borrow();
+        let types = sorted(&type_sizes).into_iter().map(type_size_json).collect();
+        let mut report = Object::new();
+        report.insert("types".to_owned(), Json::Array(types));
+        println!("{}", Json::Object(report).pretty());
+    }
+
+    /// Prints the types that are larger than in the `-Z print-type-sizes=json`
+    /// report at `path`, with the variants and fields that grew. Types that
+    /// aren't in the report are skipped.
+    pub fn compare_type_sizes(&self, path: &Path) -> Result<(), String> {
+        let report = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let report = json::from_str(&report).map_err(|e| e.to_string())?;
+        let not_a_report = || "not a `-Z print-type-sizes=json` report".to_owned();
+        let mut old_types = FxHashMap::default();
+        for ty in report.find("types").and_then(Json::as_array).ok_or_else(not_a_report)? {
+            let name = ty.find("name").and_then(Json::as_string).ok_or_else(not_a_report)?;
+            old_types.insert(name, ty);
+        }
+
+        let type_sizes = self.type_sizes.borrow();
+        let mut grown: Vec<_> = sorted(&type_sizes)
+            .into_iter()
+            .filter_map(|info| {
+                let old = *old_types.get(&*info.type_description)?;
+                let old_size = size_of(old)?;
+                (info.overall_size > old_size).then(|| (info, old, old_size))
+            })
+            .collect();
+        // Largest growth first, with ties broken by name.
+        grown.sort_by(|(info1, _, old_size1), (info2, _, old_size2)| {
+            let growth1 = info1.overall_size - old_size1;
+            let growth2 = info2.overall_size - old_size2;
+            growth2.cmp(&growth1).then_with(|| info1.type_description.cmp(&info2.type_description))
+        });
+
+        for (info, old, old_size) in grown {
+            let TypeSizeInfo { type_description, overall_size, .. } = info;
+            println!(
+                "compare-type-size type: `{type_description}`: {old_size} -> {overall_size} bytes \
+                 (+{} bytes)",
+                overall_size - old_size
+            );
+            let old_variants = old.find("variants").and_then(Json::as_array);
+            for (i, variant) in info.variants.iter().enumerate() {
+                let old_variant = old_variants.and_then(|old_variants| {
+                    old_variants.iter().find(|v| {
+                        v.find("name").and_then(Json::as_string) == variant.name.as_deref()
+                    })
+                });
+                let indent = if info.is_struct_like() {
+                    "    "
+                } else {
+                    let name = match &variant.name {
+                        Some(name) => name.to_owned(),
+                        None => i.to_string(),
+                    };
+                    match old_variant.and_then(size_of) {
+                        Some(old_size) if variant.size > old_size => println!(
+                            "compare-type-size     variant `{name}`: {old_size} -> {} bytes",
+                            variant.size
+                        ),
+                        Some(_) => continue,
+                        None => println!(
+                            "compare-type-size     variant `{name}`: new, {} bytes",
+                            variant.size
+                        ),
+                    }
+                    "        "
+                };
+
+                let old_fields =
+                    old_variant.and_then(|v| v.find("fields")).and_then(Json::as_array);
+                for field in &variant.fields {
+                    let old_field = old_fields.and_then(|old_fields| {
+                        old_fields.iter().find(|f| {
+                            f.find("name").and_then(Json::as_string) == Some(&*field.name)
+                        })
+                    });
+                    match old_field.and_then(size_of) {
+                        Some(old_size) if field.size > old_size => println!(
+                            "compare-type-size {indent}field `.{}`: {old_size} -> {} bytes",
+                            field.name, field.size
+                        ),
+                        Some(_) => {}
+                        None => println!(
+                            "compare-type-size {indent}field `.{}`: new, {} bytes",
+                            field.name, field.size
+                        ),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sorts the types large-to-small, and those of the same size by description.
+fn sorted(type_sizes: &FxHashSet<TypeSizeInfo>) -> Vec<&TypeSizeInfo> {
+    let mut sorted: Vec<_> = type_sizes.iter().collect();
+
+    // Primary sort: large-to-small.
+    // Secondary sort: description (dictionary order)
+    sorted.sort_by(|info1, info2| {
+        // (reversing cmp order to get large-to-small ordering)
+        match info2.overall_size.cmp(&info1.overall_size) {
+            Ordering::Equal => info1.type_description.cmp(&info2.type_description),
+            other => other,
+        }
+    });
+    sorted
+}
+
+fn size_of(json: &Json) -> Option<u64> {
+    json.find("size").and_then(Json::as_u64)
+}
+
+fn type_size_json(info: &TypeSizeInfo) -> Json {
+    let mut ty = Object::new();
+    ty.insert("name".to_owned(), Json::String(info.type_description.clone()));
+    let kind = match info.kind {
+        DataTypeKind::Struct => "struct",
+        DataTypeKind::Union => "union",
+        DataTypeKind::Enum => "enum",
+        DataTypeKind::Closure => "closure",
+    };
+    ty.insert("kind".to_owned(), Json::String(kind.to_owned()));
+    ty.insert("size".to_owned(), Json::U64(info.overall_size));
+    ty.insert("align".to_owned(), Json::U64(info.align));
+    ty.insert("packed".to_owned(), Json::Boolean(info.packed));
+    if let Some(discr_size) = info.opt_discr_size {
+        ty.insert("discriminant_size".to_owned(), Json::U64(discr_size));
+    }
+    if let Some(niche) = &info.niche_discr {
+        ty.insert("niche_discriminant".to_owned(), niche_json(niche));
+    }
+    if let Some(niche) = &info.largest_niche {
+        ty.insert("largest_niche".to_owned(), niche_json(niche));
+    }
+
+    // Padding is computed the same way as for the text output, see there.
+    let discr_size = info.opt_discr_size.unwrap_or(0);
+    let mut max_variant_size = discr_size;
+    let variants = info
+        .variants
+        .iter()
+        .map(|variant_info| {
+            max_variant_size = cmp::max(max_variant_size, variant_info.size);
+            let mut fields = variant_info.fields.clone();
+            fields.sort_by_key(|f| (f.offset, f.size));
+            let mut min_offset = discr_size;
+            let mut padding = 0;
+            let fields = fields
+                .into_iter()
+                .map(|FieldInfo { name, offset, size, align }| {
+                    padding += offset.saturating_sub(min_offset);
+                    min_offset = offset + size;
+                    let mut field = Object::new();
+                    field.insert("name".to_owned(), Json::String(name));
+                    field.insert("offset".to_owned(), Json::U64(offset));
+                    field.insert("size".to_owned(), Json::U64(size));
+                    field.insert("align".to_owned(), Json::U64(align));
+                    Json::Object(field)
+                })
+                .collect();
+
+            let mut variant = Object::new();
+            let name = variant_info.name.clone().map_or(Json::Null, Json::String);
+            variant.insert("name".to_owned(), name);
+            variant.insert("size".to_owned(), Json::U64(variant_info.size));
+            variant.insert("align".to_owned(), Json::U64(variant_info.align));
+            variant.insert("exact".to_owned(), Json::Boolean(variant_info.kind == SizeKind::Exact));
+            variant.insert("padding".to_owned(), Json::U64(padding));
+            variant.insert("fields".to_owned(), Json::Array(fields));
+            Json::Object(variant)
+        })
+        .collect();
+    ty.insert("variants".to_owned(), Json::Array(variants));
+    let end_padding = info.overall_size.saturating_sub(max_variant_size);
+    ty.insert("end_padding".to_owned(), Json::U64(end_padding));
+    Json::Object(ty)
+}
+
+fn niche_json(niche: &NicheInfo) -> Json {
+    let mut json = Object::new();
+    json.insert("offset".to_owned(), Json::U64(niche.offset));
+    json.insert("size".to_owned(), Json::U64(niche.size));
+    // Only niches in 128-bit fields can have more values than this.
+    json.insert("values".to_owned(), Json::U64(niche.values.try_into().unwrap_or(u64::MAX)));
+    Json::Object(json)
 }
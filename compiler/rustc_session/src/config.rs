@@ -64,6 +64,14 @@ pub enum CFGuard {
     Checks,
 }
 
+/// The value of the `-C stack-size` flag: the main thread's stack reserve and
+/// optionally its initial commit, both in bytes.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub struct StackSize {
+    pub reserve: u64,
+    pub commit: Option<u64>,
+}
+
 /// The different settings that the `-Z cf-protection` flag can have.
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub enum CFProtection {
@@ -144,6 +152,45 @@ pub enum MirSpanview {
     Block,
 }
 
+/// The formats that the `-Z print-type-sizes` flag can print layouts in.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum PrintTypeSizes {
+    /// Default `-Z print-type-sizes` or `-Z print-type-sizes=text`
+    Text,
+    /// `-Z print-type-sizes=json`, a report that can be compared to a later one with
+    /// `-Z compare-type-sizes`.
+    Json,
+}
+
+/// The formats that the `-Z dump-mono-stats` report can be written in.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum DumpMonoStatsFormat {
+    /// Default `-Z dump-mono-stats` or `-Z dump-mono-stats=markdown`, a table of
+    /// the generic definitions.
+    Markdown,
+    /// `-Z dump-mono-stats=json`, which also lists every instance.
+    Json,
+}
+
+impl DumpMonoStatsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DumpMonoStatsFormat::Markdown => "md",
+            DumpMonoStatsFormat::Json => "json",
+        }
+    }
+}
+
+/// The formats that the `-Z incremental-explain` report can be printed in.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum IncrementalExplainFormat {
+    /// Default `-Z incremental-explain` or `-Z incremental-explain=tree`, a tree
+    /// of the codegen units and the changes that invalidated them.
+    Tree,
+    /// `-Z incremental-explain=json`.
+    Json,
+}
+
 /// The different settings that the `-C instrument-coverage` flag can have.
 ///
 /// Coverage instrumentation now supports combining `-C instrument-coverage`
@@ -163,6 +210,9 @@ pub enum MirSpanview {
 ///
 /// `ExceptUnusedGenerics` will add synthetic functions to the coverage map,
 /// unless the function has type parameters.
+///
+/// `Branch` instruments everything `All` does, and additionally adds a branch
+/// region, with a true and a false count, for each boolean condition.
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub enum InstrumentCoverage {
     /// Default `-C instrument-coverage` or `-C instrument-coverage=statement`
@@ -171,6 +221,8 @@ pub enum InstrumentCoverage {
     ExceptUnusedGenerics,
     /// `-Zunstable-options -C instrument-coverage=except-unused-functions`
     ExceptUnusedFunctions,
+    /// `-Zunstable-options -C instrument-coverage=branch`
+    Branch,
     /// `-C instrument-coverage=off` (or `no`, etc.)
     Off,
 }
@@ -290,6 +342,7 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    SymbolMap,
 }
 
 impl_stable_hash_via_hash!(OutputType);
@@ -297,7 +350,10 @@ pub enum OutputType {
 impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
-            OutputType::Exe | OutputType::DepInfo | OutputType::Metadata => true,
+            OutputType::Exe
+            | OutputType::DepInfo
+            | OutputType::Metadata
+            | OutputType::SymbolMap => true,
             OutputType::Bitcode
             | OutputType::Assembly
             | OutputType::LlvmAssembly
@@ -316,6 +372,7 @@ fn shorthand(&self) -> &'static str {
             OutputType::Metadata => "metadata",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::SymbolMap => "symbol-map",
         }
     }
 
@@ -329,13 +386,14 @@ fn from_shorthand(shorthand: &str) -> Option<Self> {
             "metadata" => OutputType::Metadata,
             "link" => OutputType::Exe,
             "dep-info" => OutputType::DepInfo,
+            "symbol-map" => OutputType::SymbolMap,
             _ => return None,
         })
     }
 
     fn shorthands_display() -> String {
         format!(
-            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
+            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
             OutputType::Bitcode.shorthand(),
             OutputType::Assembly.shorthand(),
             OutputType::LlvmAssembly.shorthand(),
@@ -344,6 +402,7 @@ fn shorthands_display() -> String {
             OutputType::Metadata.shorthand(),
             OutputType::Exe.shorthand(),
             OutputType::DepInfo.shorthand(),
+            OutputType::SymbolMap.shorthand(),
         )
     }
 
@@ -356,6 +415,7 @@ pub fn extension(&self) -> &'static str {
             OutputType::Object => "o",
             OutputType::Metadata => "rmeta",
             OutputType::DepInfo => "d",
+            OutputType::SymbolMap => "symbols",
             OutputType::Exe => "",
         }
     }
@@ -374,6 +434,11 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A SARIF 2.1.0 log, written once compilation has finished, for code scanning tools.
+    Sarif {
+        /// Render the log in a human readable way (with indents and newlines).
+        pretty: bool,
+    },
 }
 
 impl Default for ErrorOutputType {
@@ -434,7 +499,8 @@ pub fn should_codegen(&self) -> bool {
             | OutputType::LlvmAssembly
             | OutputType::Mir
             | OutputType::Object
-            | OutputType::Exe => true,
+            | OutputType::Exe
+            | OutputType::SymbolMap => true,
             OutputType::Metadata | OutputType::DepInfo => false,
         })
     }
@@ -448,7 +514,8 @@ pub fn should_link(&self) -> bool {
             | OutputType::Mir
             | OutputType::Metadata
             | OutputType::Object
-            | OutputType::DepInfo => false,
+            | OutputType::DepInfo
+            | OutputType::SymbolMap => false,
             OutputType::Exe => true,
         })
     }
@@ -583,6 +650,7 @@ pub enum PrintRequest {
     NativeStaticLibs,
     StackProtectorStrategies,
     LinkArgs,
+    LinkPlan,
 }
 
 #[derive(Copy, Clone)]
@@ -800,6 +868,8 @@ fn default() -> Options {
             json_artifact_notifications: false,
             json_unused_externs: false,
             json_future_incompat: false,
+            lint_baseline: None,
+            write_lint_baseline: None,
             pretty: None,
             working_dir: RealFileName::LocalPath(std::env::current_dir().unwrap()),
         }
@@ -1393,7 +1463,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "emit",
             "Comma separated list of types of output for \
              the compiler to emit",
-            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir]",
+            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir|symbol-map]",
         ),
         opt::multi_s(
             "",
@@ -1402,7 +1472,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "[crate-name|file-names|sysroot|target-libdir|cfg|target-list|\
              target-cpus|target-features|relocation-models|code-models|\
              tls-models|target-spec-json|native-static-libs|stack-protector-strategies|\
-             link-args]",
+             link-args|link-plan]",
         ),
         opt::flagmulti_s("g", "", "Equivalent to -C debuginfo=2"),
         opt::flagmulti_s("O", "", "Equivalent to -C opt-level=2"),
@@ -1436,6 +1506,18 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
              level",
             "LEVEL",
         ),
+        opt::opt(
+            "",
+            "lint-baseline",
+            "Suppress the lint diagnostics that are listed in FILE",
+            "FILE",
+        ),
+        opt::opt(
+            "",
+            "write-lint-baseline",
+            "Write every lint diagnostic that is reported to FILE, for use with --lint-baseline",
+            "FILE",
+        ),
         opt::multi_s("C", "codegen", "Set a codegen option", "OPT[=VALUE]"),
         opt::flag_s("V", "version", "Print version info and exit"),
         opt::flag_s("v", "verbose", "Use verbose output"),
@@ -1613,12 +1695,14 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif { pretty: false },
+            Some("pretty-sarif") => ErrorOutputType::Sarif { pretty: true },
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{arg}`)"
+                    "argument for `--error-format` must be `human`, `json`, `short` or \
+                     `sarif` (instead was `{arg}`)"
                 ),
             ),
         }
@@ -1695,6 +1779,13 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif { pretty } = error_format {
+            let format = if pretty { "pretty-sarif" } else { "sarif" };
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                &format!("`--error-format={format}` is unstable"),
+            );
+        }
     }
 }
 
@@ -1830,6 +1921,17 @@ fn collect_print_requests(
             }
         }
         "link-args" => PrintRequest::LinkArgs,
+        "link-plan" => {
+            if dopts.unstable_options {
+                PrintRequest::LinkPlan
+            } else {
+                early_error(
+                    error_format,
+                    "the `-Z unstable-options` flag must also be passed to \
+                     enable the link-plan print option",
+                );
+            }
+        }
         req => early_error(error_format, &format!("unknown print request `{req}`")),
     }));
 
@@ -2432,7 +2534,8 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         (Some(_), _) if !debugging_opts.unstable_options => {
             early_error(
                 error_format,
-                "`-C instrument-coverage=except-*` requires `-Z unstable-options`",
+                "`-C instrument-coverage=except-*` and `-C instrument-coverage=branch` \
+                require `-Z unstable-options`",
             );
         }
         (None, None) => {}
@@ -2531,6 +2634,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let remap_path_prefix = parse_remap_path_prefix(matches, &debugging_opts, error_format);
 
+    let lint_baseline = matches.opt_str("lint-baseline").map(PathBuf::from);
+    let write_lint_baseline = matches.opt_str("write-lint-baseline").map(PathBuf::from);
+
     let pretty = parse_pretty(&debugging_opts, error_format);
 
     if !debugging_opts.unstable_options
@@ -2615,6 +2721,8 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        lint_baseline,
+        write_lint_baseline,
         pretty,
         working_dir,
     }
@@ -121,6 +121,10 @@ pub fn instrument_coverage_except_unused_functions(&self) -> bool {
         self.cg.instrument_coverage.unwrap_or(InstrumentCoverage::Off)
             == InstrumentCoverage::ExceptUnusedFunctions
     }
+
+    pub fn instrument_coverage_branch(&self) -> bool {
+        self.cg.instrument_coverage.unwrap_or(InstrumentCoverage::Off) == InstrumentCoverage::Branch
+    }
 }
 
 top_level_options!(
@@ -226,6 +230,12 @@ pub struct Options {
         /// `true` if we're emitting a JSON job containing a future-incompat report for lints
         json_future_incompat: bool [TRACKED],
 
+        /// The lint diagnostics to suppress because they were already there. Lints
+        /// only record the item they're in when a baseline is used, so this is tracked.
+        lint_baseline: Option<PathBuf> [TRACKED_NO_CRATE_HASH],
+        /// Where to record the lint diagnostics that are reported, to create a baseline.
+        write_lint_baseline: Option<PathBuf> [TRACKED_NO_CRATE_HASH],
+
         pretty: Option<PpMode> [UNTRACKED],
 
         /// The (potentially remapped) working directory
@@ -382,11 +392,18 @@ mod desc {
         "either a boolean (`yes`, `no`, `on`, `off`, etc), `checks`, or `nochecks`";
     pub const parse_cfprotection: &str = "`none`|`no`|`n` (default), `branch`, `return`, or `full`|`yes`|`y` (equivalent to `branch` and `return`)";
     pub const parse_strip: &str = "either `none`, `debuginfo`, or `symbols`";
+    pub const parse_opt_address: &str = "a decimal or `0x`-prefixed hexadecimal address";
+    pub const parse_opt_size: &str = "a size in bytes, optionally followed by `K`, `M`, or `G`";
+    pub const parse_stack_size: &str =
+        "a size in bytes, optionally followed by `,` and the initial commit size";
     pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavor::one_of();
     pub const parse_optimization_fuel: &str = "crate=integer";
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
+    pub const parse_print_type_sizes: &str = "`text` (default) or `json`";
+    pub const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
+    pub const parse_incremental_explain: &str = "`tree` (default) or `json`";
     pub const parse_instrument_coverage: &str =
-        "`all` (default), `except-unused-generics`, `except-unused-functions`, or `off`";
+        "`all` (default), `except-unused-generics`, `except-unused-functions`, `branch`, or `off`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
     pub const parse_treat_err_as_bug: &str = "either no value or a number bigger than 0";
     pub const parse_lto: &str =
@@ -688,6 +705,64 @@ mod parse {
         true
     }
 
+    /// Parses a decimal or `0x`-prefixed hexadecimal number.
+    fn parse_address(s: &str) -> Option<u64> {
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        }
+    }
+
+    crate fn parse_opt_address(slot: &mut Option<u64>, v: Option<&str>) -> bool {
+        match v.and_then(parse_address) {
+            Some(addr) => {
+                *slot = Some(addr);
+                true
+            }
+            None => false,
+        }
+    }
+
+    crate fn parse_opt_size(slot: &mut Option<u64>, v: Option<&str>) -> bool {
+        let Some(s) = v else {
+            return false;
+        };
+        let (digits, shift) = match s.as_bytes().last() {
+            Some(b'K') => (&s[..s.len() - 1], 10),
+            Some(b'M') => (&s[..s.len() - 1], 20),
+            Some(b'G') => (&s[..s.len() - 1], 30),
+            _ => (s, 0),
+        };
+        match digits.parse::<u64>().ok().and_then(|n| n.checked_mul(1 << shift)) {
+            Some(size) => {
+                *slot = Some(size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    crate fn parse_stack_size(slot: &mut Option<StackSize>, v: Option<&str>) -> bool {
+        let (reserve, commit) = match v {
+            Some(s) => match s.split_once(',') {
+                Some((reserve, commit)) => (reserve, Some(commit)),
+                None => (s, None),
+            },
+            None => return false,
+        };
+        let reserve = match parse_address(reserve) {
+            Some(reserve) => reserve,
+            None => return false,
+        };
+        let commit = match commit.map(parse_address) {
+            Some(Some(commit)) if commit <= reserve => Some(commit),
+            Some(_) => return false,
+            None => None,
+        };
+        *slot = Some(StackSize { reserve, commit });
+        true
+    }
+
     crate fn parse_cfguard(slot: &mut CFGuard, v: Option<&str>) -> bool {
         if v.is_some() {
             let mut bool_arg = None;
@@ -786,6 +861,63 @@ mod parse {
         true
     }
 
+    crate fn parse_print_type_sizes(slot: &mut Option<PrintTypeSizes>, v: Option<&str>) -> bool {
+        if v.is_some() {
+            let mut bool_arg = None;
+            if parse_opt_bool(&mut bool_arg, v) {
+                *slot = if bool_arg.unwrap() { Some(PrintTypeSizes::Text) } else { None };
+                return true;
+            }
+        }
+
+        *slot = Some(match v {
+            None | Some("text") => PrintTypeSizes::Text,
+            Some("json") => PrintTypeSizes::Json,
+            _ => return false,
+        });
+        true
+    }
+
+    crate fn parse_dump_mono_stats(
+        slot: &mut Option<DumpMonoStatsFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        if v.is_some() {
+            let mut bool_arg = None;
+            if parse_opt_bool(&mut bool_arg, v) {
+                *slot = if bool_arg.unwrap() { Some(DumpMonoStatsFormat::Markdown) } else { None };
+                return true;
+            }
+        }
+
+        *slot = Some(match v {
+            None | Some("markdown") => DumpMonoStatsFormat::Markdown,
+            Some("json") => DumpMonoStatsFormat::Json,
+            _ => return false,
+        });
+        true
+    }
+
+    crate fn parse_incremental_explain(
+        slot: &mut Option<IncrementalExplainFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        if v.is_some() {
+            let mut bool_arg = None;
+            if parse_opt_bool(&mut bool_arg, v) {
+                *slot = if bool_arg.unwrap() { Some(IncrementalExplainFormat::Tree) } else { None };
+                return true;
+            }
+        }
+
+        *slot = Some(match v {
+            None | Some("tree") => IncrementalExplainFormat::Tree,
+            Some("json") => IncrementalExplainFormat::Json,
+            _ => return false,
+        });
+        true
+    }
+
     crate fn parse_instrument_coverage(
         slot: &mut Option<InstrumentCoverage>,
         v: Option<&str>,
@@ -811,6 +943,7 @@ mod parse {
             "except-unused-functions" | "except_unused_functions" => {
                 InstrumentCoverage::ExceptUnusedFunctions
             }
+            "branch" => InstrumentCoverage::Branch,
             "off" | "no" | "n" | "false" | "0" => InstrumentCoverage::Off,
             _ => return false,
         });
@@ -1047,6 +1180,8 @@ mod parse {
         "force use of the frame pointers"),
     force_unwind_tables: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "force use of unwind tables"),
+    image_base: Option<u64> = (None, parse_opt_address, [UNTRACKED],
+        "preferred load address of the linked image (Windows targets only)"),
     incremental: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "enable incremental compilation"),
     inline_threshold: Option<u32> = (None, parse_opt_number, [TRACKED],
@@ -1058,13 +1193,20 @@ mod parse {
         `=all` (implicit value)
         `=except-unused-generics`
         `=except-unused-functions`
+        `=branch`
         `=off` (default)"),
+    large_address_aware: Option<bool> = (None, parse_opt_bool, [UNTRACKED],
+        "mark the image as able to handle addresses above 2 GB (Windows targets only)"),
     link_arg: (/* redirected to link_args */) = ((), parse_string_push, [UNTRACKED],
         "a single extra argument to append to the linker invocation (can be used several times)"),
     link_args: Vec<String> = (Vec::new(), parse_list, [UNTRACKED],
         "extra arguments to append to the linker invocation (space separated)"),
     link_dead_code: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "keep dead code at link time (useful for code coverage) (default: no)"),
+    link_map: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "have the linker write a map file, optionally to the given path \
+        (default: the output file name with a `.map` extension)"),
     link_self_contained: Option<bool> = (None, parse_opt_bool, [UNTRACKED],
         "control whether to link Rust provided C objects/libraries or rely
         on C toolchain installed in the system"),
@@ -1119,6 +1261,9 @@ mod parse {
         "use soft float ABI (*eabihf targets only) (default: no)"),
     split_debuginfo: Option<SplitDebuginfo> = (None, parse_split_debuginfo, [TRACKED],
         "how to handle split-debuginfo, a platform-specific option"),
+    stack_size: Option<StackSize> = (None, parse_stack_size, [UNTRACKED],
+        "stack reserve and optional commit size of the main thread, in bytes \
+        (`reserve[,commit]`, Windows targets only)"),
     strip: Strip = (Strip::None, parse_strip, [UNTRACKED],
         "tell the linker which information to strip (`none` (default), `debuginfo` or `symbols`)"),
     symbol_mangling_version: Option<SymbolManglingVersion> = (None,
@@ -1177,6 +1322,8 @@ mod parse {
         "the backend to use"),
     combine_cgu: bool = (false, parse_bool, [TRACKED],
         "combine CGUs into a single one"),
+    compare_type_sizes: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "print the types that grew since the `-Z print-type-sizes=json` report at the given path"),
     crate_attr: Vec<String> = (Vec::new(), parse_string_push, [TRACKED],
         "inject the given attribute in the crate"),
     debug_info_for_profiling: bool = (false, parse_bool, [TRACKED],
@@ -1227,6 +1374,11 @@ mod parse {
         computed `block` spans (one span encompassing a block's terminator and \
         all statements). If `-Z instrument-coverage` is also enabled, create \
         an additional `.html` file showing the computed coverage spans."),
+    dump_mono_stats: Option<DumpMonoStatsFormat> = (None, parse_dump_mono_stats, [UNTRACKED],
+        "write the number of instances of every generic definition, with their sizes as estimated \
+        from their MIR (not the generated code) and the codegen units they are placed in, to \
+        `<crate>.mono_items.md` (`markdown`, the default) or `<crate>.mono_items.json` (`json`) \
+        in the output directory (default: no)"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emit a section containing stack size metadata (default: no)"),
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1252,6 +1404,15 @@ mod parse {
         "generate human-readable, predictable names for codegen units (default: no)"),
     identify_regions: bool = (false, parse_bool, [UNTRACKED],
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
+    incremental_cache_budget: Option<u64> = (None, parse_opt_size, [UNTRACKED],
+        "delete the least recently used incremental compilation sessions and work products \
+        when the incremental compilation directory is larger than this (default: no limit)"),
+    incremental_compress: bool = (false, parse_bool, [UNTRACKED],
+        "compress the dep-graph and query result cache of incremental compilation sessions \
+        (default: no)"),
+    incremental_explain: Option<IncrementalExplainFormat> = (None, parse_incremental_explain,
+        [UNTRACKED], "print why the codegen units that could not be reused from the incremental \
+        cache had to be recompiled; `tree` (default) or `json`"),
     incremental_ignore_spans: bool = (false, parse_bool, [UNTRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
@@ -1278,6 +1439,7 @@ mod parse {
         `=all` (implicit value)
         `=except-unused-generics`
         `=except-unused-functions`
+        `=branch`
         `=off` (default)"),
     instrument_mcount: bool = (false, parse_bool, [TRACKED],
         "insert function instrument code for mcount-based tracing (default: no)"),
@@ -1374,8 +1536,12 @@ mod parse {
         "print the LLVM optimization passes being run (default: no)"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
-    print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
-        "print layout information for each type encountered (default: no)"),
+    print_stack_usage: bool = (false, parse_bool, [TRACKED],
+        "print the worst-case stack usage of the functions that nothing in the crate calls, \
+        from the call graph and the frame sizes that the backend reports (default: no)"),
+    print_type_sizes: Option<PrintTypeSizes> = (None, parse_print_type_sizes, [UNTRACKED],
+        "print layout information for each type encountered, as `text` (the default) \
+        or `json` (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
          "show backtraces for panics during proc-macro execution (default: no)"),
     profile: bool = (false, parse_bool, [TRACKED],
@@ -1520,6 +1686,9 @@ mod parse {
         "in general, enable more debug printouts (default: no)"),
     verify_llvm_ir: bool = (false, parse_bool, [TRACKED],
         "verify LLVM IR (default: no)"),
+    verify_target_cpu: bool = (false, parse_bool, [UNTRACKED],
+        "disassemble the generated object files and report instructions that the target CPU \
+        does not support (default: no)"),
     wasi_exec_model: Option<WasiExecModel> = (None, parse_wasi_exec_model, [TRACKED],
         "whether to build a wasi command or reactor"),
 
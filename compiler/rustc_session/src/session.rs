@@ -1,6 +1,6 @@
 use crate::cgu_reuse_tracker::CguReuseTracker;
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+pub use crate::code_stats::{DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use crate::config::{self, CrateType, OutputType, SwitchWithOptPath};
 use crate::parse::ParseSess;
 use crate::search_paths::{PathKind, SearchPath};
@@ -19,7 +19,8 @@
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
-use rustc_errors::{DiagnosticBuilder, DiagnosticId, ErrorGuaranteed};
+use rustc_errors::sarif::SarifEmitter;
+use rustc_errors::{DiagnosticBuilder, DiagnosticId, ErrorGuaranteed, LintBaseline};
 use rustc_macros::HashStable_Generic;
 pub use rustc_span::def_id::StableCrateId;
 use rustc_span::edition::Edition;
@@ -248,6 +249,10 @@ fn check_miri_unleashed_features(&self) {
     /// Invoked all the way at the end to finish off diagnostics printing.
     pub fn finish_diagnostics(&self, registry: &Registry) {
         self.check_miri_unleashed_features();
+        if let Err(e) = self.diagnostic().write_lint_baseline() {
+            let path = self.opts.write_lint_baseline.as_ref().unwrap();
+            self.err(&format!("failed to write lint baseline `{}`: {e}", path.display()));
+        }
         self.diagnostic().print_error_count(registry);
         self.emit_future_breakage();
     }
@@ -264,6 +269,18 @@ fn emit_future_breakage(&self) {
         self.parse_sess.span_diagnostic.emit_future_breakage_report(diags);
     }
 
+    /// Whether the layouts of types are recorded in `code_stats`, for
+    /// `-Z print-type-sizes` or `-Z compare-type-sizes`.
+    pub fn records_type_sizes(&self) -> bool {
+        self.opts.debugging_opts.print_type_sizes.is_some()
+            || self.opts.debugging_opts.compare_type_sizes.is_some()
+    }
+
+    /// Whether lints are matched against or recorded in a lint baseline.
+    pub fn uses_lint_baseline(&self) -> bool {
+        self.opts.lint_baseline.is_some() || self.opts.write_lint_baseline.is_some()
+    }
+
     pub fn local_stable_crate_id(&self) -> StableCrateId {
         self.stable_crate_id.get().copied().unwrap()
     }
@@ -442,7 +459,7 @@ pub fn delay_span_bug<S: Into<MultiSpan>>(&self, sp: S, msg: &str) -> ErrorGuara
     /// warnings or errors are emitted. If no messages are emitted ("good path"), then
     /// it's likely a bug.
     pub fn delay_good_path_bug(&self, msg: &str) {
-        if self.opts.debugging_opts.print_type_sizes
+        if self.records_type_sizes()
             || self.opts.debugging_opts.query_dep_graph
             || self.opts.debugging_opts.dump_mir.is_some()
             || self.opts.debugging_opts.unpretty.is_some()
@@ -994,6 +1011,10 @@ pub fn instrument_coverage_except_unused_functions(&self) -> bool {
         self.opts.instrument_coverage_except_unused_functions()
     }
 
+    pub fn instrument_coverage_branch(&self) -> bool {
+        self.opts.instrument_coverage_branch()
+    }
+
     pub fn is_proc_macro_attr(&self, attr: &Attribute) -> bool {
         [sym::proc_macro, sym::proc_macro_attribute, sym::proc_macro_derive]
             .iter()
@@ -1090,6 +1111,12 @@ fn default_emitter(
             )
             .ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif { pretty }, None) => {
+            Box::new(SarifEmitter::stderr(source_map, pretty))
+        }
+        (config::ErrorOutputType::Sarif { pretty }, Some(dst)) => {
+            Box::new(SarifEmitter::new(dst, source_map, pretty))
+        }
     }
 }
 
@@ -1158,6 +1185,16 @@ pub fn build_session(
         emitter,
         sopts.debugging_opts.diagnostic_handler_flags(can_emit_warnings),
     );
+    if sopts.lint_baseline.is_some() || sopts.write_lint_baseline.is_some() {
+        let path = sopts.lint_baseline.as_deref();
+        match LintBaseline::new(path, sopts.write_lint_baseline.clone()) {
+            Ok(baseline) => span_diagnostic.set_lint_baseline(baseline),
+            Err(e) => early_error(
+                sopts.error_format,
+                &format!("failed to read lint baseline `{}`: {e}", path.unwrap().display()),
+            ),
+        }
+    }
 
     let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.debugging_opts.self_profile
     {
@@ -1205,7 +1242,9 @@ pub fn build_session(
     });
     let print_fuel = AtomicU64::new(0);
 
-    let cgu_reuse_tracker = if sopts.debugging_opts.query_dep_graph {
+    let cgu_reuse_tracker = if sopts.debugging_opts.query_dep_graph
+        || sopts.debugging_opts.incremental_explain.is_some()
+    {
         CguReuseTracker::new()
     } else {
         CguReuseTracker::new_disabled()
@@ -1364,6 +1403,25 @@ fn validate_commandline_args_with_session_available(sess: &Session) {
             ))
         }
     }
+
+    if sess.opts.debugging_opts.incremental_explain.is_some() && sess.opts.incremental.is_none() {
+        sess.warn("`-Z incremental-explain` has no effect without `-C incremental`");
+    }
+
+    // The PE image options only have a meaning for PE/COFF outputs.
+    if !sess.target.is_like_windows {
+        let pe_options = [
+            ("image-base", sess.opts.cg.image_base.is_some()),
+            ("large-address-aware", sess.opts.cg.large_address_aware.is_some()),
+            ("stack-size", sess.opts.cg.stack_size.is_some()),
+        ];
+        for (name, _) in pe_options.iter().filter(|(_, set)| *set) {
+            sess.warn(&format!(
+                "`-C {}` is not supported for target {} and will be ignored",
+                name, sess.opts.target_triple
+            ));
+        }
+    }
 }
 
 /// Holds data on the current incremental compilation session, if there is one.
@@ -1393,6 +1451,7 @@ fn early_error_handler(output: config::ErrorOutputType) -> rustc_errors::Handler
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, false))
         }
+        config::ErrorOutputType::Sarif { pretty } => Box::new(SarifEmitter::basic(pretty)),
     };
     rustc_errors::Handler::with_emitter(true, None, emitter)
 }
@@ -0,0 +1,10 @@
+use crate::spec::Target;
+
+pub fn target() -> Target {
+    let mut base = super::i586_rust9x_windows_msvc::target();
+    base.cpu = "i486".into();
+    // The i486 lacks `cmpxchg8b`, so there are no 64-bit atomics.
+    base.max_atomic_width = Some(32);
+    base.llvm_target = "i486-pc-windows-msvc".into();
+    base
+}
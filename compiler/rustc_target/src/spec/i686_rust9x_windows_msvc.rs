@@ -1,4 +1,4 @@
-use crate::spec::{LinkerFlavor, LldFlavor, Target};
+use crate::spec::{MsvcCxxPersonality, Target};
 
 pub fn target() -> Target {
     let mut base = super::windows_msvc_base::opts();
@@ -6,17 +6,8 @@ pub fn target() -> Target {
     base.max_atomic_width = Some(64);
     base.vendor = "rust9x".into();
 
-    let pre_link_args_msvc = vec![
-        // Link to ___CxxFrameHandler (XP and earlier MSVCRT) instead of ___CxxFrameHandler3.
-        // This cannot be done in the MSVC `eh_personality` handling because LLVM hardcodes SEH
-        // support based on that name, sadly
-        "/ALTERNATENAME:___CxxFrameHandler3=___CxxFrameHandler".into(),
-    ];
-    base.pre_link_args.entry(LinkerFlavor::Msvc).or_default().extend(pre_link_args_msvc.clone());
-    base.pre_link_args
-        .entry(LinkerFlavor::Lld(LldFlavor::Link))
-        .or_default()
-        .extend(pre_link_args_msvc);
+    // Use the personality of the XP and earlier MSVCRT instead of `__CxxFrameHandler3`.
+    base.msvc_cxx_personality = MsvcCxxPersonality::FrameHandler;
 
     Target {
         llvm_target: "i686-pc-windows-msvc".into(),
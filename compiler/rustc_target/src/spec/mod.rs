@@ -524,6 +524,52 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// The MSVC C++ exception handling personality, which also determines the version of the EH
+/// tables that the personality expects.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub enum MsvcCxxPersonality {
+    /// `__CxxFrameHandler3`, provided by every CRT since Visual C++ 2005.
+    FrameHandler3,
+    /// `__CxxFrameHandler`, the only personality provided by the CRT of Visual C++ 6 and the
+    /// system `msvcrt.dll` up to Windows XP.
+    FrameHandler,
+}
+
+impl MsvcCxxPersonality {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MsvcCxxPersonality::FrameHandler3 => "fh3",
+            MsvcCxxPersonality::FrameHandler => "fh",
+        }
+    }
+
+    /// The undecorated name of the personality function.
+    pub fn symbol_name(&self) -> &'static str {
+        match self {
+            MsvcCxxPersonality::FrameHandler3 => "__CxxFrameHandler3",
+            MsvcCxxPersonality::FrameHandler => "__CxxFrameHandler",
+        }
+    }
+}
+
+impl FromStr for MsvcCxxPersonality {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<MsvcCxxPersonality, ()> {
+        Ok(match s {
+            "fh3" => MsvcCxxPersonality::FrameHandler3,
+            "fh" => MsvcCxxPersonality::FrameHandler,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl ToJson for MsvcCxxPersonality {
+    fn to_json(&self) -> Json {
+        self.as_str().to_json()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StackProbeType {
     /// Don't emit any stack probes.
@@ -948,6 +994,7 @@ fn $module() {
     ("i586-pc-windows-msvc", i586_pc_windows_msvc),
     ("i686-rust9x-windows-msvc", i686_rust9x_windows_msvc),
     ("i586-rust9x-windows-msvc", i586_rust9x_windows_msvc),
+    ("i486-rust9x-windows-msvc", i486_rust9x_windows_msvc),
     ("thumbv7a-pc-windows-msvc", thumbv7a_pc_windows_msvc),
     ("thumbv7a-uwp-windows-msvc", thumbv7a_uwp_windows_msvc),
 
@@ -1430,6 +1477,10 @@ pub struct TargetOptions {
     /// target-specific meaning.
     pub split_debuginfo: SplitDebuginfo,
 
+    /// The C++ personality function used for SEH unwinding on MSVC-like targets. Only has an
+    /// effect if `is_like_msvc` is set. Defaults to `FrameHandler3`.
+    pub msvc_cxx_personality: MsvcCxxPersonality,
+
     /// The sanitizers supported by this target
     ///
     /// Note that the support here is at a codegen level. If the machine code with sanitizer
@@ -1551,6 +1602,7 @@ fn default() -> TargetOptions {
             eh_frame_header: true,
             has_thumb_interworking: false,
             split_debuginfo: SplitDebuginfo::Off,
+            msvc_cxx_personality: MsvcCxxPersonality::FrameHandler3,
             supported_sanitizers: SanitizerSet::empty(),
             default_adjusted_cabi: None,
             c_enum_min_bits: 32,
@@ -1829,6 +1881,18 @@ macro_rules! key {
                     Some(Ok(()))
                 })).unwrap_or(Ok(()))
             } );
+            ($key_name:ident, MsvcCxxPersonality) => ( {
+                let name = (stringify!($key_name)).replace("_", "-");
+                obj.remove_key(&name[..]).and_then(|o| o.as_string().and_then(|s| {
+                    match s.parse::<MsvcCxxPersonality>() {
+                        Ok(personality) => base.$key_name = personality,
+                        _ => return Some(Err(format!("'{}' is not a valid value for \
+                                                      msvc-cxx-personality. Use 'fh3' or 'fh'.",
+                                                      s))),
+                    }
+                    Some(Ok(()))
+                })).unwrap_or(Ok(()))
+            } );
             ($key_name:ident, list) => ( {
                 let name = (stringify!($key_name)).replace("_", "-");
                 if let Some(j) = obj.remove_key(&name){
@@ -2141,6 +2205,7 @@ macro_rules! key {
         key!(eh_frame_header, bool);
         key!(has_thumb_interworking, bool);
         key!(split_debuginfo, SplitDebuginfo)?;
+        key!(msvc_cxx_personality, MsvcCxxPersonality)?;
         key!(supported_sanitizers, SanitizerSet)?;
         key!(default_adjusted_cabi, Option<Abi>)?;
         key!(c_enum_min_bits, u64);
@@ -2394,6 +2459,7 @@ macro_rules! target_option_val {
         target_option_val!(eh_frame_header);
         target_option_val!(has_thumb_interworking);
         target_option_val!(split_debuginfo);
+        target_option_val!(msvc_cxx_personality);
         target_option_val!(supported_sanitizers);
         target_option_val!(c_enum_min_bits);
         target_option_val!(generate_arange_section);
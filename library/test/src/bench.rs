@@ -9,6 +9,7 @@
 
 use crate::stats;
 use std::cmp;
+use std::fmt;
 use std::io;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
@@ -58,10 +59,91 @@ pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
     }
 }
 
+/// Number of timing samples a benchmark's summary is computed from.
+pub const SAMPLE_COUNT: usize = 50;
+
+/// Two measurements that differ with a p-value below this are considered significantly different.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
     pub mb_s: usize,
+    /// Comparison with the same benchmark in the baseline passed to `--baseline`.
+    pub comparison: Option<BenchComparison>,
+}
+
+/// The statistics of a benchmark that are saved in a baseline by `--save-baseline`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchBaseline {
+    pub samples: usize,
+    pub median: f64,
+    pub mean: f64,
+    pub var: f64,
+}
+
+impl BenchBaseline {
+    pub fn new(summ: &stats::Summary) -> BenchBaseline {
+        BenchBaseline { samples: SAMPLE_COUNT, median: summ.median, mean: summ.mean, var: summ.var }
+    }
+
+    /// Compares a new measurement of the benchmark with this one. Differences in the median
+    /// of at most `noise_threshold`, as a fraction of the baseline median, are considered noise.
+    pub fn compare(&self, new: &BenchBaseline, noise_threshold: f64) -> BenchComparison {
+        let change =
+            if self.median == 0.0 { 0.0 } else { (new.median - self.median) / self.median };
+        let p_value =
+            stats::welch_t_test(self.mean, self.var, self.samples, new.mean, new.var, new.samples);
+        let verdict = if p_value >= SIGNIFICANCE_LEVEL {
+            BenchVerdict::NoChange
+        } else if change.abs() <= noise_threshold {
+            BenchVerdict::WithinNoise
+        } else if change > 0.0 {
+            BenchVerdict::Regressed
+        } else {
+            BenchVerdict::Improved
+        };
+        BenchComparison { baseline_median: self.median, change, p_value, verdict }
+    }
+}
+
+/// How a benchmark compares with its baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchComparison {
+    /// Median of the baseline, in ns/iter.
+    pub baseline_median: f64,
+    /// Change of the median relative to the baseline, e.g. `0.1` for 10% slower.
+    pub change: f64,
+    /// p-value of the difference between the measurements.
+    pub p_value: f64,
+    pub verdict: BenchVerdict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchVerdict {
+    /// The difference is not statistically significant.
+    NoChange,
+    /// The difference is significant, but not larger than the noise threshold.
+    WithinNoise,
+    Improved,
+    Regressed,
+}
+
+impl BenchVerdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BenchVerdict::NoChange => "no change",
+            BenchVerdict::WithinNoise => "within noise",
+            BenchVerdict::Improved => "improved",
+            BenchVerdict::Regressed => "regressed",
+        }
+    }
+}
+
+impl fmt::Display for BenchVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -81,6 +163,16 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     if bs.mb_s != 0 {
         write!(output, " = {} MB/s", bs.mb_s).unwrap();
     }
+    if let Some(ref comparison) = bs.comparison {
+        write!(
+            output,
+            " ({:+.2}%, p = {:.2}, {})",
+            comparison.change * 100.0,
+            comparison.p_value,
+            comparison.verdict
+        )
+        .unwrap();
+    }
     output
 }
 
@@ -139,7 +231,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     n = cmp::max(1, n);
 
     let mut total_run = Duration::new(0, 0);
-    let samples: &mut [f64] = &mut [0.0_f64; 50];
+    let samples: &mut [f64] = &mut [0.0_f64; SAMPLE_COUNT];
     loop {
         let loop_start = Instant::now();
 
@@ -215,14 +307,18 @@ pub fn benchmark<F>(
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
+            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize, comparison: None };
             TestResult::TrBench(bs)
         }
         Ok(None) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let bs = BenchSamples {
+                ns_iter_summ: stats::Summary::new(samples),
+                mb_s: 0,
+                comparison: None,
+            };
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
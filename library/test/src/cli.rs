@@ -2,9 +2,12 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use super::helpers::isatty;
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::helpers::{baseline, isatty, rerun};
+use super::options::{
+    ColorConfig, CompareBaseline, Options, OutputFormat, RerunFailed, RunIgnored, TestShard,
+};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -13,6 +16,7 @@ pub struct TestOpts {
     pub filters: Vec<String>,
     pub filter_exact: bool,
     pub force_run_in_process: bool,
+    pub isolate: bool,
     pub exclude_should_panic: bool,
     pub run_ignored: RunIgnored,
     pub run_tests: bool,
@@ -23,9 +27,15 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    pub shard: Option<TestShard>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    pub test_timeout: Option<Duration>,
+    pub retries: usize,
+    pub rerun_failed: Option<RerunFailed>,
+    pub save_baseline: Option<PathBuf>,
+    pub baseline: Option<CompareBaseline>,
     pub options: Options,
 }
 
@@ -49,6 +59,7 @@ fn optgroups() -> getopts::Options {
     opts.optflag("", "include-ignored", "Run ignored and not ignored tests")
         .optflag("", "ignored", "Run only ignored tests")
         .optflag("", "force-run-in-process", "Forces tests to run in-process when panic=abort")
+        .optflag("", "isolate", "Run every test in its own process")
         .optflag("", "exclude-should-panic", "Excludes tests marked as should_panic")
         .optflag("", "test", "Run tests and not benchmarks")
         .optflag("", "bench", "Run benchmarks instead of tests")
@@ -144,6 +155,40 @@ fn optgroups() -> getopts::Options {
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of shard INDEX (counting from 0) out of --shard-count shards",
+            "INDEX",
+        )
+        .optopt("", "shard-count", "Split the tests into COUNT shards", "COUNT")
+        .optopt(
+            "",
+            "test-timeout",
+            "Run every test in its own process and kill it after SECS seconds",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests up to N more times and report them as flaky if they pass",
+            "N",
+        )
+        .optopt(
+            "",
+            "rerun-failed",
+            "Only run the tests that failed last time according to PATH, and update PATH",
+            "PATH",
+        )
+        .optopt("", "save-baseline", "Save the benchmark results as the baseline NAME", "NAME")
+        .optopt("", "baseline", "Compare the benchmark results with the baseline NAME", "NAME")
+        .optopt(
+            "",
+            "noise-threshold",
+            "Treat changes of benchmark results smaller than PCT percent of the baseline \
+             as noise (default: 2)",
+            "PCT",
         );
     opts
 }
@@ -167,6 +212,40 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+With --retries N, a test that fails is run up to N more times. If a later
+attempt passes, the test is reported as flaky instead of failed. Only tests that
+are known statically can be retried.
+
+With --rerun-failed PATH, the names of the tests that fail are recorded in
+PATH, and the next run with the same PATH only runs those tests. If PATH does
+not exist or the previous run had no failures, all tests are run. Recorded tests
+that are not run stay recorded until they pass.
+
+With --isolate, every test is run in a separate process, so that a test that
+crashes, for example with a segmentation fault, is reported as a failure with
+the reason the process terminated instead of taking down the whole run. This is
+always the case for test binaries built with panic=abort.
+
+With --test-timeout, every test is run in a separate process, which is killed
+and reported as timed out if the test does not finish in time. Benchmarks and
+tests that are not known statically (such as doctests) still run in process
+with both options.
+
+With --save-baseline NAME, the results of the benchmarks are saved as the
+baseline NAME, which later runs can be compared with using --baseline NAME.
+Baselines are stored in the directory named by RUST_BENCH_BASELINE_DIR, or in
+target/bench-baselines if it is not set. Every benchmark that is in the baseline
+is reported as improved or regressed if the difference is statistically
+significant (Welch's t-test, p < 0.05) and the median changed by more than the
+--noise-threshold.
+
+The tests can be split between several processes with --shard-index and
+--shard-count. Every test is assigned to one of the shards based on a hash of
+its name, so the assignment does not depend on the machine, on the other tests
+or on the order in which the tests are run. Running every shard from 0 to
+COUNT-1 runs every test exactly once. Sharding is applied after the filters,
+--skip and --ignored, and also restricts --list to the tests of the shard.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -252,10 +331,17 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
 
     // Unstable flags
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
+    let isolate = unstable_optflag!(matches, allow_unstable, "isolate");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let rerun_failed = get_rerun_failed(&matches, allow_unstable)?;
+    let save_baseline = get_save_baseline(&matches, allow_unstable)?;
+    let baseline = get_baseline(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -281,6 +367,7 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         filters,
         filter_exact: exact,
         force_run_in_process,
+        isolate,
         exclude_should_panic,
         run_ignored,
         run_tests,
@@ -291,9 +378,15 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
+        rerun_failed,
+        save_baseline,
+        baseline,
         options,
     };
 
@@ -369,6 +462,113 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<TestShard>> {
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let (index, count) = match (index, count) {
+        (None, None) => return Ok(None),
+        (Some(index), Some(count)) => (index, count),
+        _ => return Err("--shard-index and --shard-count must be used together".into()),
+    };
+
+    let index = match index.parse::<usize>() {
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!("argument for --shard-index must be a number (error: {})", e));
+        }
+    };
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".into()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!("argument for --shard-count must be a number (error: {})", e));
+        }
+    };
+    if index >= count {
+        return Err(format!(
+            "argument for --shard-index must be less than --shard-count ({}), but is {}",
+            count, index
+        ));
+    }
+
+    Ok(Some(TestShard { index, count }))
+}
+
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs) => secs,
+        None => return Ok(None),
+    };
+    match timeout.parse::<u64>() {
+        Ok(0) => Err("argument for --test-timeout must not be 0".into()),
+        Ok(secs) => Ok(Some(Duration::from_secs(secs))),
+        Err(e) => {
+            Err(format!("argument for --test-timeout must be a number of seconds (error: {})", e))
+        }
+    }
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n) => n
+            .parse::<usize>()
+            .map_err(|e| format!("argument for --retries must be a number (error: {})", e)),
+        None => Ok(0),
+    }
+}
+
+fn get_rerun_failed(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<RerunFailed>> {
+    let state_file = match unstable_optopt!(matches, allow_unstable, "rerun-failed") {
+        Some(path) => PathBuf::from(path),
+        None => return Ok(None),
+    };
+    let previously_failed = rerun::read_failed_tests(&state_file)
+        .map_err(|e| format!("failed to read {}: {}", state_file.display(), e))?;
+    Ok(Some(RerunFailed { state_file, previously_failed }))
+}
+
+fn get_save_baseline(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<PathBuf>> {
+    let name = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    Ok(name.map(|name| baseline::baseline_path(&name)))
+}
+
+fn get_baseline(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<CompareBaseline>> {
+    const DEFAULT_NOISE_THRESHOLD: f64 = 2.0;
+
+    let noise_threshold = match unstable_optopt!(matches, allow_unstable, "noise-threshold") {
+        Some(pct) => match pct.parse::<f64>() {
+            Ok(pct) if pct >= 0.0 => pct,
+            _ => {
+                return Err(format!(
+                    "argument for --noise-threshold must be a non-negative percentage \
+                     (got {})",
+                    pct
+                ));
+            }
+        },
+        None => DEFAULT_NOISE_THRESHOLD,
+    };
+    let path = match unstable_optopt!(matches, allow_unstable, "baseline") {
+        Some(name) => baseline::baseline_path(&name),
+        None => return Ok(None),
+    };
+    let benches = baseline::read_baseline(&path)
+        .map_err(|e| format!("failed to read baseline {}: {}", path.display(), e))?;
+    Ok(Some(CompareBaseline { benches, noise_threshold: noise_threshold / 100.0 }))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -11,7 +11,7 @@
     event::{CompletedTest, TestEvent},
     filter_tests,
     formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
-    helpers::{concurrency::get_concurrency, metrics::MetricMap},
+    helpers::{concurrency::get_concurrency, metrics::MetricMap, rerun},
     options::{Options, OutputFormat},
     run_tests, term,
     test_result::TestResult,
@@ -46,6 +46,7 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
@@ -69,6 +70,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             filtered_out: 0,
             measured: 0,
@@ -127,6 +129,11 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut => "timed out".to_owned(),
+                    TestResult::TrCrashed(reason) => format!("crashed: {reason}"),
+                    TestResult::TrFlaky(failed) => {
+                        format!("ok (flaky, failed {} of {} attempts)", failed, failed + 1)
+                    }
                 },
                 name,
             )
@@ -202,6 +209,11 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.passed += 1;
             st.not_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.passed += 1;
+            st.flaky += 1;
+            st.not_failures.push((test, stdout));
+        }
         TestResult::TrIgnored => st.ignored += 1,
         TestResult::TrBench(bs) => {
             st.metrics.insert_metric(
@@ -225,6 +237,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut | TestResult::TrCrashed(_) => {
+            st.failed += 1;
+            st.failures.push((test, stdout));
+        }
     }
 }
 
@@ -255,6 +271,18 @@ fn on_test_event(
             out.write_result(test, result, exec_time.as_ref(), &*stdout, st)?;
             handle_test_result(st, completed_test);
         }
+        TestEvent::TeRetry(completed_test, attempt) => {
+            let test = &completed_test.desc;
+            let result = &completed_test.result;
+            let exec_time = &completed_test.exec_time;
+            let stdout = &completed_test.stdout;
+
+            st.write_log(|| format!("retrying {} after failed attempt {}\n", test.name, attempt))?;
+            out.write_retry(test, result, exec_time.as_ref(), &*stdout, attempt)?;
+        }
+        TestEvent::TeRerunFailed(previously_failed) => {
+            out.write_rerun_failed(previously_failed)?;
+        }
     }
 
     Ok(())
@@ -303,6 +331,17 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
 
     assert!(st.current_test_count() == st.total);
 
+    if let Some(ref rerun) = opts.rerun_failed {
+        let failed = st.failures.iter().chain(&st.time_failures);
+        let ran = failed.clone().chain(&st.not_failures);
+        rerun::write_failed_tests(
+            &rerun.state_file,
+            &rerun.previously_failed,
+            ran.map(|(desc, _)| desc.name.as_slice()),
+            failed.map(|(desc, _)| desc.name.as_slice()),
+        )?;
+    }
+
     out.write_run_finish(&st)
 }
 
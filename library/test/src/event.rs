@@ -33,4 +33,8 @@ pub enum TestEvent {
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
+    /// A failed attempt of a test that is run again, with the number of the attempt.
+    TeRetry(CompletedTest, usize),
+    /// Only the given number of tests that failed in the previous run are run.
+    TeRerunFailed(usize),
 }
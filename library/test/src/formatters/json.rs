@@ -3,6 +3,7 @@
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    helpers::exit_code::TerminationReason,
     test_result::TestResult,
     time,
     types::TestDesc,
@@ -111,6 +112,45 @@ fn write_result(
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                stdout,
+                Some(r#""reason": "timed out""#),
+            ),
+
+            TestResult::TrFlaky(failed) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""failed_attempts": {}"#, failed)),
+            ),
+
+            TestResult::TrCrashed(reason) => {
+                let code = match reason {
+                    TerminationReason::Signal(signal) => format!(r#""signal": {signal}"#),
+                    TerminationReason::Exception(code) => {
+                        format!(r#""exception_code": "{code:#010x}""#)
+                    }
+                };
+                self.write_event(
+                    "test",
+                    desc.name.as_slice(),
+                    "failed",
+                    exec_time,
+                    stdout,
+                    Some(&*format!(
+                        r#""reason": "crashed", "message": "{}", {}"#,
+                        EscapedString(format!("terminated by {reason}")),
+                        code
+                    )),
+                )
+            }
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -147,15 +187,31 @@ fn write_result(
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
 
+                let baseline = match bs.comparison {
+                    Some(ref comparison) => format!(
+                        ", \"baseline\": {{ \
+                         \"median\": {}, \
+                         \"change\": {}, \
+                         \"p_value\": {}, \
+                         \"verdict\": \"{}\" }}",
+                        comparison.baseline_median as usize,
+                        comparison.change,
+                        comparison.p_value,
+                        comparison.verdict
+                    ),
+                    None => String::new(),
+                };
+
                 let line = format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{}\", \
                      \"median\": {}, \
-                     \"deviation\": {}{} }}",
+                     \"deviation\": {}{}{} }}",
                     EscapedString(desc.name.as_slice()),
                     median,
                     deviation,
-                    mbps
+                    mbps,
+                    baseline
                 );
 
                 self.writeln_message(&*line)
@@ -170,6 +226,31 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()> {
+        let stdout = if !stdout.is_empty() { Some(String::from_utf8_lossy(stdout)) } else { None };
+        let extra = match *result {
+            TestResult::TrFailedMsg(ref m) => {
+                format!(r#""attempt": {}, "message": "{}""#, attempt, EscapedString(m))
+            }
+            _ => format!(r#""attempt": {}"#, attempt),
+        };
+        self.write_event("test", desc.name.as_slice(), "retry", exec_time, stdout, Some(&*extra))
+    }
+
+    fn write_rerun_failed(&mut self, previously_failed: usize) -> io::Result<()> {
+        self.writeln_message(&*format!(
+            r#"{{ "type": "suite", "event": "rerun_failed", "previously_failed": {} }}"#,
+            previously_failed
+        ))
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message(&*format!(
             "{{ \"type\": \"suite\", \
@@ -187,6 +268,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             state.filtered_out,
         ))?;
 
+        if state.flaky > 0 {
+            self.write_message(&*format!(", \"flaky\": {}", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!(", \"exec_time\": {}", exec_time.0.as_secs_f64());
             self.write_message(&time_str)?;
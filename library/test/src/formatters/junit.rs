@@ -61,6 +61,24 @@ fn write_result(
         self.results.push((desc.clone(), result.clone(), duration));
         Ok(())
     }
+
+    fn write_retry(
+        &mut self,
+        _desc: &TestDesc,
+        _result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        _stdout: &[u8],
+        _attempt: usize,
+    ) -> io::Result<()> {
+        // Only the final result of a test is reported.
+        Ok(())
+    }
+
+    fn write_rerun_failed(&mut self, _previously_failed: usize) -> io::Result<()> {
+        // We do not output anything when only previously failed tests are run.
+        Ok(())
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message("<testsuites>")?;
 
@@ -113,6 +131,32 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                     self.write_message("</testcase>")?;
                 }
 
+                TestResult::TrTimedOut => {
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_message("<failure message=\"timed out\" type=\"timeout\"/>")?;
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrCrashed(reason) => {
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_message(&*format!(
+                        "<failure message=\"terminated by {reason}\" type=\"crash\"/>"
+                    ))?;
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrBench(ref b) => {
                     self.write_message(&*format!(
                         "<testcase classname=\"benchmark::{}\" \
@@ -121,6 +165,22 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                     ))?;
                 }
 
+                TestResult::TrFlaky(failed) => {
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_message(&*format!(
+                        "<flakyFailure message=\"failed {} of {} attempts\" type=\"flaky\"/>",
+                        failed,
+                        failed + 1
+                    ))?;
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrOk => {
                     self.write_message(&*format!(
                         "<testcase classname=\"{}\" \
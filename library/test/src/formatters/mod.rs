@@ -29,6 +29,15 @@ fn write_result(
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()>;
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()>;
+    fn write_rerun_failed(&mut self, previously_failed: usize) -> io::Result<()>;
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool>;
 }
 
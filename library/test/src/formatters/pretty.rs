@@ -57,6 +57,23 @@ pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("TIMED OUT", term::color::RED)
+    }
+
+    pub fn write_crashed(&mut self) -> io::Result<()> {
+        self.write_short_result("CRASHED", term::color::RED)
+    }
+
+    pub fn write_flaky(&mut self, failed: usize) -> io::Result<()> {
+        let result = format!("ok (flaky, failed {} of {} attempts)", failed, failed + 1);
+        self.write_short_result(&result, term::color::YELLOW)
+    }
+
+    pub fn write_retrying(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (retrying)", term::color::RED)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -229,6 +246,9 @@ fn write_result(
                 self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut => self.write_timed_out()?,
+            TestResult::TrCrashed(_) => self.write_crashed()?,
+            TestResult::TrFlaky(failed) => self.write_flaky(failed)?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -243,6 +263,30 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        _: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        _: &[u8],
+        _: usize,
+    ) -> io::Result<()> {
+        if self.is_multithreaded {
+            self.write_test_name(desc)?;
+        }
+
+        self.write_retrying()?;
+        self.write_time(desc, exec_time)?;
+        self.write_plain("\n")
+    }
+
+    fn write_rerun_failed(&mut self, previously_failed: usize) -> io::Result<()> {
+        let noun = if previously_failed != 1 { "tests" } else { "test" };
+        self.write_plain(&format!(
+            "only running the {previously_failed} {noun} that failed in the previous run\n"
+        ))
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
@@ -267,9 +311,11 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!(" ({} flaky)", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
-            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+            ". {} passed{}; {} failed; {} ignored; {} measured; {} filtered out",
+            state.passed, flaky, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
         self.write_plain(&s)?;
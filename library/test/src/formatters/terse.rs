@@ -50,6 +50,14 @@ pub fn write_failed(&mut self) -> io::Result<()> {
         self.write_short_result("F", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("T", term::color::RED)
+    }
+
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("f", term::color::YELLOW)
+    }
+
     pub fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("i", term::color::YELLOW)
     }
@@ -199,9 +207,12 @@ fn write_result(
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
-                self.write_failed()
-            }
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrCrashed(_) => self.write_failed(),
+            TestResult::TrTimedOut => self.write_timed_out(),
+            TestResult::TrFlaky(_) => self.write_flaky(),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
@@ -221,6 +232,25 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(
+        &mut self,
+        _: &TestDesc,
+        _: &TestResult,
+        _: Option<&time::TestExecTime>,
+        _: &[u8],
+        _: usize,
+    ) -> io::Result<()> {
+        // Only the final result of a test is shown.
+        Ok(())
+    }
+
+    fn write_rerun_failed(&mut self, previously_failed: usize) -> io::Result<()> {
+        let noun = if previously_failed != 1 { "tests" } else { "test" };
+        self.write_plain(&format!(
+            "only running the {previously_failed} {noun} that failed in the previous run\n"
+        ))
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_outputs(state)?;
@@ -239,9 +269,11 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!(" ({} flaky)", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
-            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+            ". {} passed{}; {} failed; {} ignored; {} measured; {} filtered out",
+            state.passed, flaky, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
         self.write_plain(&s)?;
@@ -0,0 +1,63 @@
+//! Reading and writing the benchmark baselines of `--save-baseline` and `--baseline`.
+//!
+//! Baselines are stored in the directory named by `RUST_BENCH_BASELINE_DIR`, or in
+//! `target/bench-baselines` if it isn't set, in a file with the name of the baseline. Every line
+//! of the file holds the name of a benchmark followed by its number of samples, median, mean and
+//! variance, separated by tabs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::bench::BenchBaseline;
+
+/// Returns the path of the file that stores the baseline called `name`.
+pub fn baseline_path(name: &str) -> PathBuf {
+    let dir = match env::var_os("RUST_BENCH_BASELINE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new("target").join("bench-baselines"),
+    };
+    dir.join(name)
+}
+
+pub fn read_baseline(path: &Path) -> io::Result<HashMap<String, BenchBaseline>> {
+    let contents = fs::read_to_string(path)?;
+    let mut baseline = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid entry on line {}", i + 1))
+        };
+        // Parse from the end, so that the name may contain tabs.
+        let mut fields = line.rsplitn(5, '\t');
+        let mut next_f64 = || -> io::Result<f64> {
+            fields.next().and_then(|field| field.parse().ok()).ok_or_else(invalid)
+        };
+        let var = next_f64()?;
+        let mean = next_f64()?;
+        let median = next_f64()?;
+        let samples = fields.next().and_then(|field| field.parse().ok()).ok_or_else(invalid)?;
+        let name = fields.next().ok_or_else(invalid)?;
+        baseline.insert(name.to_owned(), BenchBaseline { samples, median, mean, var });
+    }
+    Ok(baseline)
+}
+
+/// Replaces the baseline in `path` with the given benchmarks.
+pub fn write_baseline(path: &Path, baseline: &BTreeMap<String, BenchBaseline>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut contents = String::new();
+    for (name, bench) in baseline {
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            name, bench.samples, bench.median, bench.mean, bench.var
+        ));
+    }
+    fs::write(path, contents)
+}
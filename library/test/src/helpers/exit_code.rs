@@ -1,7 +1,32 @@
 //! Helper module to detect subprocess exit code.
 
+use std::fmt;
 use std::process::ExitStatus;
 
+/// Why a test process terminated without exiting on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// Terminated by a signal (Unix).
+    Signal(i32),
+    /// Terminated by an unhandled exception, identified by its `NTSTATUS` code (Windows).
+    Exception(u32),
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TerminationReason::Signal(signal) => match signal_name(signal) {
+                Some(name) => write!(f, "signal {signal} ({name})"),
+                None => write!(f, "signal {signal}"),
+            },
+            TerminationReason::Exception(code) => match exception_name(code) {
+                Some(name) => write!(f, "exception {code:#010x} ({name})"),
+                None => write!(f, "exception {code:#010x}"),
+            },
+        }
+    }
+}
+
 #[cfg(not(unix))]
 pub fn get_exit_code(status: ExitStatus) -> Result<i32, String> {
     status.code().ok_or_else(|| "received no exit code from child process".into())
@@ -18,3 +43,71 @@ pub fn get_exit_code(status: ExitStatus) -> Result<i32, String> {
         },
     }
 }
+
+/// Returns why the process terminated if it did not exit on its own, i.e. it crashed or was
+/// killed.
+#[cfg(unix)]
+pub fn get_termination_reason(status: ExitStatus) -> Option<TerminationReason> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().map(TerminationReason::Signal)
+}
+
+/// Returns why the process terminated if it did not exit on its own, i.e. it crashed or was
+/// killed.
+#[cfg(windows)]
+pub fn get_termination_reason(status: ExitStatus) -> Option<TerminationReason> {
+    // A process that is terminated by an unhandled exception exits with the exception code,
+    // which is an `NTSTATUS` with the error severity.
+    let code = status.code()? as u32;
+    (code & 0xf000_0000 == 0xc000_0000).then(|| TerminationReason::Exception(code))
+}
+
+/// Returns why the process terminated if it did not exit on its own, i.e. it crashed or was
+/// killed.
+#[cfg(not(any(unix, windows)))]
+pub fn get_termination_reason(_status: ExitStatus) -> Option<TerminationReason> {
+    None
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGSYS => "SIGSYS",
+        _ => return None,
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_name(_signal: i32) -> Option<&'static str> {
+    None
+}
+
+fn exception_name(code: u32) -> Option<&'static str> {
+    Some(match code {
+        0xc0000005 => "STATUS_ACCESS_VIOLATION",
+        0xc0000006 => "STATUS_IN_PAGE_ERROR",
+        0xc000001d => "STATUS_ILLEGAL_INSTRUCTION",
+        0xc000008c => "STATUS_ARRAY_BOUNDS_EXCEEDED",
+        0xc000008e => "STATUS_FLOAT_DIVIDE_BY_ZERO",
+        0xc0000094 => "STATUS_INTEGER_DIVIDE_BY_ZERO",
+        0xc0000095 => "STATUS_INTEGER_OVERFLOW",
+        0xc0000096 => "STATUS_PRIVILEGED_INSTRUCTION",
+        0xc00000fd => "STATUS_STACK_OVERFLOW",
+        0xc0000374 => "STATUS_HEAP_CORRUPTION",
+        0xc0000409 => "STATUS_STACK_BUFFER_OVERRUN",
+        _ => return None,
+    })
+}
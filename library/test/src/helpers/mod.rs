@@ -1,8 +1,11 @@
 //! Module with common helpers not directly related to tests
 //! but used in `libtest`.
 
+pub mod baseline;
 pub mod concurrency;
 pub mod exit_code;
 pub mod isatty;
 pub mod metrics;
+pub mod rerun;
+pub mod shard;
 pub mod shuffle;
@@ -0,0 +1,49 @@
+//! Reading and writing the state file of `--rerun-failed`.
+//!
+//! The state file lists the names of the tests that failed, one per line.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Reads the names of the tests that failed in the previous run. A missing state file means
+/// that there was no previous run.
+pub fn read_failed_tests(path: &Path) -> io::Result<HashSet<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Updates the state file after a run. It records the tests that failed in this run, and keeps
+/// the previously failed tests that were not run this time, for example because they were
+/// filtered out or belong to another shard.
+pub fn write_failed_tests<'a>(
+    path: &Path,
+    previously_failed: &'a HashSet<String>,
+    ran: impl Iterator<Item = &'a str>,
+    failed: impl Iterator<Item = &'a str>,
+) -> io::Result<()> {
+    let ran: HashSet<&str> = ran.collect();
+    let mut names: Vec<&str> = previously_failed
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !ran.contains(name))
+        .chain(failed)
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let mut contents = String::new();
+    for name in names {
+        contents.push_str(name);
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+}
@@ -0,0 +1,20 @@
+//! Assignment of tests to shards for `--shard-index` and `--shard-count`.
+
+use crate::options::TestShard;
+
+/// Whether the test called `name` belongs to `shard`.
+///
+/// The assignment only depends on the name of the test, so a test stays in the same shard no
+/// matter which other tests exist, how they are filtered or in which order they are run.
+pub fn is_in_shard(shard: TestShard, name: &str) -> bool {
+    stable_hash(name) % shard.count as u64 == shard.index as u64
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is specified and does not change between
+/// Rust releases or platforms, so every process running a shard agrees on the assignment.
+fn stable_hash(name: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    name.bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
@@ -26,7 +26,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic, TestShard};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -40,7 +40,7 @@ pub mod test {
         cli::{parse_opts, TestOpts},
         filter_tests,
         helpers::metrics::{Metric, MetricMap},
-        options::{Concurrent, Options, RunIgnored, RunStrategy, ShouldPanic},
+        options::{Concurrent, Options, RunIgnored, RunStrategy, ShouldPanic, TestShard},
         run_test, test_main, test_main_static,
         test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk},
         time::{TestExecTime, TestTimeOptions},
@@ -54,10 +54,10 @@ pub mod test {
 use std::{
     collections::VecDeque,
     env, io,
-    io::prelude::Write,
+    io::prelude::{Read, Write},
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
-    sync::mpsc::{channel, Sender},
+    sync::mpsc::{channel, Receiver, Sender},
     sync::{Arc, Mutex},
     thread,
     time::{Duration, Instant},
@@ -79,9 +79,12 @@ pub mod test {
 #[cfg(test)]
 mod tests;
 
+use bench::BenchBaseline;
 use event::{CompletedTest, TestEvent};
+use helpers::baseline::{read_baseline, write_baseline};
 use helpers::concurrency::get_concurrency;
-use helpers::exit_code::get_exit_code;
+use helpers::exit_code::{get_exit_code, get_termination_reason};
+use helpers::shard::is_in_shard;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::{Concurrent, RunStrategy};
 use test_result::*;
@@ -95,6 +98,23 @@ pub mod test {
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
 pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Options>) {
+    // If we're being run in SpawnedSecondary mode, run the test here. run_test
+    // will then exit the process.
+    if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
+        env::remove_var(SECONDARY_TEST_INVOKER_VAR);
+        let test = tests
+            .into_iter()
+            .find(|test| test.desc.name.as_slice() == name)
+            .unwrap_or_else(|| panic!("couldn't find a test with the provided name '{name}'"));
+        let TestDescAndFn { desc, testfn } = test;
+        let testfn = match testfn {
+            StaticTestFn(f) => f,
+            _ => panic!("only static tests are supported"),
+        };
+        let panic_abort = options.map_or(false, |options| options.panic_abort);
+        run_test_in_spawned_subprocess(desc, Box::new(testfn), panic_abort);
+    }
+
     let mut opts = match cli::parse_opts(args) {
         Some(Ok(o)) => o,
         Some(Err(msg)) => {
@@ -143,24 +163,6 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
-    // If we're being run in SpawnedSecondary mode, run the test here. run_test
-    // will then exit the process.
-    if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
-        env::remove_var(SECONDARY_TEST_INVOKER_VAR);
-        let test = tests
-            .iter()
-            .filter(|test| test.desc.name.as_slice() == name)
-            .map(make_owned_test)
-            .next()
-            .unwrap_or_else(|| panic!("couldn't find a test with the provided name '{name}'"));
-        let TestDescAndFn { desc, testfn } = test;
-        let testfn = match testfn {
-            StaticTestFn(f) => f,
-            _ => panic!("only static tests are supported"),
-        };
-        run_test_in_spawned_subprocess(desc, Box::new(testfn));
-    }
-
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
     test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
@@ -199,7 +201,7 @@ pub fn run_tests<F>(
 where
     F: FnMut(TestEvent) -> io::Result<()>,
 {
-    use std::collections::{self, HashMap};
+    use std::collections::{self, BTreeMap, HashMap};
     use std::hash::BuildHasherDefault;
     use std::sync::mpsc::RecvTimeoutError;
 
@@ -210,6 +212,7 @@ struct RunningTest {
     // Use a deterministic hasher
     type TestMap =
         HashMap<TestId, RunningTest, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
+    type RetryMap = HashMap<TestId, fn(), BuildHasherDefault<collections::hash_map::DefaultHasher>>;
 
     struct TimeoutEntry {
         id: TestId,
@@ -244,6 +247,13 @@ struct TimeoutEntry {
     let event = TestEvent::TeFiltered(filtered_descs, shuffle_seed);
     notify_about_test_event(event)?;
 
+    if let Some(ref rerun) = opts.rerun_failed {
+        if !rerun.previously_failed.is_empty() {
+            let event = TestEvent::TeRerunFailed(filtered_tests.len());
+            notify_about_test_event(event)?;
+        }
+    }
+
     let (filtered_tests, filtered_benchs): (Vec<_>, _) = filtered_tests
         .into_iter()
         .enumerate()
@@ -260,8 +270,23 @@ struct TimeoutEntry {
     }
     let mut pending = 0;
 
+    // Static tests can be run again for `--retries`, so remember their functions.
+    let retryable: RetryMap = if opts.retries > 0 {
+        remaining
+            .iter()
+            .filter_map(|(id, test)| match test.testfn {
+                StaticTestFn(f) => Some((*id, f)),
+                _ => None,
+            })
+            .collect()
+    } else {
+        HashMap::default()
+    };
+    let mut failed_attempts: HashMap<TestId, usize> = HashMap::new();
+
     let (tx, rx) = channel::<CompletedTest>();
-    let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
+    let spawn = opts.options.panic_abort || opts.isolate || opts.test_timeout.is_some();
+    let run_strategy = if spawn && !opts.force_run_in_process {
         RunStrategy::SpawnPrimary
     } else {
         RunStrategy::InProcess
@@ -288,6 +313,29 @@ fn get_timed_out_tests(
         timed_out
     }
 
+    // Returns the test to run again if it failed and has retries left, and marks a test that
+    // passes after failing as flaky.
+    fn check_retry(
+        opts: &TestOpts,
+        retryable: &RetryMap,
+        failed_attempts: &mut HashMap<TestId, usize>,
+        completed_test: &mut CompletedTest,
+    ) -> Option<TestDescAndFn> {
+        let failed = failed_attempts.get(&completed_test.id).copied().unwrap_or(0);
+        if completed_test.result.is_failure() && failed < opts.retries {
+            let f = *retryable.get(&completed_test.id)?;
+            failed_attempts.insert(completed_test.id, failed + 1);
+            return Some(TestDescAndFn {
+                desc: completed_test.desc.clone(),
+                testfn: StaticTestFn(f),
+            });
+        }
+        if failed > 0 && completed_test.result == TrOk {
+            completed_test.result = TrFlaky(failed);
+        }
+        None
+    }
+
     fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         timeout_queue.front().map(|&TimeoutEntry { timeout: next_timeout, .. }| {
             let now = Instant::now();
@@ -303,7 +351,16 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let join_handle =
                 run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone(), Concurrent::No);
             assert!(join_handle.is_none());
-            let completed_test = rx.recv().unwrap();
+            let mut completed_test = rx.recv().unwrap();
+
+            let retry = check_retry(opts, &retryable, &mut failed_attempts, &mut completed_test);
+            if let Some(test) = retry {
+                let id = completed_test.id;
+                let event = TestEvent::TeRetry(completed_test, failed_attempts[&id]);
+                notify_about_test_event(event)?;
+                remaining.push((id, test));
+                continue;
+            }
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
@@ -366,23 +423,59 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
+            pending -= 1;
+
+            let retry = check_retry(opts, &retryable, &mut failed_attempts, &mut completed_test);
+            if let Some(test) = retry {
+                let id = completed_test.id;
+                let event = TestEvent::TeRetry(completed_test, failed_attempts[&id]);
+                notify_about_test_event(event)?;
+                remaining.push((id, test));
+                continue;
+            }
+
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
-            pending -= 1;
         }
     }
 
     if opts.bench_benchmarks {
+        let mut results = BTreeMap::new();
+
         // All benchmarks run at the end, in serial.
         for (id, b) in filtered_benchs {
             let event = TestEvent::TeWait(b.desc.clone());
             notify_about_test_event(event)?;
             run_test(opts, false, id, b, run_strategy, tx.clone(), Concurrent::No);
-            let completed_test = rx.recv().unwrap();
+            let mut completed_test = rx.recv().unwrap();
+
+            if let TestResult::TrBench(ref mut bs) = completed_test.result {
+                let name = completed_test.desc.name.as_slice();
+                let result = BenchBaseline::new(&bs.ns_iter_summ);
+                if let Some(ref baseline) = opts.baseline {
+                    bs.comparison = baseline
+                        .benches
+                        .get(name)
+                        .map(|base| base.compare(&result, baseline.noise_threshold));
+                }
+                results.insert(name.to_owned(), result);
+            }
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
         }
+
+        if let Some(ref path) = opts.save_baseline {
+            // Only replace the benchmarks that were run, so that a filtered run updates a part
+            // of the baseline.
+            let mut saved = match read_baseline(path) {
+                Ok(saved) => saved.into_iter().collect(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => return Err(e),
+            };
+            saved.extend(results);
+            write_baseline(path, &saved)?;
+        }
     }
     Ok(())
 }
@@ -406,6 +499,13 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     // Skip tests that match any of the skip filters
     filtered.retain(|test| !opts.skip.iter().any(|sf| matches_filter(test, sf)));
 
+    // Only rerun the tests that failed in the previous run
+    if let Some(ref rerun) = opts.rerun_failed {
+        if !rerun.previously_failed.is_empty() {
+            filtered.retain(|test| rerun.previously_failed.contains(test.desc.name.as_slice()));
+        }
+    }
+
     // Excludes #[should_panic] tests
     if opts.exclude_should_panic {
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
@@ -423,6 +523,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Keep only the tests of the selected shard
+    if let Some(shard) = opts.shard {
+        filtered.retain(|test| is_in_shard(shard, test.desc.name.as_slice()));
+    }
+
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
@@ -475,6 +580,7 @@ struct TestRunOpts {
         pub nocapture: bool,
         pub concurrency: Concurrent,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
     }
 
     fn run_test_inner(
@@ -504,6 +610,7 @@ fn run_test_inner(
                 opts.time.is_some(),
                 monitor_ch,
                 opts.time,
+                opts.timeout,
             ),
         };
 
@@ -531,8 +638,13 @@ fn run_test_inner(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, concurrency, time: opts.time_options };
+    let test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        concurrency,
+        time: opts.time_options,
+        timeout: opts.test_timeout,
+    };
 
     match testfn {
         DynBenchFn(benchfn) => {
@@ -546,8 +658,11 @@ fn run_test_inner(
             None
         }
         DynTestFn(f) => {
-            match strategy {
-                RunStrategy::InProcess => (),
+            let strategy = match strategy {
+                RunStrategy::InProcess => strategy,
+                // Without panic=abort, tests are only spawned for `--isolate` and
+                // `--test-timeout`, so dynamic tests can keep running in process.
+                RunStrategy::SpawnPrimary if !opts.options.panic_abort => RunStrategy::InProcess,
                 _ => panic!("Cannot run dynamic test fn out-of-process"),
             };
             run_test_inner(
@@ -555,7 +670,7 @@ fn run_test_inner(
                 desc,
                 monitor_ch,
                 Box::new(move || __rust_begin_short_backtrace(f)),
-                test_run_opts,
+                TestRunOpts { strategy, ..test_run_opts },
             )
         }
         StaticTestFn(f) => run_test_inner(
@@ -618,6 +733,7 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -628,10 +744,13 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let (status, stdout, stderr) = match output_with_timeout(&mut command, timeout) {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -643,11 +762,24 @@ fn spawn_test_subprocess(
             TestExecTime(duration)
         });
 
-        let std::process::Output { stdout, stderr, status } = output;
         let mut test_output = stdout;
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        let status = match status {
+            Some(status) => status,
+            None => {
+                let secs = timeout.map_or(0, |timeout| timeout.as_secs());
+                write!(&mut test_output, "note: test did not finish within {secs}s and was killed")
+                    .unwrap();
+                return (TrTimedOut, test_output, exec_time);
+            }
+        };
+        if let Some(reason) = get_termination_reason(status) {
+            write!(&mut test_output, "note: test process terminated by {reason}").unwrap();
+            return (TrCrashed(reason), test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -666,7 +798,98 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
-fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Send>) -> ! {
+/// Runs `command` to completion like `Command::output`, but kills it once it has been running
+/// for longer than `timeout`. Returns the exit status, which is `None` if the command was killed,
+/// and the captured standard output and error.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> io::Result<(Option<process::ExitStatus>, Vec<u8>, Vec<u8>)> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            let output = command.output()?;
+            return Ok((Some(output.status), output.stdout, output.stderr));
+        }
+    };
+
+    // How long to wait for the end of the output once the test process is gone. Processes the
+    // test started may have inherited the pipes and keep them open for much longer.
+    const OUTPUT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+    // The pipes have to be drained while waiting, otherwise a test that writes more than the
+    // pipe buffer would block and be reported as timed out. The reader threads are detached if
+    // the pipes aren't closed in time, keeping whatever was read so far.
+    struct BackgroundReader {
+        buf: Arc<Mutex<Vec<u8>>>,
+        done: Receiver<()>,
+    }
+
+    fn read_in_background<R: Read + Send + 'static>(mut pipe: R) -> BackgroundReader {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (done_tx, done) = channel();
+        let thread_buf = buf.clone();
+        thread::spawn(move || {
+            // `done_tx` is dropped once the pipe is closed, which disconnects `done`.
+            let _done_tx = done_tx;
+            let mut chunk = [0; 4096];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => thread_buf.lock().unwrap().extend_from_slice(&chunk[..n]),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        BackgroundReader { buf, done }
+    }
+
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    // `Child` can't wait with a timeout, so poll it instead.
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            // The child may exit on its own in the meantime, in which case killing it fails.
+            let _ = child.kill();
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let output_deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+    let collect = |reader: Option<BackgroundReader>| {
+        reader.map_or_else(Vec::new, |reader| {
+            let wait = output_deadline.saturating_duration_since(Instant::now());
+            let _ = reader.done.recv_timeout(wait);
+            let buf = reader.buf.lock().unwrap_or_else(|e| e.into_inner());
+            buf.clone()
+        })
+    };
+    Ok((status, collect(stdout), collect(stderr)))
+}
+
+fn run_test_in_spawned_subprocess(
+    desc: TestDesc,
+    testfn: Box<dyn FnOnce() + Send>,
+    panic_abort: bool,
+) -> ! {
+    // With panic=unwind the test may catch its own panics, so only how `testfn` finishes counts.
+    if !panic_abort {
+        let test_result = match catch_unwind(AssertUnwindSafe(testfn)) {
+            Ok(()) => calc_result(&desc, Ok(()), &None, &None),
+            Err(e) => calc_result(&desc, Err(e.as_ref()), &None, &None),
+        };
+        exit_with_test_result(test_result);
+    }
+
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
         let test_result = match panic_info {
@@ -674,21 +897,11 @@ fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Sen
             None => calc_result(&desc, Ok(()), &None, &None),
         };
 
-        // We don't support serializing TrFailedMsg, so just
-        // print the message out to stderr.
-        if let TrFailedMsg(msg) = &test_result {
-            eprintln!("{msg}");
-        }
-
         if let Some(info) = panic_info {
             builtin_panic_hook(info);
         }
 
-        if let TrOk = test_result {
-            process::exit(test_result::TR_OK);
-        } else {
-            process::exit(test_result::TR_FAILED);
-        }
+        exit_with_test_result(test_result);
     });
     let record_result2 = record_result.clone();
     panic::set_hook(Box::new(move |info| record_result2(Some(&info))));
@@ -696,3 +909,18 @@ fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Sen
     record_result(None);
     unreachable!("panic=abort callback should have exited the process")
 }
+
+/// Reports the result of a test run by `run_test_in_spawned_subprocess` to the parent process.
+fn exit_with_test_result(test_result: TestResult) -> ! {
+    // We don't support serializing TrFailedMsg, so just
+    // print the message out to stderr.
+    if let TrFailedMsg(msg) = &test_result {
+        eprintln!("{msg}");
+    }
+
+    if let TrOk = test_result {
+        process::exit(test_result::TR_OK);
+    } else {
+        process::exit(test_result::TR_FAILED);
+    }
+}
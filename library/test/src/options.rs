@@ -1,5 +1,10 @@
 //! Enums denoting options for test execution.
 
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::bench::BenchBaseline;
+
 /// Whether to execute tests concurrently or not
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Concurrent {
@@ -52,6 +57,37 @@ pub enum RunIgnored {
     Only,
 }
 
+/// Selects the part of the tests that is run by one of several processes that split the test
+/// suite between them (`--shard-index` and `--shard-count`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Index of the selected shard, less than `count`.
+    pub index: usize,
+    /// Number of shards the tests are split into.
+    pub count: usize,
+}
+
+/// State of `--rerun-failed`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RerunFailed {
+    /// The file that records the tests that failed in the previous run, and is updated with the
+    /// tests that fail in this one.
+    pub state_file: PathBuf,
+    /// The tests that failed in the previous run, which are the only ones to be run. Empty if
+    /// there was no previous run or nothing failed in it, in which case all tests are run.
+    pub previously_failed: HashSet<String>,
+}
+
+/// State of `--baseline`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompareBaseline {
+    /// The benchmarks of the baseline, by name.
+    pub benches: HashMap<String, BenchBaseline>,
+    /// Largest change of the median, as a fraction of the baseline median, that is considered
+    /// noise even if it is statistically significant.
+    pub noise_threshold: f64,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
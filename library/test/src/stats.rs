@@ -300,3 +300,104 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
         }
     }
 }
+
+/// Two-sided p-value of Welch's t-test for the null hypothesis that two samples, given by their
+/// means, variances and sizes, come from populations with the same mean. Returns 1 when the test
+/// can't be carried out, because a sample has fewer than two values or both have no variance.
+///
+/// See: <https://en.wikipedia.org/wiki/Welch%27s_t-test>
+pub fn welch_t_test(mean1: f64, var1: f64, n1: usize, mean2: f64, var2: f64, n2: usize) -> f64 {
+    if n1 < 2 || n2 < 2 {
+        return 1.0;
+    }
+    let (n1, n2) = (n1 as f64, n2 as f64);
+    let (se1, se2) = (var1 / n1, var2 / n2);
+    let se = se1 + se2;
+    if se <= 0.0 {
+        return 1.0;
+    }
+    let t = (mean1 - mean2) / se.sqrt();
+    let df = se * se / (se1 * se1 / (n1 - 1.0) + se2 * se2 / (n2 - 1.0));
+    // P(|T| > |t|) for Student's t-distribution with `df` degrees of freedom.
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Natural logarithm of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula.
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, &c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    // The continued fraction converges quickly only on one side of the mean, so use the symmetry
+    // `I_x(a, b) = 1 - I_(1-x)(b, a)` on the other.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - ln_front.exp() * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function, evaluated with the modified Lentz method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        // Even step.
+        let num = m * (b - m) * x / ((a + m2 - 1.0) * (a + m2));
+        d = 1.0 / clamp(1.0 + num * d);
+        c = clamp(1.0 + num / c);
+        h *= d * c;
+
+        // Odd step.
+        let num = -(a + m) * (a + b + m) * x / ((a + m2) * (a + m2 + 1.0));
+        d = 1.0 / clamp(1.0 + num * d);
+        c = clamp(1.0 + num / c);
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
@@ -571,6 +571,21 @@ fn test_sum_f64_between_ints_that_sum_to_0() {
     assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
 }
 
+#[test]
+fn test_welch_t_test() {
+    // With equal sizes and variances there are 2 * (6 - 1) degrees of freedom, and the standard
+    // error is 1, so the difference of the means is the t statistic. The expected values are the
+    // quantiles of Student's t-distribution with 10 degrees of freedom.
+    assert_approx_eq!(welch_t_test(2.228138852, 3.0, 6, 0.0, 3.0, 6), 0.05);
+    assert_approx_eq!(welch_t_test(0.0, 3.0, 6, 3.169272673, 3.0, 6), 0.01);
+    assert_approx_eq!(welch_t_test(1.0, 3.0, 6, 1.0, 3.0, 6), 1.0);
+    assert!(welch_t_test(100.0, 1.0, 50, 110.0, 1.0, 50) < 1e-12);
+
+    // Not enough samples or no variance at all.
+    assert_eq!(welch_t_test(1.0, 3.0, 1, 2.0, 3.0, 6), 1.0);
+    assert_eq!(welch_t_test(1.0, 0.0, 6, 2.0, 0.0, 6), 1.0);
+}
+
 #[bench]
 pub fn sum_three_items(b: &mut Bencher) {
     b.iter(|| {
@@ -1,6 +1,7 @@
 use std::any::Any;
 
 use super::bench::BenchSamples;
+use super::helpers::exit_code::TerminationReason;
 use super::options::ShouldPanic;
 use super::time;
 use super::types::TestDesc;
@@ -21,6 +22,19 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test was killed because it did not finish within `--test-timeout`.
+    TrTimedOut,
+    /// The process the test was spawned in crashed or was killed from the outside.
+    TrCrashed(TerminationReason),
+    /// The test passed after failing the given number of times, see `--retries`.
+    TrFlaky(usize),
+}
+
+impl TestResult {
+    /// Whether the test failed, including by running too long or crashing.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut | TrCrashed(_))
+    }
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
@@ -1,7 +1,8 @@
 use super::*;
 
+use crate::options::RerunFailed;
 use crate::{
-    bench::Bencher,
+    bench::{BenchBaseline, BenchVerdict, Bencher},
     console::OutputLocation,
     formatters::PrettyFormatter,
     options::OutputFormat,
@@ -19,6 +20,7 @@
         TestDesc,
         TestDescAndFn,
         TestOpts,
+        TestShard,
         TrIgnored,
         TrOk,
         // FIXME (introduced by #65251)
@@ -27,8 +29,12 @@
     },
     time::{TestTimeOptions, TimeThreshold},
 };
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
 use std::time::Duration;
+use std::{env, fs, process};
 
 impl TestOpts {
     fn new() -> TestOpts {
@@ -37,6 +43,7 @@ fn new() -> TestOpts {
             filters: vec![],
             filter_exact: false,
             force_run_in_process: false,
+            isolate: false,
             exclude_should_panic: false,
             run_ignored: RunIgnored::No,
             run_tests: false,
@@ -47,9 +54,15 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
+            rerun_failed: None,
+            save_baseline: None,
+            baseline: None,
             options: Options::new(),
         }
     }
@@ -441,6 +454,38 @@ fn parse_include_ignored_flag() {
     assert_eq!(opts.run_ignored, RunIgnored::Yes);
 }
 
+#[test]
+fn parse_test_timeout_option() {
+    let args = ["progname", "-Zunstable-options", "--test-timeout", "30"];
+    let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    for timeout in ["0", "-1", "1.5"] {
+        let args = ["progname", "-Zunstable-options", "--test-timeout", timeout];
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = ["progname", "-Zunstable-options", "--shard-index", "1", "--shard-count", "3"];
+    let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(TestShard { index: 1, count: 3 }));
+
+    for args in [
+        &["progname", "-Zunstable-options", "--shard-index", "1"][..],
+        &["progname", "-Zunstable-options", "--shard-index", "3", "--shard-count", "3"],
+        &["progname", "-Zunstable-options", "--shard-index", "0", "--shard-count", "0"],
+        &["progname", "--shard-index", "0", "--shard-count", "2"],
+    ] {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -642,6 +687,147 @@ pub fn sort_tests() {
     }
 }
 
+#[test]
+pub fn shards_partition_tests() {
+    fn names(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<String> {
+        filter_tests(opts, tests).into_iter().map(|test| test.desc.name.to_string()).collect()
+    }
+
+    for run_ignored in [RunIgnored::No, RunIgnored::Yes, RunIgnored::Only] {
+        let mut sample = sample_tests();
+        for test in sample.iter_mut().step_by(2) {
+            test.desc.ignore = true;
+        }
+        let tests = || {
+            sample
+                .iter()
+                .map(|test| TestDescAndFn {
+                    desc: test.desc.clone(),
+                    testfn: DynTestFn(Box::new(move || {})),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let opts = TestOpts { run_ignored, ..TestOpts::new() };
+        let all = names(&opts, tests());
+
+        for count in 1..=4 {
+            let mut union = Vec::new();
+            for index in 0..count {
+                let shard = Some(TestShard { index, count });
+                let opts = TestOpts { run_ignored, shard, ..TestOpts::new() };
+                union.extend(names(&opts, tests()));
+            }
+            // Every test is in exactly one shard.
+            union.sort();
+            assert_eq!(union, all);
+        }
+    }
+}
+
+#[test]
+pub fn rerun_failed_tests() {
+    let previously_failed = ["isize::test_pow", "sha1::test", "removed::test"];
+    let rerun_failed = RerunFailed {
+        state_file: PathBuf::new(),
+        previously_failed: previously_failed.iter().map(|name| name.to_string()).collect(),
+    };
+    let opts = TestOpts { rerun_failed: Some(rerun_failed), ..TestOpts::new() };
+    let filtered = filter_tests(&opts, sample_tests());
+    let names: Vec<_> = filtered.iter().map(|test| test.desc.name.to_string()).collect();
+    assert_eq!(names, ["isize::test_pow", "sha1::test"]);
+
+    // Without failures in the previous run, all tests are run.
+    let rerun_failed =
+        RerunFailed { state_file: PathBuf::new(), previously_failed: HashSet::new() };
+    let opts = TestOpts { rerun_failed: Some(rerun_failed), ..TestOpts::new() };
+    assert_eq!(filter_tests(&opts, sample_tests()).len(), sample_tests().len());
+}
+
+#[test]
+pub fn rerun_failed_state_file() {
+    let path = env::temp_dir().join(format!("libtest-rerun-failed-{}", process::id()));
+    assert!(helpers::rerun::read_failed_tests(&path).unwrap().is_empty());
+
+    let failed = ["b::test", "a::test", "b::test"];
+    let none = HashSet::new();
+    helpers::rerun::write_failed_tests(
+        &path,
+        &none,
+        failed.iter().copied(),
+        failed.iter().copied(),
+    )
+    .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a::test\nb::test\n");
+    let read = helpers::rerun::read_failed_tests(&path).unwrap();
+    assert_eq!(read, ["a::test", "b::test"].iter().map(|name| name.to_string()).collect());
+
+    // Tests that were not run this time stay recorded, so an empty selection keeps the file.
+    helpers::rerun::write_failed_tests(&path, &read, [].into_iter(), [].into_iter()).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a::test\nb::test\n");
+
+    // Tests that were run are only kept if they failed again.
+    let ran = ["a::test", "c::test"];
+    helpers::rerun::write_failed_tests(&path, &read, ran.iter().copied(), ["c::test"].into_iter())
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "b::test\nc::test\n");
+
+    fs::remove_file(&path).unwrap();
+}
+
+fn run_with_retries(retries: usize, testfn: fn()) -> Vec<TestEvent> {
+    let desc = TestDesc {
+        name: StaticTestName("retried"),
+        ignore: false,
+        #[cfg(not(bootstrap))]
+        ignore_message: None,
+        should_panic: ShouldPanic::No,
+        compile_fail: false,
+        no_run: false,
+        test_type: TestType::Unknown,
+    };
+    let opts = TestOpts { run_tests: true, retries, test_threads: Some(1), ..TestOpts::new() };
+    let mut events = Vec::new();
+    run_tests(&opts, vec![TestDescAndFn { desc, testfn: StaticTestFn(testfn) }], |event| {
+        events.push(event);
+        Ok(())
+    })
+    .unwrap();
+    events
+        .into_iter()
+        .filter(|event| matches!(event, TestEvent::TeRetry(..) | TestEvent::TeResult(_)))
+        .collect()
+}
+
+#[test]
+pub fn retried_test_that_passes_is_flaky() {
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn passes_on_third_attempt() {
+        assert!(ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 2);
+    }
+
+    let events = run_with_retries(3, passes_on_third_attempt);
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[0], TestEvent::TeRetry(ref test, 1) if test.result == TrFailed));
+    assert!(matches!(events[1], TestEvent::TeRetry(ref test, 2) if test.result == TrFailed));
+    assert!(matches!(events[2], TestEvent::TeResult(ref test) if test.result == TrFlaky(2)));
+}
+
+#[test]
+pub fn retried_test_that_keeps_failing_fails() {
+    fn always_fails() {
+        panic!("always fails");
+    }
+
+    let events = run_with_retries(2, always_fails);
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[2], TestEvent::TeResult(ref test) if test.result == TrFailed));
+
+    let events = run_with_retries(0, always_fails);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], TestEvent::TeResult(ref test) if test.result == TrFailed));
+}
+
 #[test]
 pub fn shuffle_tests() {
     let mut opts = TestOpts::new();
@@ -788,6 +974,64 @@ fn f(b: &mut Bencher) {
     rx.recv().unwrap();
 }
 
+#[test]
+pub fn bench_comparison_verdicts() {
+    let base = BenchBaseline { samples: 50, median: 1000.0, mean: 1000.0, var: 100.0 };
+    let compare = |median: f64, var: f64, noise_threshold: f64| {
+        let new = BenchBaseline { samples: 50, median, mean: median, var };
+        base.compare(&new, noise_threshold)
+    };
+
+    let regressed = compare(1100.0, 100.0, 0.02);
+    assert_eq!(regressed.verdict, BenchVerdict::Regressed);
+    assert!((regressed.change - 0.1).abs() < 1e-9);
+    assert!(regressed.p_value < 0.05);
+    assert_eq!(compare(900.0, 100.0, 0.02).verdict, BenchVerdict::Improved);
+    // Significant, but smaller than the noise threshold.
+    assert_eq!(compare(1010.0, 100.0, 0.02).verdict, BenchVerdict::WithinNoise);
+    assert_eq!(compare(1010.0, 100.0, 0.0).verdict, BenchVerdict::Regressed);
+    // Too noisy to tell the measurements apart.
+    assert_eq!(compare(1100.0, 1_000_000.0, 0.02).verdict, BenchVerdict::NoChange);
+    assert_eq!(compare(1000.0, 100.0, 0.02).verdict, BenchVerdict::NoChange);
+}
+
+#[test]
+pub fn bench_baseline_file() {
+    let path = env::temp_dir().join(format!("libtest-baseline-{}", process::id()));
+    assert!(helpers::baseline::read_baseline(&path).is_err());
+
+    let mut baseline = BTreeMap::new();
+    let a = BenchBaseline { samples: 50, median: 12.5, mean: 12.75, var: 0.1 };
+    let b = BenchBaseline { samples: 1, median: 0.0, mean: 0.0, var: 0.0 };
+    baseline.insert("a::bench".to_owned(), a);
+    baseline.insert("b::bench\twith tab".to_owned(), b);
+    helpers::baseline::write_baseline(&path, &baseline).unwrap();
+
+    let read = helpers::baseline::read_baseline(&path).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read["a::bench"], a);
+    assert_eq!(read["b::bench\twith tab"], b);
+
+    fs::write(&path, "a::bench\t50\t12.5\tfast\t0.1\n").unwrap();
+    let err = helpers::baseline::read_baseline(&path).unwrap_err();
+    assert_eq!(err.to_string(), "invalid entry on line 1");
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+pub fn parse_noise_threshold_option() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args
+    };
+
+    assert!(parse_opts(&args(&["--noise-threshold", "-1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--noise-threshold", "fast"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--baseline", "does-not-exist/at-all"])).unwrap().is_err());
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     let test_a = TestDesc {
@@ -819,6 +1063,7 @@ fn should_sort_failures_before_printing_them() {
         total: 0,
         passed: 0,
         failed: 0,
+        flaky: 0,
         ignored: 0,
         filtered_out: 0,
         measured: 0,
@@ -840,3 +1085,19 @@ fn should_sort_failures_before_printing_them() {
     let bpos = s.find("b").unwrap();
     assert!(apos < bpos);
 }
+
+#[test]
+fn termination_reason_display() {
+    use crate::helpers::exit_code::TerminationReason;
+
+    assert_eq!(
+        TerminationReason::Exception(0xc0000005).to_string(),
+        "exception 0xc0000005 (STATUS_ACCESS_VIOLATION)"
+    );
+    assert_eq!(TerminationReason::Exception(0xe06d7363).to_string(), "exception 0xe06d7363");
+    #[cfg(unix)]
+    assert_eq!(
+        TerminationReason::Signal(libc::SIGSEGV).to_string(),
+        format!("signal {} (SIGSEGV)", libc::SIGSEGV)
+    );
+}
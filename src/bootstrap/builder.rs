@@ -1325,6 +1325,11 @@ pub fn cargo(
             let target = crate::envify(&target.triple);
             cargo.env(&format!("CARGO_TARGET_{}_LINKER", target), target_linker);
         }
+        // Without Visual Studio's environment the linker needs to be told
+        // where the import libraries of the MSVC SDK are.
+        for dir in self.msvc_sdk_lib_dirs(target) {
+            rustflags.arg(&format!("-Lnative={}", dir.display()));
+        }
         if self.is_fuse_ld_lld(target) {
             rustflags.arg("-Clink-args=-fuse-ld=lld");
         }
@@ -1514,7 +1519,7 @@ pub fn cargo(
         // the options through environment variables that are fetched and understood by both.
         //
         // FIXME: the guard against msvc shouldn't need to be here
-        if target.contains("msvc") {
+        if target.contains("msvc") && self.msvc_sdk_root(target).is_none() {
             if let Some(ref cl) = self.config.llvm_clang_cl {
                 cargo.env("CC", cl).env("CXX", cl);
             }
@@ -8,9 +8,10 @@
 //! 2. Configuration via `target.$target.android-ndk` in `config.toml`, if
 //!    applicable
 //! 3. Special logic to probe on OpenBSD
-//! 4. The `CC_$target` environment variable.
-//! 5. The `CC` environment variable.
-//! 6. "cc"
+//! 4. `clang-cl`, if `target.$target.msvc-sdk-root` is set in `config.toml`.
+//! 5. The `CC_$target` environment variable.
+//! 6. The `CC` environment variable.
+//! 7. "cc"
 //!
 //! Some of this logic is implemented here, but much of it is farmed out to the
 //! `cc` crate itself, so we end up having the same fallbacks as there.
@@ -99,10 +100,13 @@ pub fn find(build: &mut Build) {
         } else {
             set_compiler(&mut cfg, Language::C, target, config, build);
         }
+        add_msvc_sdk_includes(&mut cfg, target, build);
 
         let compiler = cfg.get_compiler();
         let ar = if let ar @ Some(..) = config.and_then(|c| c.ar.clone()) {
             ar
+        } else if build.msvc_sdk_root(target).is_some() {
+            Some(PathBuf::from("llvm-lib"))
         } else {
             cc2ar(compiler.path(), target)
         };
@@ -121,10 +125,15 @@ pub fn find(build: &mut Build) {
             .target(&target.triple)
             .host(&build.build.triple);
 
+        add_msvc_sdk_includes(&mut cfg, target, build);
+
         let cxx_configured = if let Some(cxx) = config.and_then(|c| c.cxx.as_ref()) {
             cfg.compiler(cxx);
             true
-        } else if build.hosts.contains(&target) || build.build == target {
+        } else if build.hosts.contains(&target)
+            || build.build == target
+            || build.msvc_sdk_root(target).is_some()
+        {
             set_compiler(&mut cfg, Language::CPlusPlus, target, config, build);
             true
         } else {
@@ -160,6 +169,15 @@ fn set_compiler(
     build: &Build,
 ) {
     match &*target.triple {
+        // When cross-compiling for MSVC targets we use clang-cl, which knows
+        // how to compile both C and C++, instead of the Visual Studio tools.
+        t if t.contains("msvc") && build.msvc_sdk_root(target).is_some() => {
+            match &build.config.llvm_clang_cl {
+                Some(clang_cl) => cfg.compiler(clang_cl),
+                None => cfg.compiler("clang-cl"),
+            };
+        }
+
         // When compiling for android we may have the NDK configured in the
         // config.toml in which case we look there. Otherwise the default
         // compiler already takes into account the triple in question.
@@ -224,6 +242,15 @@ fn set_compiler(
     }
 }
 
+/// Points the compiler at the headers of the MSVC SDK of `target`, if it has
+/// one. Visual Studio's compiler finds them through the `INCLUDE` environment
+/// variable instead.
+fn add_msvc_sdk_includes(cfg: &mut cc::Build, target: TargetSelection, build: &Build) {
+    for dir in build.msvc_sdk_include_dirs(target) {
+        cfg.flag(&format!("/imsvc{}", dir.display()));
+    }
+}
+
 /// The target programming language for a native compiler.
 enum Language {
     /// The compiler is targeting C.
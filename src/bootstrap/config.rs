@@ -302,7 +302,12 @@ pub struct Target {
     pub musl_root: Option<PathBuf>,
     pub musl_libdir: Option<PathBuf>,
     pub wasi_root: Option<PathBuf>,
+    /// Some(path to the headers and import libraries) if cross-compiling for
+    /// an MSVC target with `clang-cl` and `lld-link`.
+    pub msvc_sdk_root: Option<PathBuf>,
     pub qemu_rootfs: Option<PathBuf>,
+    /// Some(command line) if tests are run through a user-defined runner.
+    pub runner: Option<Vec<String>>,
     pub no_std: bool,
 }
 
@@ -634,7 +639,9 @@ struct TomlTarget {
         musl_root: Option<String> = "musl-root",
         musl_libdir: Option<String> = "musl-libdir",
         wasi_root: Option<String> = "wasi-root",
+        msvc_sdk_root: Option<String> = "msvc-sdk-root",
         qemu_rootfs: Option<String> = "qemu-rootfs",
+        runner: Option<Vec<String>> = "runner",
         no_std: Option<bool> = "no-std",
     }
 }
@@ -1062,7 +1069,9 @@ pub fn parse(args: &[String]) -> Config {
                 target.musl_root = cfg.musl_root.map(PathBuf::from);
                 target.musl_libdir = cfg.musl_libdir.map(PathBuf::from);
                 target.wasi_root = cfg.wasi_root.map(PathBuf::from);
+                target.msvc_sdk_root = cfg.msvc_sdk_root.map(PathBuf::from);
                 target.qemu_rootfs = cfg.qemu_rootfs.map(PathBuf::from);
+                target.runner = cfg.runner;
                 target.sanitizers = cfg.sanitizers;
                 target.profiler = cfg.profiler;
 
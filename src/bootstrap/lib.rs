@@ -1046,7 +1046,7 @@ fn cflags(&self, target: TargetSelection, which: GitRepo, c: CLang) -> Vec<Strin
             let cc = self.cc(target);
             if cc.ends_with("clang") || cc.ends_with("gcc") {
                 base.push(format!("-fdebug-prefix-map={}", map));
-            } else if cc.ends_with("clang-cl.exe") {
+            } else if cc.ends_with("clang-cl.exe") || cc.ends_with("clang-cl") {
                 base.push("-Xclang".into());
                 base.push(format!("-fdebug-prefix-map={}", map));
             }
@@ -1088,6 +1088,8 @@ fn linker(&self, target: TargetSelection) -> Option<&Path> {
             && !target.contains("msvc")
         {
             Some(self.cc(target))
+        } else if self.msvc_sdk_root(target).is_some() {
+            Some(Path::new("lld-link"))
         } else if self.config.use_lld && !self.is_fuse_ld_lld(target) && self.build == target {
             Some(&self.initial_lld)
         } else {
@@ -1149,6 +1151,54 @@ fn wasi_root(&self, target: TargetSelection) -> Option<&Path> {
         self.config.target_config.get(&target).and_then(|t| t.wasi_root.as_ref()).map(|p| &**p)
     }
 
+    /// Returns the root of the MSVC headers and import libraries for this
+    /// `target`, if defined. Setting it means that the target is built with
+    /// `clang-cl` and `lld-link` instead of the Visual Studio tools.
+    fn msvc_sdk_root(&self, target: TargetSelection) -> Option<&Path> {
+        self.config
+            .target_config
+            .get(&target)
+            .and_then(|t| t.msvc_sdk_root.as_ref())
+            .map(|p| &**p)
+    }
+
+    /// Returns the header directories of the MSVC SDK of this `target`.
+    ///
+    /// The SDK is laid out like the output of `xwin splat`, and only the
+    /// directories that exist are returned, since older SDKs don't have a
+    /// separate universal CRT.
+    fn msvc_sdk_include_dirs(&self, target: TargetSelection) -> Vec<PathBuf> {
+        let root = match self.msvc_sdk_root(target) {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+        ["crt/include", "sdk/include/ucrt", "sdk/include/um", "sdk/include/shared"]
+            .iter()
+            .map(|dir| root.join(dir))
+            .filter(|dir| dir.exists())
+            .collect()
+    }
+
+    /// Returns the import library directories of the MSVC SDK of this
+    /// `target`, see `msvc_sdk_include_dirs`.
+    fn msvc_sdk_lib_dirs(&self, target: TargetSelection) -> Vec<PathBuf> {
+        let root = match self.msvc_sdk_root(target) {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+        let arch = match target.triple.split('-').next().unwrap() {
+            "x86_64" => "x86_64",
+            "aarch64" => "aarch64",
+            arch if arch.starts_with("arm") || arch.starts_with("thumb") => "aarch",
+            _ => "x86",
+        };
+        ["crt/lib", "sdk/lib/ucrt", "sdk/lib/um"]
+            .iter()
+            .map(|dir| root.join(dir).join(arch))
+            .filter(|dir| dir.exists())
+            .collect()
+    }
+
     /// Returns `true` if this is a no-std `target`, if defined
     fn no_std(&self, target: TargetSelection) -> Option<bool> {
         self.config.target_config.get(&target).map(|t| t.no_std)
@@ -1157,9 +1207,17 @@ fn no_std(&self, target: TargetSelection) -> Option<bool> {
     /// Returns `true` if the target will be tested using the `remote-test-client`
     /// and `remote-test-server` binaries.
     fn remote_tested(&self, target: TargetSelection) -> bool {
-        self.qemu_rootfs(target).is_some()
-            || target.contains("android")
-            || env::var_os("TEST_DEVICE_ADDR").is_some()
+        self.runner(target).is_none()
+            && (self.qemu_rootfs(target).is_some()
+                || target.contains("android")
+                || env::var_os("TEST_DEVICE_ADDR").is_some())
+    }
+
+    /// Returns the command line that tests for this target are run through,
+    /// if one was configured. It takes the same arguments as
+    /// `remote-test-client run`.
+    fn runner(&self, target: TargetSelection) -> Option<&[String]> {
+        self.config.target_config.get(&target).and_then(|t| t.runner.as_deref())
     }
 
     /// Returns the root of the "rootfs" image that this target will be using,
@@ -177,6 +177,10 @@ pub fn check(build: &mut Build) {
             }
         }
 
+        if build.runner(*target).map_or(false, |runner| runner.is_empty()) {
+            panic!("the target.{}.runner option must not be empty", target);
+        }
+
         // Make sure musl-root is valid
         if target.contains("musl") {
             // If this is a native target (host is also musl) and no musl-root is given,
@@ -199,7 +203,46 @@ pub fn check(build: &mut Build) {
             }
         }
 
-        if need_cmake && target.contains("msvc") {
+        // Make sure msvc-sdk-root is valid. Without it only a Windows host
+        // knows where to find the Visual Studio tools and libraries, unless
+        // the toolchain is set up by hand through the `cc`, `ar` and `linker`
+        // options or the `CC_$TARGET` and `AR_$TARGET` environment variables.
+        if target.contains("msvc") {
+            match build.msvc_sdk_root(*target) {
+                Some(root) => {
+                    if build.msvc_sdk_lib_dirs(*target).is_empty() {
+                        panic!(
+                            "couldn't find import libraries for {} in msvc-sdk-root: {}",
+                            target,
+                            root.display()
+                        );
+                    }
+                }
+                None if !build.build.contains("windows") => {
+                    let config = build.config.target_config.get(target);
+                    let configured_in_toml = config
+                        .map_or(false, |t| t.cc.is_some() || t.ar.is_some() || t.linker.is_some());
+                    let underscored = target.triple.replace('-', "_");
+                    let configured_in_env = ["CC", "AR"].iter().any(|tool| {
+                        [&*target.triple, &*underscored]
+                            .iter()
+                            .any(|triple| env::var_os(format!("{}_{}", tool, triple)).is_some())
+                    });
+                    if !configured_in_toml && !configured_in_env {
+                        panic!(
+                            "when cross-compiling for {} from a non-Windows host either the \
+                             target.$TARGET.msvc-sdk-root option must be specified in \
+                             config.toml, or the C compiler, archiver and linker must be \
+                             configured for the target",
+                            target
+                        );
+                    }
+                }
+                None => {}
+            }
+        }
+
+        if need_cmake && target.contains("msvc") && build.build.contains("windows") {
             // There are three builds of cmake on windows: MSVC, MinGW, and
             // Cygwin. The Cygwin build does not have generators for Visual
             // Studio, so detect that here and error.
@@ -1554,7 +1554,12 @@ fn run(self, builder: &Builder<'_>) {
                 .arg("");
         }
 
-        if builder.remote_tested(target) {
+        if let Some((runner, args)) = builder.runner(target).and_then(|r| r.split_first()) {
+            cmd.arg("--runner").arg(runner);
+            for arg in args {
+                cmd.arg("--runner-arg").arg(arg);
+            }
+        } else if builder.remote_tested(target) {
             cmd.arg("--remote-test-client").arg(builder.tool_exe(Tool::RemoteTestClient));
         }
 
@@ -2020,6 +2025,23 @@ fn run(self, builder: &Builder<'_>) {
             }
             DocTests::Yes => {}
         }
+        // Cargo only runs doctests for cross targets when asked to, and then
+        // runs them through the runner too.
+        if builder.runner(target).is_some()
+            && target != builder.config.build
+            && builder.doc_tests != DocTests::No
+        {
+            cargo.arg("-Zdoctest-xcompile");
+        }
+        if let Some(runner) = builder.runner(target) {
+            // Pass the runner as an array, so that its arguments are kept
+            // intact. Unit tests don't have any support libraries.
+            let runner = runner.iter().map(String::as_str).chain(["0"]);
+            let runner = toml::Value::Array(runner.map(toml::Value::from).collect());
+            let key = toml::Value::from(&*target.triple);
+            cargo.arg("-Zunstable-options");
+            cargo.arg("--config").arg(format!("target.{}.runner={}", key, runner));
+        }
 
         cargo.arg("-p").arg(krate);
 
@@ -2039,7 +2061,9 @@ fn run(self, builder: &Builder<'_>) {
             cargo.arg("--quiet");
         }
 
-        if target.contains("emscripten") {
+        if builder.runner(target).is_some() {
+            // The runner was already passed to Cargo with `--config`.
+        } else if target.contains("emscripten") {
             cargo.env(
                 format!("CARGO_TARGET_{}_RUNNER", envify(&target.triple)),
                 builder.config.nodejs.as_ref().expect("nodejs not configured"),
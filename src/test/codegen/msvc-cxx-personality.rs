@@ -0,0 +1,49 @@
+// Test that MSVC-like targets use the C++ personality selected by the target spec.
+// LLVM always sees `__CxxFrameHandler3`, targets using the legacy `__CxxFrameHandler`
+// redirect it to that symbol with a linker directive embedded in the object.
+
+// revisions: FH3 FH
+// needs-llvm-components: x86
+// compile-flags: -C no-prepopulate-passes -C panic=unwind
+// [FH3] compile-flags: --target i686-pc-windows-msvc
+// [FH] compile-flags: --target i686-rust9x-windows-msvc
+
+#![feature(no_core, lang_items)]
+#![crate_type = "lib"]
+#![no_core]
+
+#[lang = "sized"]
+trait Sized {}
+
+#[lang = "drop"]
+trait Drop {
+    fn drop(&mut self);
+}
+
+#[lang = "drop_in_place"]
+#[allow(unconditional_recursion)]
+unsafe fn drop_in_place<T: ?Sized>(to_drop: *mut T) {
+    drop_in_place(to_drop)
+}
+
+struct S;
+
+impl Drop for S {
+    fn drop(&mut self) {}
+}
+
+extern "Rust" {
+    fn might_unwind();
+}
+
+// CHECK-LABEL: define{{.*}}void @test()
+// CHECK-SAME: personality {{.*}}@__CxxFrameHandler3
+#[no_mangle]
+pub fn test() {
+    let _s = S;
+    unsafe { might_unwind() }
+}
+
+// FH3-NOT: ALTERNATENAME
+// FH: !llvm.linker.options = !{![[OPTION:[0-9]+]]}
+// FH: ![[OPTION]] = !{!"/ALTERNATENAME:___CxxFrameHandler3=___CxxFrameHandler"}
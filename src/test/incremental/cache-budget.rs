@@ -0,0 +1,24 @@
+// Test that `-Z incremental-cache-budget` deletes the work products of the
+// current session when the incremental directory does not fit in the budget,
+// and that a large enough budget leaves them alone.
+
+// revisions: rpass1 rpass2 rpass3
+// compile-flags: -Z query-dep-graph
+// [rpass1]compile-flags: -Z incremental-cache-budget=0
+// [rpass3]compile-flags: -Z incremental-cache-budget=1G
+
+#![feature(rustc_attrs)]
+#![rustc_partition_codegened(module = "cache_budget", cfg = "rpass2")]
+#![rustc_partition_codegened(module = "cache_budget-x", cfg = "rpass2")]
+#![rustc_partition_reused(module = "cache_budget", cfg = "rpass3")]
+#![rustc_partition_reused(module = "cache_budget-x", cfg = "rpass3")]
+
+mod x {
+    pub fn sum(x: u32, y: u32) -> u32 {
+        x + y
+    }
+}
+
+pub fn main() {
+    assert_eq!(x::sum(11, 11), 22);
+}
@@ -0,0 +1,24 @@
+// Test that the dep-graph and query result cache can be reused when they are
+// stored compressed with `-Z incremental-compress`, and when switching between
+// compressed and uncompressed sessions.
+
+// revisions: rpass1 rpass2 rpass3
+// compile-flags: -Z query-dep-graph
+// [rpass1]compile-flags: -Z incremental-compress
+// [rpass3]compile-flags: -Z incremental-compress
+
+#![feature(rustc_attrs)]
+#![rustc_partition_reused(module = "compress", cfg = "rpass2")]
+#![rustc_partition_reused(module = "compress-x", cfg = "rpass2")]
+#![rustc_partition_reused(module = "compress", cfg = "rpass3")]
+#![rustc_partition_reused(module = "compress-x", cfg = "rpass3")]
+
+mod x {
+    pub fn sum(x: u32, y: u32) -> u32 {
+        x + y
+    }
+}
+
+pub fn main() {
+    assert_eq!(x::sum(11, 11), 22);
+}
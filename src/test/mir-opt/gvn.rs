@@ -0,0 +1,86 @@
+// Tests that a computation whose result is already held by a local is replaced by a copy.
+
+// EMIT_MIR gvn.slice_index.GVN.diff
+fn slice_index(x: &[f32], i: usize) -> f32 {
+    let a = x[i];
+    let b = x[i];
+    a + b
+}
+
+// EMIT_MIR gvn.arrays.GVN.diff
+fn arrays(x: u8) -> ([u8; 2], [u8; 2]) {
+    ([x, x], [x, x])
+}
+
+// EMIT_MIR gvn.casts.GVN.diff
+fn casts(x: u8) -> [u16; 2] {
+    [x as u16, x as u16]
+}
+
+// EMIT_MIR gvn.repeated_discriminant.GVN.diff
+fn repeated_discriminant(x: Option<u8>) -> (u8, u8) {
+    let a = match x {
+        None => 0,
+        Some(_) => 1,
+    };
+    let b = match x {
+        None => 2,
+        Some(_) => 3,
+    };
+    (a, b)
+}
+
+// EMIT_MIR gvn.repeated_deref.GVN.diff
+fn repeated_deref(r: &u32) -> (u32, u32) {
+    (*r, *r)
+}
+
+// EMIT_MIR gvn.mut_deref.GVN.diff
+fn mut_deref(r: &mut u32) -> (u32, u32) {
+    (*r, *r)
+}
+
+// EMIT_MIR gvn.interior_mutability.GVN.diff
+fn interior_mutability(r: &(u32, std::cell::Cell<u32>)) -> (u32, u32) {
+    (r.0, r.0)
+}
+
+// EMIT_MIR gvn.borrowed.GVN.diff
+fn borrowed(x: u8) -> [u16; 2] {
+    let mut y = x;
+    let a = y as u16;
+    let r = &mut y;
+    *r = 0;
+    [a, y as u16]
+}
+
+union U {
+    a: u32,
+    b: f32,
+}
+
+// EMIT_MIR gvn.union_field.GVN.diff
+unsafe fn union_field(x: u32) -> (u32, f32) {
+    let u = U { a: x };
+    (u.a, u.b)
+}
+
+struct NotCopy(u8);
+
+// EMIT_MIR gvn.non_copy_aggregate.GVN.diff
+fn non_copy_aggregate(x: u8) -> (NotCopy, NotCopy) {
+    (NotCopy(x), NotCopy(x))
+}
+
+fn main() {
+    slice_index(&[1.0, 2.0], 1);
+    arrays(5);
+    casts(5);
+    repeated_discriminant(Some(5));
+    repeated_deref(&5);
+    mut_deref(&mut 5);
+    interior_mutability(&(5, std::cell::Cell::new(5)));
+    borrowed(5);
+    unsafe { union_field(5) };
+    non_copy_aggregate(5);
+}
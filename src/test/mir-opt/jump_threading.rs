@@ -0,0 +1,69 @@
+// compile-flags: -Zmir-opt-level=3
+// `SeparateConstSwitch` only runs at mir-opt-level=4, so here both arms of the
+// inner match still jump to the switch of the outer match.
+#![feature(control_flow_enum, raw_ref_op)]
+
+use std::ops::ControlFlow;
+
+// EMIT_MIR jump_threading.too_complex.JumpThreading.diff
+fn too_complex(x: Result<i32, usize>) -> Option<i32> {
+    // Each arm of the inner match sets the discriminant the outer
+    // match switches on, so the edge out of each arm is threaded
+    // to the arm of the outer match it will take, and the outer
+    // switch is removed.
+    match {
+        match x {
+            Ok(v) => ControlFlow::Continue(v),
+            Err(r) => ControlFlow::Break(r),
+        }
+    } {
+        ControlFlow::Continue(v) => Some(v),
+        ControlFlow::Break(r) => None,
+    }
+}
+
+// EMIT_MIR jump_threading.too_costly.JumpThreading.diff
+fn too_costly(x: bool, a: u8) -> u8 {
+    // `y` is known along both edges into the switch, but its block has more
+    // statements than `MAX_COST` to copy, so neither edge is threaded.
+    let y = if x { 1 } else { 2 };
+    let b = a;
+    let c = b;
+    let d = c;
+    let e = d;
+    match y {
+        1 => e,
+        _ => 0,
+    }
+}
+
+// EMIT_MIR jump_threading.borrowed.JumpThreading.diff
+unsafe fn borrowed(x: bool) -> u8 {
+    // `y` is borrowed, and is written through `p` after it is assigned, so
+    // it is not followed back into the arms of the `if`.
+    let mut y = 0;
+    let p = &raw mut y;
+    y = if x { 1 } else { 2 };
+    *p = 3;
+    match y {
+        1 => 1,
+        _ => 0,
+    }
+}
+
+// EMIT_MIR jump_threading.in_loop.JumpThreading.diff
+fn in_loop() {
+    // The discriminant of `x` is known along both the edge into the loop and
+    // the back edge, so both skip the loop header.
+    let mut x = None;
+    while let None = x {
+        x = Some(1);
+    }
+}
+
+fn main() {
+    too_complex(Ok(0));
+    too_costly(true, 0);
+    unsafe { borrowed(true) };
+    in_loop();
+}
@@ -0,0 +1,19 @@
+fn classify(x: u32, y: u32) -> u32 {
+    if x > 5 && y < 3 {
+        1
+    } else {
+        0
+    }
+}
+
+fn main() {
+    let mut total = 0;
+    let mut x = 0;
+    while x < 10 {
+        total += classify(x, x % 4);
+        x += 1;
+    }
+    if total != 3 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,6 @@
+#![feature(link_cfg)]
+
+#[link(name = "plan_never_linked", cfg(any()))]
+extern "C" {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[inline(never)]
+fn frobnicate(x: u32) -> u32 {
+    x.wrapping_mul(31).rotate_left(7)
+}
+
+#[no_mangle]
+pub extern "C" fn exported_entry_point(x: u32) -> u32 {
+    frobnicate(x)
+}
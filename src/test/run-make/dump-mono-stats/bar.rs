@@ -0,0 +1,11 @@
+#![crate_type = "rlib"]
+
+#[inline(never)]
+pub fn outer<T: Copy>(x: T) -> T {
+    inner(x)
+}
+
+#[inline(never)]
+pub fn inner<T: Copy>(x: T) -> T {
+    x
+}
@@ -0,0 +1,42 @@
+#!/usr/bin/env python
+
+import json
+import sys
+
+with open(sys.argv[1]) as f:
+    report = json.load(f)
+
+assert report["crate"] == "foo"
+definitions = {d["name"]: d for d in report["definitions"]}
+
+
+def instances(name, krate, count):
+    definition = definitions[name]
+    assert definition["crate"] == krate, definition
+    assert definition["instance_count"] == count, definition
+    assert len(definition["instances"]) == count, definition
+    for instance in definition["instances"]:
+        # With a single codegen unit, every instance is placed once.
+        assert len(instance["codegen_units"]) == 1, instance
+        assert instance["size_estimate"] > 0, instance
+    assert definition["total_size_estimate"] == sum(
+        i["size_estimate"] for i in definition["instances"]), definition
+    return {i["name"]: i["used_by"] for i in definition["instances"]}
+
+
+assert list(instances("main", "foo", 1)) == ["main"]
+assert instances("local", "foo", 2) == {
+    "local::<u8>": ["foo"],
+    "local::<u32>": ["foo"],
+}
+assert instances("bar::outer", "bar", 2) == {
+    "bar::outer::<u8>": ["foo"],
+    "bar::outer::<u32>": ["foo"],
+}
+# `foo` only calls `bar::inner::<u16>` itself, the other instances are
+# there because of the code of `bar::outer`.
+assert instances("bar::inner", "bar", 3) == {
+    "bar::inner::<u8>": ["bar"],
+    "bar::inner::<u16>": ["foo"],
+    "bar::inner::<u32>": ["bar"],
+}
@@ -0,0 +1,12 @@
+extern crate bar;
+
+#[inline(never)]
+fn local<T: Copy>(x: T) -> T {
+    bar::outer(x)
+}
+
+fn main() {
+    local(1u8);
+    local(2u32);
+    bar::inner(3u16);
+}
@@ -0,0 +1,13 @@
+pub fn sum(values: &[u32]) -> u32 {
+    values.iter().sum()
+}
+
+pub fn largest(values: &[u32]) -> Option<u32> {
+    values.iter().copied().max()
+}
+
+pub mod text {
+    pub fn shout(s: &str) -> String {
+        s.to_uppercase() + "!"
+    }
+}
@@ -0,0 +1,20 @@
+#!/usr/bin/env python
+
+import json
+import sys
+
+with open(sys.argv[1]) as f:
+    report = json.load(f)
+
+cgus = {cgu["name"].split("-", 1)[1]: cgu for cgu in report["codegen_units"]}
+assert sorted(cgus) == ["edited", "untouched"], cgus
+
+edited = cgus["edited"]
+assert edited["reuse"] == "no", edited
+assert edited["reason"] == "changed", edited
+assert any(node.endswith("(edited::value)") for node in edited["changed"]), edited
+
+untouched = cgus["untouched"]
+assert untouched["reuse"] == "post-lto", untouched
+assert untouched["reason"] is None, untouched
+assert untouched["changed"] == [], untouched
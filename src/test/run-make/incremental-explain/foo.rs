@@ -0,0 +1,13 @@
+#![crate_type = "rlib"]
+
+pub mod edited {
+    pub fn value() -> u32 {
+        1
+    }
+}
+
+pub mod untouched {
+    pub fn value() -> u32 {
+        2
+    }
+}
@@ -0,0 +1,5 @@
+pub fn added() {
+    let mut added = 2;
+    let _y = added;
+}
+
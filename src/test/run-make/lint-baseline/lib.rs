@@ -0,0 +1,5 @@
+pub fn known() {
+    let mut x = 1;
+    let _y = x;
+    let _z = (1);
+}
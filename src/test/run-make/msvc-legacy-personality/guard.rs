@@ -0,0 +1,35 @@
+#![feature(no_core, lang_items, c_unwind)]
+#![crate_type = "lib"]
+#![no_core]
+
+#[lang = "sized"]
+trait Sized {}
+
+#[lang = "drop"]
+trait Drop {
+    fn drop(&mut self);
+}
+
+#[lang = "drop_in_place"]
+unsafe fn drop_in_place<T: ?Sized>(to_drop: *mut T) {
+    drop_in_place(to_drop)
+}
+
+extern "C-unwind" {
+    fn throw_from_cpp();
+    fn guard_dropped();
+}
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        unsafe { guard_dropped() }
+    }
+}
+
+#[no_mangle]
+pub extern "C-unwind" fn call_through_rust() {
+    let _guard = Guard;
+    unsafe { throw_from_cpp() }
+}
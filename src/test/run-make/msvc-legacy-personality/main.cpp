@@ -0,0 +1,25 @@
+#include <stdio.h>
+
+extern "C" void call_through_rust();
+
+static bool dropped = false;
+
+extern "C" void guard_dropped() {
+    dropped = true;
+}
+
+extern "C" void throw_from_cpp() {
+    throw 42;
+}
+
+int main() {
+    try {
+        call_through_rust();
+    } catch (int value) {
+        if (dropped) {
+            printf("caught %d after dropping the guard\n", value);
+            return 0;
+        }
+    }
+    return 1;
+}
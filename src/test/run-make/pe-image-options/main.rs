@@ -0,0 +1,11 @@
+// A `no_core` image, so that it can be linked for a Windows target without its standard library.
+
+#![feature(no_core, lang_items)]
+#![no_core]
+#![no_main]
+
+#[lang = "sized"]
+trait Sized {}
+
+#[no_mangle]
+pub extern "C" fn mainCRTStartup() {}
@@ -0,0 +1,25 @@
+#![crate_type = "rlib"]
+
+pub fn chain(i: usize) -> u8 {
+    middle(i)
+}
+
+#[inline(never)]
+fn middle(i: usize) -> u8 {
+    big(i) ^ 1
+}
+
+#[inline(never)]
+fn big(i: usize) -> u8 {
+    let mut buf = [0u8; 1024];
+    buf[i % 1024] = 1;
+    buf[(i + 1) % 1024]
+}
+
+pub fn recurse(n: u32) -> u32 {
+    if n == 0 { 0 } else { recurse(n - 1) ^ 1 }
+}
+
+pub fn indirect(f: fn(usize) -> u8) -> u8 {
+    f(0)
+}
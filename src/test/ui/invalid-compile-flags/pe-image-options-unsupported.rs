@@ -0,0 +1,9 @@
+// check-pass
+// compile-flags: --target x86_64-unknown-linux-gnu
+// compile-flags: -C image-base=0x400000 -C stack-size=0x100000 -C large-address-aware
+// needs-llvm-components: x86
+
+#![crate_type = "lib"]
+#![feature(no_core)]
+#![no_std]
+#![no_core]
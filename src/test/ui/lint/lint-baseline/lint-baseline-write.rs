@@ -0,0 +1,10 @@
+// Writing a baseline reports lints as usual.
+
+// check-pass
+// compile-flags: -Zunstable-options
+// compile-flags: --write-lint-baseline {{build-base}}/lint-baseline-write.baseline
+
+fn main() {
+    let mut x = 1; //~ WARN variable does not need to be mutable
+    let _y = x;
+}
@@ -0,0 +1,25 @@
+// Lint diagnostics that are listed in the baseline are suppressed, wherever in
+// the file they are. The same lint in an item that the baseline doesn't know
+// about is still reported with its usual level, for early and late lints alike.
+
+// compile-flags: -Zunstable-options
+// compile-flags: --lint-baseline {{src-base}}/lint/lint-baseline/lint-baseline.baseline
+
+#![deny(unused_mut, unused_parens)]
+
+fn known() {
+    let mut x = 1;
+    let _y = x;
+    let _z = (1);
+}
+
+fn new() {
+    let mut x = 1; //~ ERROR variable does not need to be mutable
+    let _y = x;
+    let _z = (2); //~ ERROR unnecessary parentheses around assigned value
+}
+
+fn main() {
+    known();
+    new();
+}
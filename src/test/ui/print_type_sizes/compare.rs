@@ -0,0 +1,37 @@
+// compile-flags: -Z compare-type-sizes={{src-base}}/print_type_sizes/auxiliary/compare-type-sizes.json
+// build-pass
+// ignore-pass
+// ^-- needed because `--pass check` does not emit the output needed.
+//     FIXME: consider using an attribute instead of side-effects.
+
+// This file checks that `-Z compare-type-sizes` reports the types that grew
+// since an earlier `-Z print-type-sizes=json` report, along with the variants
+// and fields that grew or are new. `Niche` has the same size as before, and
+// `Gone` is not in this crate anymore, so neither is printed.
+
+#![allow(dead_code)]
+#![feature(start)]
+
+pub struct Padded {
+    a: u8,
+    b: u32,
+    c: u16,
+}
+
+pub enum Tagged {
+    Small([u8; 7]),
+    Large([u8; 50]),
+}
+
+pub enum Niche {
+    Flag(bool),
+    Empty,
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _p: Padded;
+    let _t: Tagged;
+    let _n: Niche;
+    0
+}
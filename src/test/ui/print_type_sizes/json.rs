@@ -0,0 +1,35 @@
+// compile-flags: -Z print-type-sizes=json
+// build-pass
+// ignore-pass
+// ^-- needed because `--pass check` does not emit the output needed.
+//     FIXME: consider using an attribute instead of side-effects.
+
+// This file illustrates the JSON form of the report: the same layouts as the
+// text output, with field offsets, padding and niches spelled out.
+
+#![allow(dead_code)]
+#![feature(start)]
+
+pub struct Padded {
+    a: u8,
+    b: u32,
+    c: u16,
+}
+
+pub enum Tagged {
+    Small([u8; 7]),
+    Large([u8; 50]),
+}
+
+pub enum Niche {
+    Flag(bool),
+    Empty,
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _p: Padded;
+    let _t: Tagged;
+    let _n: Niche;
+    0
+}
@@ -0,0 +1,8 @@
+// Errors are reported with their code as the rule, and secondary labels as
+// related locations.
+
+// compile-flags: -Zunstable-options --error-format=pretty-sarif
+
+fn main() {
+    let _x: u32 = "a";
+}
@@ -0,0 +1,11 @@
+// Lints are reported with their name as the rule and `MachineApplicable`
+// suggestions as fixes.
+
+// compile-flags: -Zunstable-options --error-format=pretty-sarif
+
+#![deny(unused_mut)]
+
+fn main() {
+    let mut x = 1;
+    let _y = x;
+}
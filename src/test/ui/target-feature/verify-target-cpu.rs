@@ -0,0 +1,48 @@
+// Checks that `-Z verify-target-cpu` reports instructions that the target CPU lacks.
+// build-fail
+// compile-flags: --target i486-rust9x-windows-msvc -Z verify-target-cpu -C panic=abort
+// needs-llvm-components: x86
+// normalize-stderr-test "offset 0x[0-9a-f]+ in `.*`" -> "offset $$OFFSET in `$$OBJECT`"
+
+#![feature(no_core, lang_items, rustc_attrs)]
+#![crate_type = "rlib"]
+#![no_core]
+
+#[rustc_builtin_macro]
+macro_rules! asm {
+    () => {};
+}
+#[lang = "sized"]
+trait Sized {}
+
+#[no_mangle]
+pub unsafe fn supported() {
+    asm!("add eax, ecx");
+}
+
+#[no_mangle]
+pub unsafe fn uses_cmov() {
+    asm!("cmovne eax, ecx");
+}
+
+#[no_mangle]
+pub unsafe fn uses_cmpxchg8b_and_sse() {
+    // The instructions are never executed, so the clobbered registers are not declared.
+    asm!("lock cmpxchg8b qword ptr [edi]");
+    asm!("xorps xmm0, xmm0");
+}
+
+#[no_mangle]
+pub unsafe fn uses_avx_and_avx2() {
+    // `vpermilps` is an AVX instruction even on 256-bit vectors, unlike `vpaddd`.
+    asm!("vpermilps ymm0, ymm1, 0");
+    asm!("vpaddd ymm0, ymm1, ymm2");
+}
+
+#[no_mangle]
+pub unsafe fn uses_nopl_and_fcmov() {
+    // Both forms of the multi-byte `nop` are NOPL, which is only reported once.
+    asm!("nop dword ptr [eax + eax]");
+    asm!("nop word ptr cs:[eax + eax]");
+    asm!("fcmovb st, st(1)");
+}
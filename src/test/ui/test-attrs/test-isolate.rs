@@ -0,0 +1,38 @@
+// no-prefer-dynamic
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --isolate
+// run-fail
+// check-run-results
+// exec-env:RUST_BACKTRACE=0
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// normalize-stdout-test "signal \d+ \(SIGABRT\)" -> "signal $$N (SIGABRT)"
+
+// ignore-windows the process is terminated by an exception instead of a signal
+// ignore-wasm no subprocess support
+// ignore-emscripten no subprocess support
+// ignore-sgx no subprocess support
+
+#![cfg(test)]
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+#[should_panic]
+fn it_panics() {
+    assert_eq!(1 + 1, 4);
+}
+
+#[test]
+fn it_catches_a_panic() {
+    // The panic is caught by the test itself, so the test process must not report a failure.
+    assert!(std::panic::catch_unwind(|| panic!("caught")).is_err());
+}
+
+#[test]
+fn it_aborts() {
+    println!("about to abort");
+    std::process::abort();
+}
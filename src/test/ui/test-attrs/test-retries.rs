@@ -0,0 +1,25 @@
+// no-prefer-dynamic
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --retries 2
+// run-pass
+// check-run-results
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// ignore-wasm32 no support for `Instant`
+
+// Tests that a test which passes after being retried is reported as flaky.
+
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+fn it_is_flaky() {
+    assert!(ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 1);
+}
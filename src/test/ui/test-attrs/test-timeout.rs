@@ -0,0 +1,57 @@
+// no-prefer-dynamic
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --test-timeout=1
+// run-fail
+// check-run-results
+// exec-env:RUST_BACKTRACE=0
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+// ignore-wasm no subprocess support
+// ignore-emscripten no subprocess support
+// ignore-sgx no subprocess support
+
+#![cfg(test)]
+
+use std::env;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+#[should_panic]
+fn it_panics() {
+    assert_eq!(1 + 1, 4);
+}
+
+#[test]
+fn it_hangs() {
+    println!("waiting forever");
+    loop {
+        thread::sleep(Duration::from_secs(60));
+    }
+}
+
+#[test]
+fn it_hangs_in_a_child() {
+    // The child inherits the test's stderr and outlives it, which must not keep the harness from
+    // reporting the test as timed out.
+    if env::var_os("TEST_TIMEOUT_CHILD").is_some() {
+        thread::sleep(Duration::from_secs(60));
+        return;
+    }
+    let _child = Command::new(env::current_exe().unwrap())
+        .args(["it_hangs_in_a_child", "--exact"])
+        .env("TEST_TIMEOUT_CHILD", "1")
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    println!("spawned a child");
+    loop {
+        thread::sleep(Duration::from_secs(60));
+    }
+}
@@ -335,6 +335,13 @@ pub struct Config {
     /// where to find the remote test client process, if we're using it
     pub remote_test_client: Option<PathBuf>,
 
+    /// A program to run tests through instead of the remote test client,
+    /// which is invoked with the same arguments as `remote-test-client run`
+    pub runner: Option<String>,
+
+    /// Arguments passed to the runner before those of the test
+    pub runner_args: Vec<String>,
+
     /// mode describing what file the actual ui output will be compared to
     pub compare_mode: Option<CompareMode>,
 
@@ -372,6 +379,12 @@ pub struct Config {
 }
 
 impl Config {
+    /// Returns `true` if tests are run somewhere else than the host, through
+    /// the remote test client or a runner.
+    pub fn runs_remotely(&self) -> bool {
+        self.remote_test_client.is_some() || self.runner.is_some()
+    }
+
     pub fn run_enabled(&self) -> bool {
         self.run.unwrap_or_else(|| {
             // Auto-detect whether to run based on the platform.
@@ -134,6 +134,18 @@ pub fn parse_config(args: Vec<String>) -> Config {
         .optopt("", "nodejs", "the name of nodejs", "PATH")
         .optopt("", "npm", "the name of npm", "PATH")
         .optopt("", "remote-test-client", "path to the remote test client", "PATH")
+        .optopt(
+            "",
+            "runner",
+            "program to run tests through, with the arguments of `remote-test-client run`",
+            "PATH",
+        )
+        .optmulti(
+            "",
+            "runner-arg",
+            "argument to pass to the runner before those of the test",
+            "ARG",
+        )
         .optopt(
             "",
             "compare-mode",
@@ -280,6 +292,8 @@ fn make_absolute(path: PathBuf) -> PathBuf {
         quiet: matches.opt_present("quiet"),
         color,
         remote_test_client: matches.opt_str("remote-test-client").map(PathBuf::from),
+        runner: matches.opt_str("runner"),
+        runner_args: matches.opt_strs("runner-arg"),
         compare_mode: matches.opt_str("compare-mode").map(CompareMode::parse),
         rustfix_coverage: matches.opt_present("rustfix-coverage"),
         has_tidy,
@@ -320,6 +334,8 @@ pub fn log_config(config: &Config) {
         format!("force_pass_mode: {}", opt_str(&config.force_pass_mode.map(|m| format!("{}", m))),),
     );
     logv(c, format!("runtool: {}", opt_str(&config.runtool)));
+    logv(c, format!("runner: {}", opt_str(&config.runner)));
+    logv(c, format!("runner_args: {:?}", config.runner_args));
     logv(c, format!("host-rustcflags: {}", opt_str(&config.host_rustcflags)));
     logv(c, format!("target-rustcflags: {}", opt_str(&config.target_rustcflags)));
     logv(c, format!("target: {}", config.target));
@@ -447,7 +463,7 @@ fn configure_gdb(config: &Config) -> Option<Config> {
         return None;
     }
 
-    if config.remote_test_client.is_some() && !config.target.contains("android") {
+    if config.runs_remotely() && !config.target.contains("android") {
         println!(
             "WARNING: debuginfo tests are not available when \
              testing with remote"
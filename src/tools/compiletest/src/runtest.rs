@@ -1522,7 +1522,10 @@ fn exec_compiled_test(&self) -> ProcRes {
             // the program on the emulator with the arguments specified
             // (in the environment we give the process) and then report back
             // the same result.
-            _ if self.config.remote_test_client.is_some() => {
+            //
+            // A runner that was configured instead is invoked the same way,
+            // with its own command line in place of `remote-test-client run`.
+            _ if self.config.runs_remotely() => {
                 let aux_dir = self.aux_output_dir_name();
                 let ProcArgs { prog, args } = self.make_run_args();
                 let mut support_libs = Vec::new();
@@ -1535,10 +1538,17 @@ fn exec_compiled_test(&self) -> ProcRes {
                         support_libs.push(entry.path());
                     }
                 }
-                let mut test_client =
-                    Command::new(self.config.remote_test_client.as_ref().unwrap());
+                let mut test_client = if let Some(runner) = &self.config.runner {
+                    let mut runner_cmd = Command::new(runner);
+                    runner_cmd.args(&self.config.runner_args);
+                    runner_cmd
+                } else {
+                    let mut client = Command::new(self.config.remote_test_client.as_ref().unwrap());
+                    client.arg("run");
+                    client
+                };
                 test_client
-                    .args(&["run", &support_libs.len().to_string(), &prog])
+                    .args(&[&support_libs.len().to_string(), &prog])
                     .args(support_libs)
                     .args(args)
                     .envs(env.clone());
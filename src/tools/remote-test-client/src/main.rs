@@ -1,6 +1,7 @@
 //! This is a small client program intended to pair with `remote-test-server` in
-//! this repository. This client connects to the server over TCP and is used to
-//! push artifacts and run tests on the server instead of locally.
+//! this repository. This client connects to the server over TCP or a serial
+//! line and is used to push artifacts and run tests on the server instead of
+//! locally.
 //!
 //! Here is also where we bake in the support to spawn the QEMU emulator as
 //! well.
@@ -9,14 +10,28 @@
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufWriter};
-use std::net::TcpStream;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::thread;
 use std::time::Duration;
 
+use remote_test_transport::{self as transport, Address};
+use sha2::{Digest, Sha256};
+
 const REMOTE_ADDR_ENV: &str = "TEST_DEVICE_ADDR";
 const DEFAULT_ADDR: &str = "127.0.0.1:12345";
+const TIMEOUT_ENV: &str = "TEST_DEVICE_TIMEOUT";
+
+// How long past the timeout to wait for the server to kill the test, before
+// asking it to again.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+// Kinds of the frame that ends a `run` command, followed by a 32-bit value.
+const EXIT_EXITED: u8 = 0; // exit code
+const EXIT_SIGNALLED: u8 = 1; // signal number
+const EXIT_TIMED_OUT: u8 = 2; // timeout in seconds
+const EXIT_CANCELLED: u8 = 3; // unused
+const EXIT_CRASHED: u8 = 4; // exception code
 
 macro_rules! t {
     ($e:expr) => {
@@ -42,13 +57,18 @@ fn main() {
             args.next().map(|s| s.into()),
         ),
         "push" => push(Path::new(&args.next().unwrap())),
-        "run" => run(
-            args.next().and_then(|count| count.parse().ok()).unwrap(),
-            // the last required parameter must remain the executable
-            // path so that the client works as a cargo runner
-            args.next().unwrap(),
-            args.collect(),
-        ),
+        "run" => {
+            let mut args = args.peekable();
+            let cache = args.next_if_eq("--no-cache").is_none();
+            run(
+                args.next().and_then(|count| count.parse().ok()).unwrap(),
+                // the last required parameter must remain the executable
+                // path so that the client works as a cargo runner
+                args.next().unwrap(),
+                args.collect(),
+                cache,
+            )
+        }
         "help" | "-h" | "--help" => help(),
         cmd => {
             println!("unknown command: {}", cmd);
@@ -57,8 +77,16 @@ fn main() {
     }
 }
 
+fn device_address() -> Address {
+    let addr = env::var(REMOTE_ADDR_ENV).unwrap_or(DEFAULT_ADDR.to_string());
+    match Address::parse(&addr) {
+        Ok(addr) => addr,
+        Err(e) => panic!("invalid {}: {}", REMOTE_ADDR_ENV, e),
+    }
+}
+
 fn spawn_emulator(target: &str, server: &Path, tmpdir: &Path, rootfs: Option<PathBuf>) {
-    let device_address = env::var(REMOTE_ADDR_ENV).unwrap_or(DEFAULT_ADDR.to_string());
+    let device_address = device_address();
 
     if env::var(REMOTE_ADDR_ENV).is_ok() {
         println!("Connecting to remote device {} ...", device_address);
@@ -72,9 +100,8 @@ fn spawn_emulator(target: &str, server: &Path, tmpdir: &Path, rootfs: Option<Pat
     // Wait for the emulator to come online
     loop {
         let dur = Duration::from_millis(100);
-        if let Ok(mut client) = TcpStream::connect(&device_address) {
-            t!(client.set_read_timeout(Some(dur)));
-            t!(client.set_write_timeout(Some(dur)));
+        if let Ok(mut client) = transport::connect(&device_address) {
+            t!(client.set_timeout(Some(dur)));
             if client.write_all(b"ping").is_ok() {
                 let mut b = [0; 4];
                 if client.read_exact(&mut b).is_ok() {
@@ -240,8 +267,8 @@ fn start_qemu_emulator(target: &str, rootfs: &Path, server: &Path, tmpdir: &Path
 }
 
 fn push(path: &Path) {
-    let device_address = env::var(REMOTE_ADDR_ENV).unwrap_or(DEFAULT_ADDR.to_string());
-    let client = t!(TcpStream::connect(device_address));
+    let device_address = device_address();
+    let client = t!(transport::connect(&device_address));
     let mut client = BufWriter::new(client);
     t!(client.write_all(b"push"));
     send(path, &mut client);
@@ -249,18 +276,26 @@ fn push(path: &Path) {
 
     // Wait for an acknowledgement that all the data was received. No idea
     // why this is necessary, seems like it shouldn't be!
-    let mut client = client.into_inner().unwrap();
+    let mut client = t!(client.into_inner());
     let mut buf = [0; 4];
     t!(client.read_exact(&mut buf));
     assert_eq!(&buf, b"ack ");
     println!("done pushing {:?}", path);
 }
 
-fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
-    let device_address = env::var(REMOTE_ADDR_ENV).unwrap_or(DEFAULT_ADDR.to_string());
-    let client = t!(TcpStream::connect(device_address));
+fn run(support_lib_count: usize, exe: String, all_args: Vec<String>, cache: bool) {
+    let device_address = device_address();
+    let timeout = match env::var(TIMEOUT_ENV) {
+        Ok(secs) => match secs.parse::<u32>() {
+            Ok(secs) => secs,
+            Err(_) => panic!("{} must be a number of seconds, found {:?}", TIMEOUT_ENV, secs),
+        },
+        Err(_) => 0,
+    };
+    let client = t!(transport::connect(&device_address));
     let mut client = BufWriter::new(client);
     t!(client.write_all(b"run "));
+    t!(client.write_all(&timeout.to_be_bytes()));
 
     let (support_libs, args) = all_args.split_at(support_lib_count);
 
@@ -287,16 +322,37 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
     }
     t!(client.write_all(&[0]));
 
-    // Send over support libraries
-    for file in support_libs.iter().map(Path::new) {
-        send(&file, &mut client);
+    // Describe the support libraries and then the client executable as the
+    // last piece, and send over the ones that the server doesn't have cached.
+    // Without the cache their hashes are left out, so they are always sent.
+    let files: Vec<&Path> =
+        support_libs.iter().map(Path::new).chain(Some(Path::new(&exe))).collect();
+    let mut lens = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        if i == support_libs.len() {
+            t!(client.write_all(&[0]));
+        }
+        let hash = if cache { hash_file(file) } else { [0; 32] };
+        lens.push(send_header(file, &hash, &mut client));
+    }
+    t!(client.flush());
+    let mut missing = vec![0; files.len()];
+    t!(client.get_mut().read_exact(&mut missing));
+    for ((file, len), _) in files.iter().zip(lens).zip(&missing).filter(|(_, &m)| m != 0) {
+        t!(io::copy(&mut t!(File::open(file)).take(len), &mut client));
     }
-    t!(client.write_all(&[0]));
-
-    // Send over the client executable as the last piece
-    send(exe.as_ref(), &mut client);
 
-    println!("uploaded {:?}, waiting for result", exe);
+    let cached = missing.iter().filter(|&&missing| missing == 0).count();
+    if cached == 0 {
+        println!("uploaded {:?}, waiting for result", exe);
+    } else {
+        println!(
+            "uploaded {:?} ({} of {} files cached), waiting for result",
+            exe,
+            cached,
+            files.len()
+        );
+    }
 
     // Ok now it's time to read all the output. We're receiving "frames"
     // representing stdout/stderr, so we decode all that here.
@@ -304,6 +360,17 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
     let mut stderr_done = false;
     let mut stdout_done = false;
     let mut client = t!(client.into_inner());
+
+    // The server kills the test when it times out, but if it doesn't manage
+    // to, ask it again.
+    if timeout != 0 {
+        let mut cancel = t!(client.try_clone());
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs(timeout as u64) + CANCEL_GRACE_PERIOD);
+            let _ = cancel.write_all(b"kill");
+        });
+    }
+
     let mut stdout = io::stdout();
     let mut stderr = io::stderr();
     while !stdout_done || !stderr_done {
@@ -329,19 +396,38 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
         }
     }
 
-    // Finally, read out the exit status
+    // Finally, read out why the test exited
     let mut status = [0; 5];
     t!(client.read_exact(&mut status));
     let code = ((status[1] as i32) << 24)
         | ((status[2] as i32) << 16)
         | ((status[3] as i32) << 8)
         | ((status[4] as i32) << 0);
-    if status[0] == 0 {
-        std::process::exit(code);
-    } else {
-        println!("died due to signal {}", code);
-        std::process::exit(3);
+    match status[0] {
+        EXIT_EXITED => std::process::exit(code),
+        EXIT_SIGNALLED => println!("died due to signal {}", code),
+        EXIT_TIMED_OUT => println!("timed out after {} seconds and was killed", code),
+        EXIT_CANCELLED => println!("was killed after the server failed to time it out"),
+        EXIT_CRASHED => println!("crashed with exception code {:#010x}", code as u32),
+        which => panic!("unknown exit reason {}", which),
     }
+    std::process::exit(3);
+}
+
+/// Sends the name, size and hash of the file at `path`, and returns its size.
+fn send_header(path: &Path, hash: &[u8; 32], dst: &mut dyn Write) -> u64 {
+    t!(dst.write_all(path.file_name().unwrap().to_str().unwrap().as_bytes()));
+    t!(dst.write_all(&[0]));
+    let amt = t!(fs::metadata(path)).len();
+    t!(dst.write_all(&(amt as u32).to_be_bytes()));
+    t!(dst.write_all(hash));
+    amt
+}
+
+fn hash_file(path: &Path) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    t!(io::copy(&mut t!(File::open(path)), &mut hasher));
+    hasher.finalize().into()
 }
 
 fn send(path: &Path, dst: &mut dyn Write) {
@@ -361,7 +447,7 @@ fn help() {
 Sub-commands:
     spawn-emulator <target> <server> <tmpdir> [rootfs]   See below
     push <path>                                          Copy <path> to emulator
-    run <support_lib_count> <file> [support_libs...] [args...]
+    run [--no-cache] <support_lib_count> <file> [support_libs...] [args...]
                                                          Run program on emulator
     help                                                 Display help message
 
@@ -376,7 +462,9 @@ fn help() {
 
 A running emulator or adb device is connected to at the IP address and port in
 the {1} environment variable or {2} if this isn't
-specified. The file at <path> is sent to this target.
+specified. The file at <path> is sent to this target. Devices without a network
+connection can be reached over a serial line by setting {1} to
+serial:<device>[,<baud>] instead, where <baud> defaults to 115200.
 
 Executing commands on a running emulator:
 
@@ -384,9 +472,17 @@ fn help() {
 the <file> and any specified support libs are pushed to the target. Finally, the
 <file> is executed in the emulator, preserving the current environment.
 That command's status code is returned.
+
+The server keeps the files it receives in a cache, keyed by the hash of their
+contents, and only the ones it doesn't have yet are sent. Pass --no-cache to
+send all of them, and to keep them out of the cache.
+
+If {3} is set in the environment to a number of seconds, the
+<file> and any processes it started are killed if it runs for longer than that.
 ",
         env::args().next().unwrap(),
         REMOTE_ADDR_ENV,
-        DEFAULT_ADDR
+        DEFAULT_ADDR,
+        TIMEOUT_ENV
     );
 }
@@ -0,0 +1,173 @@
+//! A content-addressed cache of the files that clients upload, so that a
+//! client only has to send the executables and support libraries that the
+//! server hasn't seen yet.
+//!
+//! Each file is stored under the hex encoding of the SHA-256 hash of its
+//! contents. Clients name the files they want by that hash, and the server
+//! checks the hash of every file it adds, so a client can't make the server
+//! hand out other contents under some hash to later clients. Tests get copies
+//! of the cached files, so that a test that changes its files can't change
+//! them for later tests. Once the cache grows past its capacity the least
+//! recently used files are removed.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+/// Hash that clients send for files that should not be cached.
+pub const NO_HASH: [u8; 32] = [0; 32];
+
+pub struct Cache {
+    dir: PathBuf,
+    capacity: u64,
+    state: Mutex<State>,
+    next_partial: AtomicUsize,
+}
+
+#[derive(Default)]
+struct State {
+    blobs: HashMap<String, Blob>,
+    size: u64,
+    clock: u64,
+}
+
+struct Blob {
+    size: u64,
+    last_used: u64,
+}
+
+impl Cache {
+    /// Opens the cache in `dir`, keeping the files that an earlier server
+    /// left behind. A capacity of zero disables the cache.
+    pub fn open(dir: &Path, capacity: u64) -> io::Result<Cache> {
+        let cache = Cache {
+            dir: dir.to_owned(),
+            capacity,
+            state: Mutex::default(),
+            next_partial: AtomicUsize::new(0),
+        };
+        fs::create_dir_all(dir)?;
+
+        let mut blobs = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            match entry.file_name().into_string() {
+                Ok(name) if is_hex_hash(&name) && metadata.is_file() => {
+                    blobs.push((metadata.modified()?, name, metadata.len()))
+                }
+                _ => fs::remove_file(entry.path())?,
+            }
+        }
+        blobs.sort();
+
+        let mut guard = cache.state.lock().unwrap();
+        let state = &mut *guard;
+        for (_, name, size) in blobs {
+            state.clock += 1;
+            state.size += size;
+            state.blobs.insert(name, Blob { size, last_used: state.clock });
+        }
+        cache.evict(state);
+        drop(guard);
+        Ok(cache)
+    }
+
+    /// Copies the cached file with the given hash to `dst`, and returns
+    /// whether there was one.
+    pub fn get(&self, hash: &[u8; 32], dst: &Path) -> bool {
+        if self.capacity == 0 || *hash == NO_HASH {
+            return false;
+        }
+        let name = to_hex(hash);
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if !state.blobs.contains_key(&name) {
+            return false;
+        }
+        let blob = self.dir.join(&name);
+        if fs::copy(&blob, dst).is_err() {
+            // Somebody else removed the file, forget about it.
+            let blob = state.blobs.remove(&name).unwrap();
+            state.size -= blob.size;
+            return false;
+        }
+        state.clock += 1;
+        state.blobs.get_mut(&name).unwrap().last_used = state.clock;
+        true
+    }
+
+    /// Adds `src`, which a client just uploaded, to the cache, after checking
+    /// that its contents match `hash`.
+    pub fn insert(&self, hash: &[u8; 32], src: &Path) -> io::Result<()> {
+        if self.capacity == 0 || *hash == NO_HASH {
+            return Ok(());
+        }
+        let name = to_hex(hash);
+        if self.state.lock().unwrap().blobs.contains_key(&name) {
+            return Ok(());
+        }
+
+        // The file is copied rather than linked, as the test may still change
+        // it, and it's the copy that is checked. Leftover partial files are
+        // removed by `Cache::open`.
+        let id = self.next_partial.fetch_add(1, Ordering::Relaxed);
+        let partial = self.dir.join(format!("{}.{}", name, id));
+        fs::copy(src, &partial)?;
+        if hash_file(&partial)? != *hash {
+            fs::remove_file(&partial)?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("contents of {} don't match their hash {}", src.display(), name),
+            ));
+        }
+
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if state.blobs.contains_key(&name) {
+            // Another test uploaded the same file in the meantime.
+            return fs::remove_file(&partial);
+        }
+        let blob = self.dir.join(&name);
+        fs::rename(&partial, &blob)?;
+        let size = fs::metadata(&blob)?.len();
+        state.clock += 1;
+        state.size += size;
+        state.blobs.insert(name, Blob { size, last_used: state.clock });
+        self.evict(state);
+        Ok(())
+    }
+
+    /// Removes the least recently used files until the cache fits in its
+    /// capacity again.
+    fn evict(&self, state: &mut State) {
+        while state.size > self.capacity {
+            let name = match state.blobs.iter().min_by_key(|(_, blob)| blob.last_used) {
+                Some((name, _)) => name.clone(),
+                None => break,
+            };
+            let blob = state.blobs.remove(&name).unwrap();
+            state.size -= blob.size;
+            let _ = fs::remove_file(self.dir.join(&name));
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+fn to_hex(hash: &[u8; 32]) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn is_hex_hash(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
@@ -1,14 +1,24 @@
 //! This is a small server which is intended to run inside of an emulator or
 //! on a remote test device. This server pairs with the `remote-test-client`
 //! program in this repository. The `remote-test-client` connects to this
-//! server over a TCP socket and performs work such as:
+//! server over a TCP socket or a serial line and performs work such as:
 //!
 //! 1. Pushing shared libraries to the server
 //! 2. Running tests through the server
 //!
 //! The server supports running tests concurrently and also supports tests
-//! themselves having support libraries. All data over the TCP sockets is in a
-//! basically custom format suiting our needs.
+//! themselves having support libraries. All data over the connections is in a
+//! basically custom format suiting our needs. A serial line carries a single
+//! connection at a time, so there tests run one after the other.
+//!
+//! A test can be given a timeout by the client, after which the test and any
+//! processes it started are killed. The client can also cancel a running test
+//! by sending `kill`, or by closing the connection. Either way the server ends
+//! the `run` command with a frame describing why the test process exited.
+//!
+//! Clients describe the files of a test by their size and the hash of their
+//! contents before uploading them, and only send the ones that the server
+//! doesn't have in its cache yet (see the `cache` module).
 
 #[cfg(not(windows))]
 use std::fs::Permissions;
@@ -20,13 +30,19 @@
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufReader};
-use std::net::{TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+use remote_test_transport::{Address, Connection, Listener};
+
+use crate::cache::Cache;
+
+mod cache;
 
 macro_rules! t {
     ($e:expr) => {
@@ -39,22 +55,35 @@ macro_rules! t {
 
 static TEST: AtomicUsize = AtomicUsize::new(0);
 
-#[derive(Copy, Clone)]
+// Default capacity of the cache of uploaded files, in bytes.
+const DEFAULT_CACHE_SIZE: u64 = 1 << 30;
+
+// Kinds of the frame that ends a `run` command, followed by a 32-bit value.
+const EXIT_EXITED: u8 = 0; // exit code
+const EXIT_SIGNALLED: u8 = 1; // signal number
+const EXIT_TIMED_OUT: u8 = 2; // timeout in seconds
+const EXIT_CANCELLED: u8 = 3; // unused
+#[cfg_attr(not(windows), allow(dead_code))]
+const EXIT_CRASHED: u8 = 4; // exception code
+
+#[derive(Clone)]
 struct Config {
     pub remote: bool,
     pub verbose: bool,
+    pub bind: Option<Address>,
+    pub cache_size: u64,
 }
 
 impl Config {
     pub fn default() -> Config {
-        Config { remote: false, verbose: false }
+        Config { remote: false, verbose: false, bind: None, cache_size: DEFAULT_CACHE_SIZE }
     }
 
     pub fn parse_args() -> Config {
         let mut config = Config::default();
 
-        let args = env::args().skip(1);
-        for argument in args {
+        let mut args = env::args().skip(1);
+        while let Some(argument) = args.next() {
             match &argument[..] {
                 "remote" => {
                     config.remote = true;
@@ -62,6 +91,14 @@ pub fn parse_args() -> Config {
                 "verbose" | "-v" => {
                     config.verbose = true;
                 }
+                "--bind" => {
+                    let addr = args.next().expect("missing address after --bind");
+                    config.bind = Some(t!(Address::parse(&addr)));
+                }
+                "--cache-size" => {
+                    let size = args.next().expect("missing size after --cache-size");
+                    config.cache_size = t!(size.parse());
+                }
                 arg => panic!("unknown argument: {}", arg),
             }
         }
@@ -70,7 +107,7 @@ pub fn parse_args() -> Config {
     }
 }
 
-fn print_verbose(s: &str, conf: Config) {
+fn print_verbose(s: &str, conf: &Config) {
     if conf.verbose {
         println!("{}", s);
     }
@@ -81,13 +118,15 @@ fn main() {
 
     let config = Config::parse_args();
 
-    let bind_addr = if cfg!(target_os = "android") || cfg!(windows) || config.remote {
-        "0.0.0.0:12345"
+    let bind_addr = if let Some(bind) = config.bind.clone() {
+        bind
+    } else if cfg!(target_os = "android") || cfg!(windows) || config.remote {
+        Address::Tcp("0.0.0.0:12345".to_string())
     } else {
-        "10.0.2.15:12345"
+        Address::Tcp("10.0.2.15:12345".to_string())
     };
 
-    let listener = t!(TcpListener::bind(bind_addr));
+    let listener = t!(Listener::bind(&bind_addr));
     let (work, tmp): (PathBuf, PathBuf) = if cfg!(target_os = "android") {
         ("/data/tmp/work".into(), "/data/tmp/work/tmp".into())
     } else {
@@ -101,32 +140,35 @@ fn main() {
 
     t!(fs::create_dir_all(&work));
     t!(fs::create_dir_all(&tmp));
+    let cache = Arc::new(t!(Cache::open(&work.join("cache"), config.cache_size)));
 
     let lock = Arc::new(Mutex::new(()));
 
-    for socket in listener.incoming() {
-        let mut socket = t!(socket);
+    loop {
+        let mut socket = t!(listener.accept());
         let mut buf = [0; 4];
         if socket.read_exact(&mut buf).is_err() {
             continue;
         }
         if &buf[..] == b"ping" {
-            print_verbose("Received ping", config);
+            print_verbose("Received ping", &config);
             t!(socket.write_all(b"pong"));
         } else if &buf[..] == b"push" {
-            handle_push(socket, &work, config);
+            handle_push(socket, &work, &config);
         } else if &buf[..] == b"run " {
             let lock = lock.clone();
             let work = work.clone();
             let tmp = tmp.clone();
-            thread::spawn(move || handle_run(socket, &work, &tmp, &lock, config));
+            let cache = cache.clone();
+            let config = config.clone();
+            thread::spawn(move || handle_run(socket, &work, &tmp, &lock, &cache, &config));
         } else {
             panic!("unknown command {:?}", buf);
         }
     }
 }
 
-fn handle_push(socket: TcpStream, work: &Path, config: Config) {
+fn handle_push(socket: Box<dyn Connection>, work: &Path, config: &Config) {
     let mut reader = BufReader::new(socket);
     let dst = recv(&work, &mut reader);
     print_verbose(&format!("push {:#?}", dst), config);
@@ -145,10 +187,24 @@ fn drop(&mut self) {
     }
 }
 
-fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, config: Config) {
+fn handle_run(
+    socket: Box<dyn Connection>,
+    work: &Path,
+    tmp: &Path,
+    lock: &Mutex<()>,
+    cache: &Cache,
+    config: &Config,
+) {
     let mut arg = Vec::new();
     let mut reader = BufReader::new(socket);
 
+    // The timeout of the test comes first, in seconds. Zero means that the
+    // test can run forever.
+    let timeout = match read_u32(&mut reader) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs as u64)),
+    };
+
     // Allocate ourselves a directory that we'll delete when we're done to save
     // space.
     let n = TEST.fetch_add(1, Ordering::SeqCst);
@@ -202,17 +258,37 @@ fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, conf
     // the execution of the child, just the creation of its files.
     let lock = lock.lock();
 
-    // Next there's a list of dynamic libraries preceded by their filenames.
+    // Next there's a list of dynamic libraries, and finally the binary, each
+    // described by its filename, size and hash.
+    let mut files = Vec::new();
     while t!(reader.fill_buf())[0] != 0 {
-        recv(&path, &mut reader);
+        files.push(FileHeader::read(&path, &mut reader));
     }
     assert_eq!(t!(reader.read(&mut [0])), 1);
+    files.push(FileHeader::read(&path, &mut reader));
+
+    // Take whatever we can from the cache, and tell the other end which files
+    // it still has to send. Cached files are copied into place right away, as
+    // storing the uploaded ones may evict them again.
+    let missing: Vec<u8> =
+        files.iter().map(|file| !cache.get(&file.hash, &file.path) as u8).collect();
+    t!(reader.get_mut().write_all(&missing));
+    t!(reader.get_mut().flush());
+    for (file, _) in files.iter().zip(&missing).filter(|(_, &missing)| missing != 0) {
+        recv_contents(&file.path, file.len, &mut reader);
+        // The test can still run if its files can't be cached, for example
+        // because they don't match the hash that the client sent.
+        if let Err(e) = cache.insert(&file.hash, &file.path) {
+            print_verbose(&format!("not caching {:?}: {}", file.path, e), config);
+        }
+    }
+    let cached = missing.iter().filter(|&&missing| missing == 0).count();
 
-    // Finally we'll get the binary. The other end will tell us how big the
-    // binary is and then we'll download it all to the exe path we calculated
-    // earlier.
-    let exe = recv(&path, &mut reader);
-    print_verbose(&format!("run {:#?}", exe), config);
+    let exe = files.pop().unwrap().path;
+    print_verbose(
+        &format!("run {:#?} ({} of {} files cached)", exe, cached, missing.len()),
+        config,
+    );
 
     let mut cmd = Command::new(&exe);
     cmd.args(args);
@@ -235,6 +311,10 @@ fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, conf
     // Some tests assume RUST_TEST_TMPDIR exists
     cmd.env("RUST_TEST_TMPDIR", tmp.to_owned());
 
+    // Put the test into its own process group, so that the processes it
+    // starts can be killed along with it.
+    new_process_group(&mut cmd);
+
     // Spawn the child and ferry over stdout/stderr to the socket in a framed
     // fashion (poor man's style)
     let mut child =
@@ -242,43 +322,305 @@ fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, conf
     drop(lock);
     let mut stdout = child.stdout.take().unwrap();
     let mut stderr = child.stderr.take().unwrap();
-    let socket = Arc::new(Mutex::new(reader.into_inner()));
+    let socket = reader.into_inner();
+
+    // Kill the test when it runs out of time, or when the client asks for it
+    // by sending `kill` or by going away.
+    let watchdog = Arc::new(Watchdog::new(child.id()));
+    if let Some(timeout) = timeout {
+        let watchdog = watchdog.clone();
+        thread::spawn(move || watchdog.kill_after(timeout));
+    }
+    let mut cancel = t!(socket.try_clone());
+    let watchdog2 = watchdog.clone();
+    thread::spawn(move || {
+        let mut buf = [0; 4];
+        match cancel.read_exact(&mut buf) {
+            Ok(()) if &buf[..] != b"kill" => {}
+            _ => watchdog2.kill(TestState::Cancelled),
+        }
+    });
+
+    let socket = Arc::new(Mutex::new(socket));
     let socket2 = socket.clone();
     let thread = thread::spawn(move || my_copy(&mut stdout, 0, &*socket2));
     my_copy(&mut stderr, 1, &*socket);
     thread.join().unwrap();
 
-    // Finally send over the exit status.
-    let status = t!(child.wait());
-
-    let (which, code) = get_status_code(&status);
+    // Finally send over the reason the test exited.
+    let (state, status) = watchdog.reap(&mut child);
+    let (which, code) = match state {
+        TestState::TimedOut => (EXIT_TIMED_OUT, timeout.unwrap().as_secs() as i32),
+        TestState::Cancelled => (EXIT_CANCELLED, 0),
+        _ => get_status_code(&status),
+    };
+    print_verbose(&format!("exit {} {}", which, code), config);
 
-    t!(socket.lock().unwrap().write_all(&[
+    // The client may have gone away if it cancelled the test.
+    let _ = socket.lock().unwrap().write_all(&[
         which,
         (code >> 24) as u8,
         (code >> 16) as u8,
         (code >> 8) as u8,
         (code >> 0) as u8,
-    ]));
+    ]);
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum TestState {
+    Running,
+    Finished,
+    TimedOut,
+    Cancelled,
+}
+
+/// Kills a test process and the processes it started, unless it has already
+/// finished by itself.
+struct Watchdog {
+    pid: u32,
+    state: Mutex<TestState>,
+    finished: Condvar,
+}
+
+impl Watchdog {
+    fn new(pid: u32) -> Watchdog {
+        Watchdog { pid, state: Mutex::new(TestState::Running), finished: Condvar::new() }
+    }
+
+    fn kill_after(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        while *state == TestState::Running {
+            let now = Instant::now();
+            if now >= deadline {
+                drop(state);
+                self.kill(TestState::TimedOut);
+                return;
+            }
+            state = self.finished.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    fn kill(&self, reason: TestState) {
+        // The process isn't reaped while the state is still `Running` under
+        // the lock, so its pid, and its process group, can't have been reused.
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            TestState::Running => *state = reason,
+            // The test survived an earlier kill, e.g. because it started more
+            // processes in the meantime. Kill whatever is left of it again,
+            // but keep the first reason.
+            TestState::TimedOut | TestState::Cancelled => {}
+            TestState::Finished => return,
+        }
+        if let Err(e) = kill_process_tree(self.pid) {
+            eprintln!("failed to kill test process {}: {}", self.pid, e);
+        }
+    }
+
+    /// Reaps the test process once it has exited, and returns whether it was
+    /// killed. The process is reaped under the lock and the state is
+    /// `Finished` afterwards, so `kill` can't reach an unrelated process that
+    /// reused its pid.
+    fn reap(&self, child: &mut Child) -> (TestState, ExitStatus) {
+        loop {
+            let mut state = self.state.lock().unwrap();
+            if let Some(status) = t!(child.try_wait()) {
+                let reason = match *state {
+                    TestState::Running => TestState::Finished,
+                    reason => reason,
+                };
+                *state = TestState::Finished;
+                self.finished.notify_all();
+                return (reason, status);
+            }
+            drop(state);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+#[cfg(not(windows))]
+extern "C" {
+    fn setpgid(pid: i32, pgid: i32) -> i32;
+    fn kill(pid: i32, sig: i32) -> i32;
+}
+
+#[cfg(not(windows))]
+fn new_process_group(cmd: &mut Command) {
+    unsafe {
+        cmd.pre_exec(|| if setpgid(0, 0) == 0 { Ok(()) } else { Err(io::Error::last_os_error()) });
+    }
+}
+
+#[cfg(windows)]
+fn new_process_group(_cmd: &mut Command) {}
+
+#[cfg(not(windows))]
+fn kill_process_tree(pid: u32) -> io::Result<()> {
+    const SIGKILL: i32 = 9;
+    // The test is the leader of its process group, so this kills the whole
+    // group.
+    if unsafe { kill(-(pid as i32), SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Kills the test process and its descendants, which are found through the
+/// parent process ids in a Toolhelp snapshot. Unlike job objects and
+/// `taskkill`, this is also available on Windows 9x and XP Home. Processes
+/// whose parent already exited can't be told apart from unrelated ones, and
+/// are left alone.
+#[cfg(windows)]
+fn kill_process_tree(pid: u32) -> io::Result<()> {
+    let processes = windows::processes()?;
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        let children: Vec<u32> = processes
+            .iter()
+            .filter(|&&(child, parent)| parent == tree[i] && !tree.contains(&child))
+            .map(|&(child, _)| child)
+            .collect();
+        tree.extend(children);
+        i += 1;
+    }
+    // The test itself is killed first, so that it can't start any more
+    // processes.
+    let mut result = Ok(());
+    for pid in tree {
+        if let Err(e) = windows::terminate(pid) {
+            result = result.and(Err(e));
+        }
+    }
+    result
+}
+
+#[cfg(windows)]
+#[allow(non_snake_case)]
+mod windows {
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem;
+
+    type HANDLE = *mut c_void;
+
+    const INVALID_HANDLE_VALUE: HANDLE = -1isize as HANDLE;
+    const TH32CS_SNAPPROCESS: u32 = 0x2;
+    const PROCESS_TERMINATE: u32 = 0x1;
+    const ERROR_INVALID_PARAMETER: i32 = 87;
+
+    #[repr(C)]
+    struct PROCESSENTRY32 {
+        dwSize: u32,
+        cntUsage: u32,
+        th32ProcessID: u32,
+        th32DefaultHeapID: usize,
+        th32ModuleID: u32,
+        cntThreads: u32,
+        th32ParentProcessID: u32,
+        pcPriClassBase: i32,
+        dwFlags: u32,
+        szExeFile: [u8; 260],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateToolhelp32Snapshot(dwFlags: u32, th32ProcessID: u32) -> HANDLE;
+        fn Process32First(hSnapshot: HANDLE, lppe: *mut PROCESSENTRY32) -> i32;
+        fn Process32Next(hSnapshot: HANDLE, lppe: *mut PROCESSENTRY32) -> i32;
+        fn OpenProcess(dwDesiredAccess: u32, bInheritHandle: i32, dwProcessId: u32) -> HANDLE;
+        fn TerminateProcess(hProcess: HANDLE, uExitCode: u32) -> i32;
+        fn CloseHandle(hObject: HANDLE) -> i32;
+    }
+
+    /// Returns the id and parent id of every running process.
+    pub fn processes() -> io::Result<Vec<(u32, u32)>> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error());
+            }
+            let mut entry: PROCESSENTRY32 = mem::zeroed();
+            entry.dwSize = mem::size_of::<PROCESSENTRY32>() as u32;
+            let mut processes = Vec::new();
+            let mut more = Process32First(snapshot, &mut entry);
+            while more != 0 {
+                processes.push((entry.th32ProcessID, entry.th32ParentProcessID));
+                more = Process32Next(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+            Ok(processes)
+        }
+    }
+
+    /// Terminates a process, unless it has already gone away.
+    pub fn terminate(pid: u32) -> io::Result<()> {
+        unsafe {
+            let process = OpenProcess(PROCESS_TERMINATE, 0, pid);
+            if process.is_null() {
+                let err = io::Error::last_os_error();
+                return match err.raw_os_error() {
+                    Some(ERROR_INVALID_PARAMETER) => Ok(()),
+                    _ => Err(err),
+                };
+            }
+            let ok = TerminateProcess(process, 1);
+            let err = io::Error::last_os_error();
+            CloseHandle(process);
+            if ok != 0 { Ok(()) } else { Err(err) }
+        }
+    }
 }
 
 #[cfg(not(windows))]
 fn get_status_code(status: &ExitStatus) -> (u8, i32) {
     match status.code() {
-        Some(n) => (0, n),
-        None => (1, status.signal().unwrap()),
+        Some(n) => (EXIT_EXITED, n),
+        None => (EXIT_SIGNALLED, status.signal().unwrap()),
     }
 }
 
 #[cfg(windows)]
 fn get_status_code(status: &ExitStatus) -> (u8, i32) {
-    (0, status.code().unwrap())
+    let code = status.code().unwrap();
+    // NTSTATUS error codes, like STATUS_ACCESS_VIOLATION, mean that the
+    // process was terminated by an unhandled exception.
+    if code as u32 & 0xf0000000 == 0xc0000000 {
+        (EXIT_CRASHED, code)
+    } else {
+        (EXIT_EXITED, code)
+    }
+}
+
+struct FileHeader {
+    path: PathBuf,
+    len: u64,
+    hash: [u8; 32],
+}
+
+impl FileHeader {
+    fn read<B: BufRead>(dir: &Path, io: &mut B) -> FileHeader {
+        let path = recv_filename(dir, io);
+        let len = read_u32(io) as u64;
+        let mut hash = [0; 32];
+        t!(io.read_exact(&mut hash));
+        FileHeader { path, len, hash }
+    }
 }
 
 fn recv<B: BufRead>(dir: &Path, io: &mut B) -> PathBuf {
+    let dst = recv_filename(dir, io);
+    let amt = read_u32(io) as u64;
+    recv_contents(&dst, amt, io);
+    dst
+}
+
+fn recv_filename<B: BufRead>(dir: &Path, io: &mut B) -> PathBuf {
     let mut filename = Vec::new();
     t!(io.read_until(0, &mut filename));
-
     // We've got some tests with *really* long names. We try to name the test
     // executable the same on the target as it is on the host to aid with
     // debugging, but the targets we're emulating are often more restrictive
@@ -289,11 +631,12 @@ fn recv<B: BufRead>(dir: &Path, io: &mut B) -> PathBuf {
     // hopefully allow us to still identify what's running while staying under
     // the filesystem limits.
     let len = cmp::min(filename.len() - 1, 50);
-    let dst = dir.join(t!(str::from_utf8(&filename[..len])));
-    let amt = read_u32(io) as u64;
-    t!(io::copy(&mut io.take(amt), &mut t!(File::create(&dst))));
-    set_permissions(&dst);
-    dst
+    dir.join(t!(str::from_utf8(&filename[..len])))
+}
+
+fn recv_contents<B: BufRead>(dst: &Path, amt: u64, io: &mut B) {
+    t!(io::copy(&mut io.take(amt), &mut t!(File::create(dst))));
+    set_permissions(dst);
 }
 
 #[cfg(not(windows))]
@@ -308,16 +651,16 @@ fn my_copy(src: &mut dyn Read, which: u8, dst: &Mutex<dyn Write>) {
     loop {
         let n = t!(src.read(&mut b));
         let mut dst = dst.lock().unwrap();
-        t!(dst.write_all(&[
+        let frame = dst.write_all(&[
             which,
             (n >> 24) as u8,
             (n >> 16) as u8,
             (n >> 8) as u8,
             (n >> 0) as u8,
-        ]));
-        if n > 0 {
-            t!(dst.write_all(&b[..n]));
-        } else {
+        ]);
+        // If the client went away the test is being killed, so there's no
+        // one to forward the output to anymore.
+        if frame.and_then(|()| dst.write_all(&b[..n])).is_err() || n == 0 {
             break;
         }
     }
@@ -0,0 +1,259 @@
+//! Runs the server on the loopback interface and checks how it reports the
+//! ways a test can exit, and how it caches the files of tests.
+
+#![cfg(target_os = "linux")]
+
+use std::env;
+use std::fs;
+use std::io::prelude::*;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+struct Server {
+    child: Child,
+    addr: String,
+    tmp: PathBuf,
+}
+
+impl Server {
+    fn start(name: &str, args: &[&str]) -> Server {
+        let tmp =
+            env::temp_dir().join(format!("remote-test-server-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&tmp).unwrap();
+
+        // Find a free port for the server.
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let addr = format!("127.0.0.1:{}", port);
+        let child = Command::new(env!("CARGO_BIN_EXE_remote-test-server"))
+            .args(&["--bind", &addr])
+            .args(args)
+            .env("TMPDIR", &tmp)
+            .spawn()
+            .unwrap();
+
+        let start = Instant::now();
+        loop {
+            if let Ok(mut socket) = TcpStream::connect(&addr) {
+                socket.write_all(b"ping").unwrap();
+                let mut buf = [0; 4];
+                socket.read_exact(&mut buf).unwrap();
+                assert_eq!(&buf, b"pong");
+                break;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10), "server did not start");
+            thread::sleep(Duration::from_millis(10));
+        }
+        Server { child, addr, tmp }
+    }
+
+    /// Sends a shell script as the test to run, and returns the connection
+    /// the output of the test is read from.
+    fn run(&self, script: &str, timeout: u32) -> TcpStream {
+        self.run_cached(script, timeout, [0; 32]).0
+    }
+
+    /// Like `run`, but claims that the script has the given hash, and also
+    /// returns whether the server asked for the script to be sent.
+    fn run_cached(&self, script: &str, timeout: u32, hash: [u8; 32]) -> (TcpStream, bool) {
+        let mut socket = TcpStream::connect(&self.addr).unwrap();
+        let mut msg = Vec::new();
+        msg.extend_from_slice(b"run ");
+        msg.extend_from_slice(&timeout.to_be_bytes());
+        // No arguments, no environment variables and no support libraries.
+        msg.extend_from_slice(&[0, 0, 0]);
+        let script = to_file(script);
+        msg.extend_from_slice(b"test.sh\0");
+        msg.extend_from_slice(&(script.len() as u32).to_be_bytes());
+        msg.extend_from_slice(&hash);
+        socket.write_all(&msg).unwrap();
+
+        let mut missing = [0];
+        socket.read_exact(&mut missing).unwrap();
+        if missing[0] != 0 {
+            socket.write_all(script.as_bytes()).unwrap();
+        }
+        (socket, missing[0] != 0)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.tmp);
+    }
+}
+
+fn to_file(script: &str) -> String {
+    format!("#!/bin/sh\n{}", script)
+}
+
+fn hash(script: &str) -> [u8; 32] {
+    Sha256::digest(to_file(script).as_bytes()).into()
+}
+
+/// Reads the next frame, returning its kind and payload. The payload of the
+/// exit frame is the 32-bit value that goes with its kind.
+fn read_frame(socket: &mut TcpStream, exited: bool) -> (u8, Vec<u8>) {
+    let mut header = [0; 5];
+    socket.read_exact(&mut header).unwrap();
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    if exited {
+        return (header[0], len.to_be_bytes().to_vec());
+    }
+    let mut data = vec![0; len as usize];
+    socket.read_exact(&mut data).unwrap();
+    (header[0], data)
+}
+
+/// Reads the output of a test until both of its streams are closed, then
+/// returns the stdout and the exit frame.
+fn wait(socket: &mut TcpStream) -> (String, u8, u32) {
+    let mut stdout = Vec::new();
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    while !stdout_done || !stderr_done {
+        let (which, data) = read_frame(socket, false);
+        match (which, data.is_empty()) {
+            (0, true) => stdout_done = true,
+            (0, false) => stdout.extend_from_slice(&data),
+            (_, true) => stderr_done = true,
+            (_, false) => {}
+        }
+    }
+    let (which, code) = read_frame(socket, true);
+    let code = u32::from_be_bytes([code[0], code[1], code[2], code[3]]);
+    (String::from_utf8(stdout).unwrap(), which, code)
+}
+
+fn is_running(pid: &str) -> bool {
+    // A killed process that hasn't been reaped yet is a zombie.
+    match fs::read_to_string(Path::new("/proc").join(pid).join("stat")) {
+        Ok(stat) => !stat.rsplit(')').next().unwrap().trim_start().starts_with('Z'),
+        Err(_) => false,
+    }
+}
+
+fn assert_killed(pid: &str) {
+    let start = Instant::now();
+    while is_running(pid) {
+        assert!(start.elapsed() < Duration::from_secs(10), "process {} was not killed", pid);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn exit_reasons() {
+    let server = Server::start("exit", &[]);
+
+    let mut socket = server.run("echo hello; exit 7", 0);
+    assert_eq!(wait(&mut socket), ("hello\n".to_string(), 0, 7));
+
+    let mut socket = server.run("kill -SEGV $$", 0);
+    assert_eq!(wait(&mut socket), (String::new(), 1, 11));
+}
+
+#[test]
+fn timeout_kills_process_tree() {
+    let server = Server::start("timeout", &[]);
+
+    // Start a grandchild that would keep the test running if it were not
+    // killed along with the test.
+    let start = Instant::now();
+    let mut socket = server.run("sleep 1000 &\necho $!\nwait", 1);
+    let (stdout, which, code) = wait(&mut socket);
+    assert_eq!((which, code), (2, 1));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert_killed(stdout.trim());
+
+    // A test that finishes in time is unaffected.
+    let mut socket = server.run("exit 0", 60);
+    assert_eq!(wait(&mut socket), (String::new(), 0, 0));
+}
+
+#[test]
+fn kill_cancels_test() {
+    let server = Server::start("kill", &[]);
+
+    let mut socket = server.run("echo $$\nsleep 1000", 0);
+    let (which, pid) = read_frame(&mut socket, false);
+    assert_eq!(which, 0);
+    let pid = String::from_utf8(pid).unwrap();
+    socket.write_all(b"kill").unwrap();
+    assert_eq!(wait(&mut socket).1, 3);
+    assert_killed(pid.trim());
+
+    // Closing the connection cancels the test as well.
+    let mut socket = server.run("echo $$\nsleep 1000", 0);
+    let (_, pid) = read_frame(&mut socket, false);
+    drop(socket);
+    assert_killed(String::from_utf8(pid).unwrap().trim());
+}
+
+#[test]
+fn cached_files_are_not_sent_again() {
+    let server = Server::start("cache", &[]);
+
+    let (mut socket, sent) = server.run_cached("echo one", 0, hash("echo one"));
+    assert!(sent);
+    assert_eq!(wait(&mut socket), ("one\n".to_string(), 0, 0));
+    let (mut socket, sent) = server.run_cached("echo changed", 0, hash("echo one"));
+    assert!(!sent);
+    assert_eq!(wait(&mut socket), ("one\n".to_string(), 0, 0));
+
+    // Files without a hash are always sent.
+    let (mut socket, sent) = server.run_cached("echo two", 0, [0; 32]);
+    assert!(sent);
+    assert_eq!(wait(&mut socket), ("two\n".to_string(), 0, 0));
+    let (mut socket, sent) = server.run_cached("echo three", 0, [0; 32]);
+    assert!(sent);
+    assert_eq!(wait(&mut socket), ("three\n".to_string(), 0, 0));
+}
+
+#[test]
+fn least_recently_used_files_are_evicted() {
+    // Room for two of the scripts, but not for three.
+    let server = Server::start("evict", &["--cache-size", "40"]);
+    let run = |script: &str| {
+        let (mut socket, sent) = server.run_cached(script, 0, hash(script));
+        assert_eq!(wait(&mut socket).1, 0);
+        sent
+    };
+
+    assert!(run("exit 0 # 1"));
+    assert!(run("exit 0 # 2"));
+    assert!(!run("exit 0 # 1"));
+    assert!(run("exit 0 # 3"));
+    assert!(!run("exit 0 # 1"));
+    assert!(run("exit 0 # 2"));
+}
+
+#[test]
+fn files_not_matching_their_hash_are_not_cached() {
+    let server = Server::start("mismatch", &[]);
+
+    // The test still runs, but the server doesn't remember the file under the
+    // wrong hash.
+    let (mut socket, sent) = server.run_cached("echo two", 0, hash("echo one"));
+    assert!(sent);
+    assert_eq!(wait(&mut socket), ("two\n".to_string(), 0, 0));
+    let (mut socket, sent) = server.run_cached("echo one", 0, hash("echo one"));
+    assert!(sent);
+    assert_eq!(wait(&mut socket), ("one\n".to_string(), 0, 0));
+}
+
+#[test]
+fn tests_cannot_change_cached_files() {
+    let server = Server::start("overwrite", &[]);
+    let script = "echo one; echo 'echo changed' > \"$0\"";
+
+    for i in 0..3 {
+        let (mut socket, sent) = server.run_cached(script, 0, hash(script));
+        assert_eq!(sent, i == 0);
+        assert_eq!(wait(&mut socket), ("one\n".to_string(), 0, 0));
+    }
+}
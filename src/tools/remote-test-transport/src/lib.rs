@@ -0,0 +1,111 @@
+//! Transports used by `remote-test-client` and `remote-test-server` to talk
+//! to each other.
+//!
+//! The client and the server exchange commands over connections, which are
+//! reliable byte streams like TCP sockets. Devices that can't do TCP can be
+//! reached over a serial line instead, which carries one connection at a time
+//! in checksummed frames that are retransmitted when they get lost (see the
+//! `serial` module).
+//!
+//! Addresses are either `host:port` for TCP, or `serial:PATH[,BAUD]` for a
+//! serial port or any other character device, like a pseudo-terminal.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub mod serial;
+
+/// Baud rate of serial ports whose address doesn't specify one.
+pub const DEFAULT_BAUD: u32 = 115200;
+
+/// Where a server listens, or what a client connects to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Address {
+    Tcp(String),
+    Serial { path: PathBuf, baud: u32 },
+}
+
+impl Address {
+    pub fn parse(addr: &str) -> Result<Address, String> {
+        let device = match addr.strip_prefix("serial:") {
+            Some(device) => device,
+            None => return Ok(Address::Tcp(addr.to_string())),
+        };
+        let (path, baud) = match device.rsplit_once(',') {
+            Some((path, baud)) => match baud.parse() {
+                Ok(baud) if baud > 0 => (path, baud),
+                _ => return Err(format!("invalid baud rate `{}` in `{}`", baud, addr)),
+            },
+            None => (device, DEFAULT_BAUD),
+        };
+        if path.is_empty() {
+            return Err(format!("missing serial device in `{}`", addr));
+        }
+        Ok(Address::Serial { path: PathBuf::from(path), baud })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Tcp(addr) => f.write_str(addr),
+            Address::Serial { path, baud } => write!(f, "serial:{},{}", path.display(), baud),
+        }
+    }
+}
+
+/// A reliable, bidirectional byte stream between the client and the server.
+pub trait Connection: Read + Write + Send {
+    /// Returns another handle to the same connection, for example to read
+    /// from it on one thread while writing to it on another.
+    fn try_clone(&self) -> io::Result<Box<dyn Connection>>;
+
+    /// Sets how long reads wait for data before failing with `TimedOut` or
+    /// `WouldBlock`. `None` waits forever.
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> io::Result<Box<dyn Connection>> {
+        Ok(Box::new(TcpStream::try_clone(self)?))
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+/// Connects to the server at `addr`.
+pub fn connect(addr: &Address) -> io::Result<Box<dyn Connection>> {
+    match addr {
+        Address::Tcp(addr) => Ok(Box::new(TcpStream::connect(addr)?)),
+        Address::Serial { path, baud } => Ok(Box::new(serial::connect(path, *baud)?)),
+    }
+}
+
+/// Accepts the connections of clients.
+pub enum Listener {
+    Tcp(TcpListener),
+    Serial(serial::Listener),
+}
+
+impl Listener {
+    pub fn bind(addr: &Address) -> io::Result<Listener> {
+        match addr {
+            Address::Tcp(addr) => Ok(Listener::Tcp(TcpListener::bind(addr)?)),
+            Address::Serial { path, baud } => Ok(Listener::Serial(serial::listen(path, *baud)?)),
+        }
+    }
+
+    /// Waits for the next client to connect.
+    pub fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        match self {
+            Listener::Tcp(listener) => Ok(Box::new(listener.accept()?.0)),
+            Listener::Serial(listener) => Ok(Box::new(listener.accept()?)),
+        }
+    }
+}
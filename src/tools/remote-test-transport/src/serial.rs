@@ -0,0 +1,823 @@
+//! A transport over serial lines, or any other character device that carries
+//! a stream of bytes in both directions.
+//!
+//! The line carries frames of the form
+//!
+//! ```text
+//! a5 5a KIND SESSION(4) SEQ LEN(2) PAYLOAD(LEN) CRC32(4)
+//! ```
+//!
+//! with integers in big-endian, and a CRC-32 of everything from `KIND` to the
+//! end of the payload. Frames that are damaged are dropped, and the receiver
+//! looks for the start of the next frame.
+//!
+//! A client starts a connection by sending `OPEN` with a new, random session
+//! until the server answers with `OPEN_ACK`, at which point the server forgets
+//! about any previous connection. Each side then sends its data in `DATA`
+//! frames, one at a time, and sends a frame again until the other side
+//! acknowledges its sequence number with `ACK`. An empty `DATA` frame closes
+//! the connection in its direction.
+//!
+//! Only one connection can be open at a time, so clients on Unix take an
+//! exclusive lock on the device while they use it, and Windows only lets one
+//! process open a serial port at a time anyway.
+
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+#[cfg(not(windows))]
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read, Write};
+use std::path::Path;
+#[cfg(not(windows))]
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::Connection;
+
+#[cfg(test)]
+mod tests;
+
+const MAGIC: [u8; 2] = [0xa5, 0x5a];
+const HEADER_LEN: usize = 10;
+const CRC_LEN: usize = 4;
+const MAX_PAYLOAD: usize = 1024;
+
+const KIND_OPEN: u8 = 1;
+const KIND_OPEN_ACK: u8 = 2;
+const KIND_DATA: u8 = 3;
+const KIND_ACK: u8 = 4;
+
+/// How many times `OPEN` is sent before giving up on the server.
+const OPEN_ATTEMPTS: u32 = 20;
+/// How many times a `DATA` frame is sent before giving up on the other side.
+const DATA_ATTEMPTS: u32 = 50;
+/// How long a client waits for another client to close the port, on Windows.
+#[cfg(windows)]
+const PORT_BUSY_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[cfg(not(windows))]
+type Port = File;
+#[cfg(windows)]
+use windows::Port;
+
+/// Opens the serial port at `path` and connects to the server on the other
+/// end.
+pub fn connect(path: &Path, baud: u32) -> io::Result<SerialConnection> {
+    let port = open_port(path, baud)?;
+    lock_port(&port)?;
+    let link = Link::start(port, baud)?;
+    let session = loop {
+        let session = RandomState::new().build_hasher().finish() as u32;
+        if session != 0 {
+            break session;
+        }
+    };
+    link.lock().reset(session);
+
+    let open = encode(KIND_OPEN, session, 0, &[]);
+    for _ in 0..OPEN_ATTEMPTS {
+        link.send(&open)?;
+        let (state, _) =
+            link.wait(Some(link.retransmit), |state| state.opened || state.error.is_some());
+        if state.opened {
+            drop(state);
+            return Ok(SerialConnection::new(link, session));
+        }
+        if let Some(ref error) = state.error {
+            return Err(error.to_io());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("no answer from a server on {}", path.display()),
+    ))
+}
+
+/// Opens the serial port at `path` to accept the connections of clients.
+pub fn listen(path: &Path, baud: u32) -> io::Result<Listener> {
+    let port = open_port(path, baud)?;
+    Ok(Listener { link: Link::start(port, baud)? })
+}
+
+/// The server end of a serial line.
+pub struct Listener {
+    link: Arc<Link>,
+}
+
+impl Listener {
+    /// Waits for a client to open a connection. Opening it closes the
+    /// previous one.
+    pub fn accept(&self) -> io::Result<SerialConnection> {
+        let mut state = self.link.lock();
+        loop {
+            if let Some(session) = state.accepted.take() {
+                drop(state);
+                return Ok(SerialConnection::new(self.link.clone(), session));
+            }
+            if let Some(ref error) = state.error {
+                return Err(error.to_io());
+            }
+            state = self.link.changed.wait(state).unwrap();
+        }
+    }
+}
+
+/// A connection over a serial line. It is closed when the last of its handles
+/// is dropped.
+pub struct SerialConnection {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    link: Arc<Link>,
+    session: u32,
+    timeout: Mutex<Option<Duration>>,
+}
+
+impl SerialConnection {
+    fn new(link: Arc<Link>, session: u32) -> SerialConnection {
+        SerialConnection { inner: Arc::new(Inner { link, session, timeout: Mutex::new(None) }) }
+    }
+}
+
+impl Read for SerialConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let session = self.inner.session;
+        let timeout = *self.inner.timeout.lock().unwrap();
+        let (mut state, timed_out) = self.inner.link.wait(timeout, |state| {
+            state.session != Some(session)
+                || !state.rx.is_empty()
+                || state.rx_closed
+                || state.error.is_some()
+        });
+        if state.session != Some(session) {
+            // A new connection replaced this one.
+            return Ok(0);
+        }
+        if !state.rx.is_empty() {
+            let n = buf.len().min(state.rx.len());
+            for (dst, src) in buf.iter_mut().zip(state.rx.drain(..n)) {
+                *dst = src;
+            }
+            return Ok(n);
+        }
+        if state.rx_closed {
+            return Ok(0);
+        }
+        if let Some(ref error) = state.error {
+            return Err(error.to_io());
+        }
+        assert!(timed_out);
+        Err(io::Error::new(io::ErrorKind::TimedOut, "timed out reading from the serial line"))
+    }
+}
+
+impl Write for SerialConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let n = buf.len().min(MAX_PAYLOAD);
+        self.inner.send_data(&buf[..n], DATA_ATTEMPTS)?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Every write has been acknowledged by the other side already.
+        Ok(())
+    }
+}
+
+impl Connection for SerialConnection {
+    fn try_clone(&self) -> io::Result<Box<dyn Connection>> {
+        Ok(Box::new(SerialConnection { inner: self.inner.clone() }))
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        *self.inner.timeout.lock().unwrap() = timeout;
+        Ok(())
+    }
+}
+
+impl Inner {
+    fn send_data(&self, payload: &[u8], attempts: u32) -> io::Result<()> {
+        let link = &*self.link;
+        // Only one frame can be waiting for its acknowledgement at a time.
+        let _sending = link.sending.lock().unwrap();
+
+        let mut state = link.lock();
+        if state.session != Some(self.session) || state.tx_closed {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the connection was closed"));
+        }
+        let seq = state.tx_seq;
+        state.tx_acked = false;
+        state.tx_closed = payload.is_empty();
+        drop(state);
+
+        let frame = encode(KIND_DATA, self.session, seq, payload);
+        for _ in 0..attempts {
+            link.send(&frame)?;
+            let (mut state, _) = link.wait(Some(link.retransmit), |state| {
+                state.session != Some(self.session) || state.tx_acked || state.error.is_some()
+            });
+            if state.session != Some(self.session) {
+                return Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "the connection was replaced by a new one",
+                ));
+            }
+            if state.tx_acked {
+                state.tx_seq = state.tx_seq.wrapping_add(1);
+                return Ok(());
+            }
+            if let Some(ref error) = state.error {
+                return Err(error.to_io());
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "no acknowledgement from the other end of the serial line",
+        ))
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Tell the other side that nothing more is coming. If it closed the
+        // connection already it may have gone away too, so don't insist.
+        let state = self.link.lock();
+        if state.session == Some(self.session) && !state.tx_closed && state.error.is_none() {
+            let attempts = if state.rx_closed { 1 } else { DATA_ATTEMPTS };
+            drop(state);
+            let _ = self.send_data(&[], attempts);
+        }
+    }
+}
+
+/// The state shared by everything that uses a serial line.
+struct Link {
+    writer: Mutex<Port>,
+    sending: Mutex<()>,
+    state: Mutex<State>,
+    changed: Condvar,
+    /// How long to wait for an acknowledgement before sending a frame again.
+    retransmit: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    /// The session of the open connection.
+    session: Option<u32>,
+    /// A new session that `accept` hasn't returned yet, on the server.
+    accepted: Option<u32>,
+    /// Whether the server answered the `OPEN` of the session, on the client.
+    opened: bool,
+    rx: VecDeque<u8>,
+    rx_seq: u8,
+    rx_closed: bool,
+    tx_seq: u8,
+    tx_acked: bool,
+    tx_closed: bool,
+    /// Why the serial line can't be read anymore.
+    error: Option<LinkError>,
+}
+
+impl State {
+    fn reset(&mut self, session: u32) {
+        *self = State { session: Some(session), error: self.error.take(), ..State::default() };
+    }
+}
+
+struct LinkError {
+    kind: io::ErrorKind,
+    message: String,
+}
+
+impl LinkError {
+    fn to_io(&self) -> io::Error {
+        io::Error::new(self.kind, self.message.clone())
+    }
+}
+
+impl Link {
+    fn start(port: Port, baud: u32) -> io::Result<Arc<Link>> {
+        let reader = port.try_clone()?;
+        // Time it takes to send the largest frame, in milliseconds.
+        let frame_time = ((HEADER_LEN + MAX_PAYLOAD + CRC_LEN) * 10 * 1000) as u64 / baud as u64;
+        let link = Arc::new(Link {
+            writer: Mutex::new(port),
+            sending: Mutex::new(()),
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+            retransmit: Duration::from_millis(100 + 3 * frame_time),
+        });
+        let weak = Arc::downgrade(&link);
+        thread::spawn(move || receive(weak, reader));
+        Ok(link)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Waits until `done` returns true, or until `timeout` runs out. Returns
+    /// whether it ran out.
+    fn wait(
+        &self,
+        timeout: Option<Duration>,
+        mut done: impl FnMut(&State) -> bool,
+    ) -> (MutexGuard<'_, State>, bool) {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.lock();
+        while !done(&state) {
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return (state, true);
+                    }
+                    state = self.changed.wait_timeout(state, deadline - now).unwrap().0;
+                }
+                None => state = self.changed.wait(state).unwrap(),
+            }
+        }
+        (state, false)
+    }
+
+    fn send(&self, frame: &[u8]) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(frame)?;
+        writer.flush()
+    }
+
+    fn handle(&self, frame: Frame) {
+        let mut state = self.lock();
+        let current = state.session == Some(frame.session);
+        let reply = match frame.kind {
+            KIND_OPEN => {
+                // Either a new client, or the answer to its `OPEN` got lost.
+                if !current {
+                    state.reset(frame.session);
+                    state.accepted = Some(frame.session);
+                }
+                Some(encode(KIND_OPEN_ACK, frame.session, 0, &[]))
+            }
+            KIND_OPEN_ACK if current => {
+                state.opened = true;
+                None
+            }
+            KIND_DATA if current => {
+                // Frames that were received already are acknowledged again, as
+                // the acknowledgement must have been lost.
+                if frame.seq == state.rx_seq {
+                    state.rx_seq = state.rx_seq.wrapping_add(1);
+                    if frame.payload.is_empty() {
+                        state.rx_closed = true;
+                    } else {
+                        state.rx.extend(frame.payload);
+                    }
+                }
+                Some(encode(KIND_ACK, frame.session, frame.seq, &[]))
+            }
+            KIND_ACK if current => {
+                if frame.seq == state.tx_seq {
+                    state.tx_acked = true;
+                }
+                None
+            }
+            // Leftovers of an earlier connection.
+            _ => None,
+        };
+        self.changed.notify_all();
+        drop(state);
+
+        if let Some(reply) = reply {
+            if let Err(e) = self.send(&reply) {
+                self.fail(e);
+            }
+        }
+    }
+
+    fn fail(&self, error: io::Error) {
+        let mut state = self.lock();
+        if state.error.is_none() {
+            state.error = Some(LinkError { kind: error.kind(), message: error.to_string() });
+        }
+        self.changed.notify_all();
+    }
+}
+
+/// Reads frames from the serial line until the link is dropped.
+fn receive(link: Weak<Link>, mut port: Port) {
+    let mut frames = FrameReader::default();
+    let mut buf = [0; 4096];
+    loop {
+        let result = port.read(&mut buf);
+        let link = match link.upgrade() {
+            Some(link) => link,
+            None => return,
+        };
+        match result {
+            // Serial ports are set up by `configure_port` so that reads return
+            // after a while even if there is no data, so that this thread
+            // notices when the link is dropped.
+            Ok(0) => thread::sleep(Duration::from_millis(10)),
+            Ok(n) => {
+                frames.extend(&buf[..n]);
+                while let Some(frame) = frames.next_frame() {
+                    link.handle(frame);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return link.fail(e),
+        }
+    }
+}
+
+struct Frame {
+    kind: u8,
+    session: u32,
+    seq: u8,
+    payload: Vec<u8>,
+}
+
+fn encode(kind: u8, session: u32, seq: u8, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() <= MAX_PAYLOAD);
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len() + CRC_LEN);
+    frame.extend_from_slice(&MAGIC);
+    frame.push(kind);
+    frame.extend_from_slice(&session.to_be_bytes());
+    frame.push(seq);
+    frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    frame.extend_from_slice(payload);
+    let crc = crc32(&frame[MAGIC.len()..]);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    frame
+}
+
+/// Splits the bytes received from the serial line into frames.
+#[derive(Default)]
+struct FrameReader {
+    buf: Vec<u8>,
+}
+
+impl FrameReader {
+    fn extend(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            // Skip to the start of the next frame.
+            match self.buf.windows(2).position(|w| w == MAGIC) {
+                Some(start) => drop(self.buf.drain(..start)),
+                None => {
+                    let keep = if self.buf.last() == Some(&MAGIC[0]) { 1 } else { 0 };
+                    let len = self.buf.len();
+                    self.buf.drain(..len - keep);
+                    return None;
+                }
+            }
+            if self.buf.len() < HEADER_LEN {
+                return None;
+            }
+            let len = u16::from_be_bytes([self.buf[8], self.buf[9]]) as usize;
+            if len > MAX_PAYLOAD {
+                self.buf.drain(..1);
+                continue;
+            }
+            let end = HEADER_LEN + len;
+            if self.buf.len() < end + CRC_LEN {
+                return None;
+            }
+            let crc = &self.buf[end..end + CRC_LEN];
+            if crc32(&self.buf[MAGIC.len()..end]).to_be_bytes() != crc {
+                // Either the frame is damaged, or this wasn't the start of a
+                // frame after all.
+                self.buf.drain(..1);
+                continue;
+            }
+            let b = &self.buf;
+            let frame = Frame {
+                kind: b[2],
+                session: u32::from_be_bytes([b[3], b[4], b[5], b[6]]),
+                seq: b[7],
+                payload: b[HEADER_LEN..end].to_vec(),
+            };
+            self.buf.drain(..end + CRC_LEN);
+            return Some(frame);
+        }
+    }
+}
+
+/// CRC-32 as used by Ethernet and zlib.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Opens a serial port and sets it up to pass bytes through unchanged at
+/// `baud`.
+fn open_port(path: &Path, baud: u32) -> io::Result<Port> {
+    let port = open_device(path)?;
+    configure_port(&port, path, baud)?;
+    Ok(port)
+}
+
+#[cfg(not(windows))]
+fn open_device(path: &Path) -> io::Result<File> {
+    OpenOptions::new().read(true).write(true).open(path)
+}
+
+#[cfg(windows)]
+fn open_device(path: &Path) -> io::Result<Port> {
+    // `COM10` and above can only be opened through the device namespace.
+    let path = if path.to_str().map_or(false, |path| path.starts_with(r"\\")) {
+        path.to_owned()
+    } else {
+        let mut device = std::ffi::OsString::from(r"\\.\");
+        device.push(path);
+        std::path::PathBuf::from(device)
+    };
+    let start = Instant::now();
+    loop {
+        match Port::open(&path) {
+            // Another client is using the port, or it can't be opened at all.
+            Err(e) if e.raw_os_error() == Some(5) => {
+                if start.elapsed() >= PORT_BUSY_TIMEOUT {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("{} is busy or can't be opened: {}", path.display(), e),
+                    ));
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn configure_port(_port: &Port, path: &Path, baud: u32) -> io::Result<()> {
+    let device_flag = if cfg!(target_os = "linux") { "-F" } else { "-f" };
+    // Raw 8N1, without echo, and with reads that return after a tenth of a
+    // second without data.
+    let status = Command::new("stty")
+        .arg(device_flag)
+        .arg(path)
+        .arg(baud.to_string())
+        .args(&["raw", "-echo", "cs8", "-parenb", "-cstopb", "clocal", "min", "0", "time", "1"])
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to configure {} with stty: {}", path.display(), status),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn configure_port(port: &Port, path: &Path, baud: u32) -> io::Result<()> {
+    // Tools like `mode` can't be used, as they would have to open the port
+    // too, and it can only be open once.
+    port.configure(baud).map_err(|e| {
+        io::Error::new(e.kind(), format!("failed to configure {}: {}", path.display(), e))
+    })
+}
+
+#[cfg(not(windows))]
+fn lock_port(port: &Port) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    extern "C" {
+        fn flock(fd: i32, operation: i32) -> i32;
+    }
+    const LOCK_EX: i32 = 2;
+
+    loop {
+        if unsafe { flock(port.as_raw_fd(), LOCK_EX) } == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(windows)]
+fn lock_port(_port: &Port) -> io::Result<()> {
+    // The port can't be opened while another client has it open.
+    Ok(())
+}
+
+#[cfg(windows)]
+#[allow(non_snake_case)]
+mod windows {
+    use std::ffi::c_void;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::mem;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+    use std::ptr;
+
+    type HANDLE = *mut c_void;
+
+    const FILE_FLAG_OVERLAPPED: u32 = 0x40000000;
+    const ERROR_IO_PENDING: i32 = 997;
+    const MAXDWORD: u32 = 0xffffffff;
+    const NOPARITY: u8 = 0;
+    const ONESTOPBIT: u8 = 0;
+    /// `fBinary`, `DTR_CONTROL_ENABLE` and `RTS_CONTROL_ENABLE`, without any
+    /// flow control.
+    const DCB_FLAGS: u32 = 0x1 | 0x1 << 4 | 0x1 << 12;
+
+    #[repr(C)]
+    struct DCB {
+        DCBlength: u32,
+        BaudRate: u32,
+        flags: u32,
+        wReserved: u16,
+        XonLim: u16,
+        XoffLim: u16,
+        ByteSize: u8,
+        Parity: u8,
+        StopBits: u8,
+        XonChar: i8,
+        XoffChar: i8,
+        ErrorChar: i8,
+        EofChar: i8,
+        EvtChar: i8,
+        wReserved1: u16,
+    }
+
+    #[repr(C)]
+    struct COMMTIMEOUTS {
+        ReadIntervalTimeout: u32,
+        ReadTotalTimeoutMultiplier: u32,
+        ReadTotalTimeoutConstant: u32,
+        WriteTotalTimeoutMultiplier: u32,
+        WriteTotalTimeoutConstant: u32,
+    }
+
+    #[repr(C)]
+    struct OVERLAPPED {
+        Internal: usize,
+        InternalHigh: usize,
+        Offset: u32,
+        OffsetHigh: u32,
+        hEvent: HANDLE,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCommState(hFile: HANDLE, lpDCB: *mut DCB) -> i32;
+        fn SetCommState(hFile: HANDLE, lpDCB: *const DCB) -> i32;
+        fn SetCommTimeouts(hFile: HANDLE, lpCommTimeouts: *const COMMTIMEOUTS) -> i32;
+        fn CreateEventA(
+            lpEventAttributes: *mut c_void,
+            bManualReset: i32,
+            bInitialState: i32,
+            lpName: *const i8,
+        ) -> HANDLE;
+        fn ReadFile(
+            hFile: HANDLE,
+            lpBuffer: *mut c_void,
+            nNumberOfBytesToRead: u32,
+            lpNumberOfBytesRead: *mut u32,
+            lpOverlapped: *mut OVERLAPPED,
+        ) -> i32;
+        fn WriteFile(
+            hFile: HANDLE,
+            lpBuffer: *const c_void,
+            nNumberOfBytesToWrite: u32,
+            lpNumberOfBytesWritten: *mut u32,
+            lpOverlapped: *mut OVERLAPPED,
+        ) -> i32;
+        fn GetOverlappedResult(
+            hFile: HANDLE,
+            lpOverlapped: *mut OVERLAPPED,
+            lpNumberOfBytesTransferred: *mut u32,
+            bWait: i32,
+        ) -> i32;
+        fn CloseHandle(hObject: HANDLE) -> i32;
+    }
+
+    /// A serial port opened for overlapped I/O. Synchronous reads and writes
+    /// through the same port are carried out one at a time, so a read that
+    /// waits for data would hold up the acknowledgements that are sent while
+    /// it waits. Windows 9x supports overlapped I/O on serial ports too.
+    pub struct Port {
+        file: File,
+    }
+
+    impl Port {
+        pub fn open(path: &Path) -> io::Result<Port> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(FILE_FLAG_OVERLAPPED)
+                .open(path)?;
+            Ok(Port { file })
+        }
+
+        pub fn try_clone(&self) -> io::Result<Port> {
+            Ok(Port { file: self.file.try_clone()? })
+        }
+
+        /// Sets the port up for raw 8N1 at `baud`, with reads that return as
+        /// soon as there is data, or after a tenth of a second without any.
+        pub fn configure(&self, baud: u32) -> io::Result<()> {
+            let handle = self.file.as_raw_handle() as HANDLE;
+            unsafe {
+                let mut dcb: DCB = mem::zeroed();
+                dcb.DCBlength = mem::size_of::<DCB>() as u32;
+                if GetCommState(handle, &mut dcb) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                dcb.BaudRate = baud;
+                dcb.flags = DCB_FLAGS;
+                dcb.ByteSize = 8;
+                dcb.Parity = NOPARITY;
+                dcb.StopBits = ONESTOPBIT;
+                if SetCommState(handle, &dcb) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let timeouts = COMMTIMEOUTS {
+                    ReadIntervalTimeout: MAXDWORD,
+                    ReadTotalTimeoutMultiplier: MAXDWORD,
+                    ReadTotalTimeoutConstant: 100,
+                    WriteTotalTimeoutMultiplier: 0,
+                    WriteTotalTimeoutConstant: 0,
+                };
+                if SetCommTimeouts(handle, &timeouts) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        }
+
+        /// Starts an overlapped `ReadFile` or `WriteFile` with `start`, and
+        /// waits for it to complete.
+        fn transfer(
+            &self,
+            start: impl FnOnce(HANDLE, *mut OVERLAPPED) -> i32,
+        ) -> io::Result<usize> {
+            let handle = self.file.as_raw_handle() as HANDLE;
+            unsafe {
+                let event = CreateEventA(ptr::null_mut(), 1, 0, ptr::null());
+                if event.is_null() {
+                    return Err(io::Error::last_os_error());
+                }
+                let mut overlapped: OVERLAPPED = mem::zeroed();
+                overlapped.hEvent = event;
+                let mut n = 0;
+                let started = start(handle, &mut overlapped) != 0
+                    || io::Error::last_os_error().raw_os_error() == Some(ERROR_IO_PENDING);
+                let result =
+                    if started && GetOverlappedResult(handle, &mut overlapped, &mut n, 1) != 0 {
+                        Ok(n as usize)
+                    } else {
+                        Err(io::Error::last_os_error())
+                    };
+                CloseHandle(event);
+                result
+            }
+        }
+    }
+
+    impl Read for Port {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(u32::MAX as usize) as u32;
+            self.transfer(|handle, overlapped| unsafe {
+                ReadFile(handle, buf.as_mut_ptr().cast(), len, ptr::null_mut(), overlapped)
+            })
+        }
+    }
+
+    impl Write for Port {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(u32::MAX as usize) as u32;
+            self.transfer(|handle, overlapped| unsafe {
+                WriteFile(handle, buf.as_ptr().cast(), len, ptr::null_mut(), overlapped)
+            })
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            // A write completes once the driver has taken the data.
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use super::*;
+
+#[test]
+fn crc32_check_value() {
+    assert_eq!(crc32(b"123456789"), 0xcbf43926);
+}
+
+#[test]
+fn frames_survive_garbage_and_damage() {
+    let first = encode(KIND_DATA, 7, 1, b"hello");
+    let mut damaged = encode(KIND_DATA, 7, 2, b"lost");
+    damaged[12] ^= 0x20;
+    let last = encode(KIND_ACK, 7, 3, &[]);
+
+    let mut reader = FrameReader::default();
+    let mut stream = vec![0x00, 0xa5, 0xa5];
+    stream.extend_from_slice(&first);
+    stream.extend_from_slice(&damaged);
+    stream.extend_from_slice(&[0xa5, 0x5a, 0xff, 0xff]);
+    stream.extend_from_slice(&last);
+
+    // Feed the bytes one at a time, as a slow serial line would.
+    let mut frames = Vec::new();
+    for byte in stream {
+        reader.extend(&[byte]);
+        while let Some(frame) = reader.next_frame() {
+            frames.push(frame);
+        }
+    }
+    assert_eq!(frames.len(), 2);
+    assert_eq!((frames[0].kind, frames[0].session, frames[0].seq), (KIND_DATA, 7, 1));
+    assert_eq!(frames[0].payload, b"hello");
+    assert_eq!((frames[1].kind, frames[1].seq), (KIND_ACK, 3));
+    assert!(frames[1].payload.is_empty());
+}
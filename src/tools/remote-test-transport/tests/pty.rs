@@ -0,0 +1,134 @@
+//! Runs connections over pseudo-terminals, with a relay in between that
+//! plays the role of the cable and can damage the data on the way.
+
+#![cfg(target_os = "linux")]
+
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::thread;
+
+use remote_test_transport::{connect, Address, Listener};
+
+extern "C" {
+    fn grantpt(fd: i32) -> i32;
+    fn unlockpt(fd: i32) -> i32;
+    fn ptsname_r(fd: i32, buf: *mut u8, buflen: usize) -> i32;
+}
+
+/// Opens a new pseudo-terminal, returning its master and the path of its
+/// slave, which is what the transport opens.
+fn open_pty() -> (File, PathBuf) {
+    let master = OpenOptions::new().read(true).write(true).open("/dev/ptmx").unwrap();
+    let fd = master.as_raw_fd();
+    let mut name = [0u8; 128];
+    unsafe {
+        assert_eq!(grantpt(fd), 0);
+        assert_eq!(unlockpt(fd), 0);
+        assert_eq!(ptsname_r(fd, name.as_mut_ptr(), name.len()), 0);
+    }
+    let len = name.iter().position(|&b| b == 0).unwrap();
+    let path = PathBuf::from(String::from_utf8(name[..len].to_vec()).unwrap());
+    // Reading from the master fails while the slave isn't open, so keep it
+    // open for as long as the test runs.
+    std::mem::forget(File::open(&path).unwrap());
+    (master, path)
+}
+
+/// Copies everything from `from` to `to`, passing every chunk through
+/// `damage` first.
+fn relay(mut from: File, mut to: File, mut damage: impl FnMut(&mut Vec<u8>) + Send + 'static) {
+    thread::spawn(move || {
+        let mut buf = [0; 4096];
+        loop {
+            let n = match from.read(&mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => n,
+            };
+            let mut chunk = buf[..n].to_vec();
+            damage(&mut chunk);
+            if to.write_all(&chunk).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Returns the addresses of the server and the client ends of a line.
+fn line(damage: impl FnMut(&mut Vec<u8>) + Clone + Send + 'static) -> (Address, Address) {
+    let (server_master, server_path) = open_pty();
+    let (client_master, client_path) = open_pty();
+    relay(server_master.try_clone().unwrap(), client_master.try_clone().unwrap(), damage.clone());
+    relay(client_master, server_master, damage);
+    (
+        Address::parse(&format!("serial:{}", server_path.display())).unwrap(),
+        Address::parse(&format!("serial:{},115200", client_path.display())).unwrap(),
+    )
+}
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+}
+
+/// Has the server echo data back, and checks that all of it arrives.
+fn echo(server: Address, client: Address, len: usize) {
+    let listener = Listener::bind(&server).unwrap();
+    let echo = thread::spawn(move || {
+        let mut conn = listener.accept().unwrap();
+        let mut len = [0; 4];
+        conn.read_exact(&mut len).unwrap();
+        let mut buf = vec![0; u32::from_be_bytes(len) as usize];
+        conn.read_exact(&mut buf).unwrap();
+        conn.write_all(&buf).unwrap();
+        // The client closes the connection once it has everything.
+        assert_eq!(conn.read(&mut len).unwrap(), 0);
+    });
+
+    let mut conn = connect(&client).unwrap();
+    let sent = data(len);
+    conn.write_all(&(len as u32).to_be_bytes()).unwrap();
+    conn.write_all(&sent).unwrap();
+    let mut received = vec![0; len];
+    conn.read_exact(&mut received).unwrap();
+    assert!(received == sent);
+    drop(conn);
+    echo.join().unwrap();
+}
+
+#[test]
+fn parse_addresses() {
+    assert_eq!(Address::parse("10.0.2.15:12345"), Ok(Address::Tcp("10.0.2.15:12345".into())));
+    assert_eq!(
+        Address::parse("serial:/dev/ttyS0"),
+        Ok(Address::Serial { path: "/dev/ttyS0".into(), baud: 115200 })
+    );
+    assert_eq!(
+        Address::parse("serial:COM3,9600"),
+        Ok(Address::Serial { path: "COM3".into(), baud: 9600 })
+    );
+    assert!(Address::parse("serial:/dev/ttyS0,fast").is_err());
+    assert!(Address::parse("serial:").is_err());
+}
+
+#[test]
+fn clean_line() {
+    let (server, client) = line(|_| {});
+    echo(server, client, 100_000);
+}
+
+#[test]
+fn noisy_line() {
+    // Flip a bit every now and then, and lose whole chunks sometimes.
+    let mut count = 0usize;
+    let (server, client) = line(move |chunk: &mut Vec<u8>| {
+        count += 1;
+        if count % 17 == 0 {
+            chunk.clear();
+        } else if count % 11 == 0 {
+            let i = count % chunk.len();
+            chunk[i] ^= 0x10;
+        }
+    });
+    echo(server, client, 8_000);
+}